@@ -0,0 +1,198 @@
+//! Times loading a scrape and its views from a large generated database, next to the per-row
+//! loaders they replaced, which made a query for every repo, contributor and org they touched.
+//!
+//! Run with `cargo bench --bench scrape_loading`, optionally followed by the number of
+//! contributors to generate (default 2000).
+
+use std::time::{Duration, Instant};
+
+use chrono::{TimeZone, Utc};
+use org_pulse::db::{self, RepoScrape, Scrape};
+use org_pulse::demo::{generate_demo, DemoOptions};
+use sqlx::sqlite::SqlitePoolOptions;
+
+const RUNS: u32 = 10;
+
+/// Awaits `$load` `RUNS` times and returns the mean time it took
+macro_rules! mean {
+    ($load:expr) => {{
+        let started = Instant::now();
+        for _ in 0..RUNS {
+            $load.await?;
+        }
+        started.elapsed() / RUNS
+    }};
+}
+
+/// Prints a loader's mean time, and how it compares to the per-row baseline when there is one
+fn report(name: &str, loaded: Duration, baseline: Option<(Duration, u64)>) {
+    match baseline {
+        Some((baseline, queries)) => println!(
+            "{name:<24} {loaded:>10.2?}   per-row {baseline:>10.2?} in {queries:>6} queries   {:>6.1}x",
+            baseline.as_secs_f64() / loaded.as_secs_f64(),
+        ),
+        None => println!("{name:<24} {loaded:>10.2?}"),
+    }
+}
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    // `cargo bench` passes `--bench`, so take the first number given
+    let contributors = std::env::args().skip(1).find_map(|arg| arg.parse().ok()).unwrap_or(2000);
+
+    let pool = SqlitePoolOptions::new().max_connections(1).connect("sqlite::memory:").await?;
+    sqlx::migrate!().run(&pool).await?;
+    let options = DemoOptions {
+        orgs: 8,
+        repos_per_org: 25,
+        contributors,
+        weeks: 4,
+        end: Utc.with_ymd_and_hms(2025, 7, 7, 0, 0, 0).unwrap(),
+        ..Default::default()
+    };
+    let started = Instant::now();
+    let summary = generate_demo(&pool, &options).await?;
+    println!("{summary} in {:.1?}", started.elapsed());
+
+    let mut conn = pool.acquire().await?;
+    let latest = Scrape::get_latest(&mut conn).await?.expect("demo data has scrapes");
+    let scrape = Scrape::get(&mut conn, &latest.id).await?;
+    let contributor_scrapes: usize = scrape.repo_scrapes.iter().map(|rs| rs.contributor_scrapes.len()).sum();
+    println!("Latest scrape: {} repo scrapes, {} contributor scrapes\n", scrape.repo_scrapes.len(), contributor_scrapes);
+    let busiest = scrape.repo_scrapes.iter().max_by_key(|rs| rs.contributor_scrapes.len()).expect("scrape has repos");
+
+    // The baselines load the same rows, so the comparison is like for like
+    let mut queries = 0;
+    assert_eq!(per_row::scrape(&mut conn, latest.id, &mut queries).await?, contributor_scrapes);
+    let baseline = mean!(per_row::scrape(&mut conn, latest.id, &mut 0));
+    report("Scrape::get", mean!(Scrape::get(&mut conn, &latest.id)), Some((baseline, queries)));
+
+    let mut queries = 0;
+    assert_eq!(per_row::repo_scrape(&mut conn, busiest.id, &mut queries).await?, busiest.contributor_scrapes.len());
+    let baseline = mean!(per_row::repo_scrape(&mut conn, busiest.id, &mut 0));
+    report("RepoScrape::get", mean!(RepoScrape::get(&mut conn, &busiest.id)), Some((baseline, queries)));
+
+    let mut queries = 0;
+    let orgs = per_row::contributor_orgs(&mut conn, latest.id, &mut queries).await?;
+    assert_eq!(orgs, per_row::batched_contributor_orgs(&mut conn, latest.id).await?);
+    let baseline = mean!(per_row::contributor_orgs(&mut conn, latest.id, &mut 0));
+    report("contributor orgs", mean!(per_row::batched_contributor_orgs(&mut conn, latest.id)), Some((baseline, queries)));
+
+    report("get_contributor_stats", mean!(db::get_contributor_stats(&mut conn, latest.id)), None);
+    report("get_org_stats", mean!(db::get_org_stats(&mut conn, latest.id)), None);
+    report("get_repo_stats", mean!(db::get_repo_stats(&mut conn, latest.id)), None);
+    Ok(())
+}
+
+/// The loaders as they were before repos, orgs and contributors were joined in, counting the
+/// queries they make
+mod per_row {
+    use std::collections::BTreeMap;
+
+    use anyhow::Result;
+    use org_pulse::db::{Contributor, Org};
+    use sqlx::{query, query_as, Row, SqliteConnection};
+
+    /// What `Scrape::get` loaded, returning the number of contributor scrapes
+    pub async fn scrape(conn: &mut SqliteConnection, scrape_id: i64, queries: &mut u64) -> Result<usize> {
+        let repo_scrapes: Vec<(i64, i64, i64)> = query_as("
+            SELECT id, org_id, repo_id FROM repo_scrapes WHERE scrape_id = $1;
+        ").bind(scrape_id).fetch_all(&mut *conn).await?;
+        *queries += 1;
+
+        let mut contributor_scrapes = 0;
+        for (id, org_id, repo_id) in repo_scrapes {
+            Org::get(conn, &org_id).await?;
+            *queries += 1;
+            repo(conn, repo_id, queries).await?;
+            contributor_scrapes += contributors(conn, id, queries).await?;
+        }
+        Ok(contributor_scrapes)
+    }
+
+    /// What `RepoScrape::get` loaded, returning the number of contributor scrapes
+    pub async fn repo_scrape(conn: &mut SqliteConnection, id: i64, queries: &mut u64) -> Result<usize> {
+        let (org_id, repo_id): (i64, i64) = query_as("
+            SELECT org_id, repo_id FROM repo_scrapes WHERE id = $1;
+        ").bind(id).fetch_one(&mut *conn).await?;
+        *queries += 1;
+        Org::get(conn, &org_id).await?;
+        *queries += 1;
+        repo(conn, repo_id, queries).await?;
+        contributors(conn, id, queries).await
+    }
+
+    /// The orgs `get_contributor_stats` looked up for each contributor, one query apiece
+    pub async fn contributor_orgs(conn: &mut SqliteConnection, scrape_id: i64, queries: &mut u64) -> Result<BTreeMap<String, Vec<String>>> {
+        let usernames: Vec<(String,)> = query_as("
+            SELECT DISTINCT c.username
+            FROM contributors c
+            JOIN contributor_scrapes cs ON c.id = cs.contributor_id
+            JOIN repo_scrapes rs ON cs.repo_scrape_id = rs.id
+            WHERE rs.scrape_id = $1;
+        ").bind(scrape_id).fetch_all(&mut *conn).await?;
+        *queries += 1;
+
+        let mut orgs = BTreeMap::new();
+        for (username,) in usernames {
+            let org_names: Vec<(String,)> = query_as("
+                SELECT DISTINCT o.name
+                FROM contributors c
+                JOIN contributor_scrapes cs ON c.id = cs.contributor_id
+                JOIN repo_scrapes rs ON cs.repo_scrape_id = rs.id
+                JOIN orgs o ON rs.org_id = o.id
+                WHERE c.username = $1 AND rs.scrape_id = $2
+                ORDER BY o.name;
+            ").bind(&username).bind(scrape_id).fetch_all(&mut *conn).await?;
+            *queries += 1;
+            orgs.insert(username, org_names.into_iter().map(|(name,)| name).collect());
+        }
+        Ok(orgs)
+    }
+
+    /// The single query `get_contributor_stats` now makes for the same orgs
+    pub async fn batched_contributor_orgs(conn: &mut SqliteConnection, scrape_id: i64) -> Result<BTreeMap<String, Vec<String>>> {
+        let rows: Vec<(String, String)> = query_as("
+            SELECT DISTINCT c.username, o.name
+            FROM contributors c
+            JOIN contributor_scrapes cs ON c.id = cs.contributor_id
+            JOIN repo_scrapes rs ON cs.repo_scrape_id = rs.id
+            JOIN orgs o ON rs.org_id = o.id
+            WHERE rs.scrape_id = $1
+            ORDER BY o.name;
+        ").bind(scrape_id).fetch_all(&mut *conn).await?;
+
+        let mut orgs: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        for (username, org_name) in rows {
+            orgs.entry(username).or_default().push(org_name);
+        }
+        Ok(orgs)
+    }
+
+    /// `Repo::get`, which loaded the repo and then its org
+    async fn repo(conn: &mut SqliteConnection, id: i64, queries: &mut u64) -> Result<()> {
+        let row = query("
+            SELECT r.id, r.name, r.org_id, r.language, r.topics, r.fork, r.archived, r.private,
+                   r.visibility, r.default_branch, r.size, r.created_at, r.pushed_at
+            FROM repos r
+            WHERE r.id = $1
+            LIMIT 1;
+        ").bind(id).fetch_one(&mut *conn).await?;
+        Org::get(conn, &row.get("org_id")).await?;
+        *queries += 2;
+        Ok(())
+    }
+
+    /// A repo scrape's contributor scrapes, with a query for each contributor
+    async fn contributors(conn: &mut SqliteConnection, repo_scrape_id: i64, queries: &mut u64) -> Result<usize> {
+        let rows: Vec<(i64, i64)> = query_as("
+            SELECT id, contributor_id FROM contributor_scrapes WHERE repo_scrape_id = $1;
+        ").bind(repo_scrape_id).fetch_all(&mut *conn).await?;
+        *queries += 1;
+        for (_, contributor_id) in &rows {
+            Contributor::get(conn, contributor_id).await?;
+            *queries += 1;
+        }
+        Ok(rows.len())
+    }
+}
@@ -0,0 +1,16 @@
+CREATE TABLE IF NOT EXISTS commits (
+  id INTEGER PRIMARY KEY NOT NULL,
+  repo_id INTEGER NOT NULL REFERENCES repos(id) ON DELETE CASCADE,
+  sha TEXT NOT NULL,
+  author_id INTEGER NOT NULL REFERENCES contributors(id) ON DELETE CASCADE,
+  committer_id INTEGER REFERENCES contributors(id) ON DELETE SET NULL,
+  authored_at DATETIME,
+  committed_at DATETIME,
+  additions INTEGER NOT NULL DEFAULT 0,
+  deletions INTEGER NOT NULL DEFAULT 0,
+  message_summary TEXT NOT NULL DEFAULT '',
+  UNIQUE(repo_id, sha)
+);
+
+CREATE INDEX IF NOT EXISTS commits_repo_committed_at ON commits(repo_id, committed_at);
+CREATE INDEX IF NOT EXISTS commits_author ON commits(author_id);
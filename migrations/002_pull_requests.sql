@@ -0,0 +1,20 @@
+CREATE TABLE IF NOT EXISTS pull_requests (
+  id INTEGER PRIMARY KEY NOT NULL,
+  repo_id INTEGER NOT NULL REFERENCES repos(id) ON DELETE CASCADE,
+  number INTEGER NOT NULL,
+  title TEXT NOT NULL DEFAULT '',
+  author_id INTEGER NOT NULL REFERENCES contributors(id) ON DELETE CASCADE,
+  state TEXT NOT NULL DEFAULT 'open',
+  created_at DATETIME,
+  updated_at DATETIME,
+  closed_at DATETIME,
+  merged_at DATETIME,
+  additions INTEGER NOT NULL DEFAULT 0,
+  deletions INTEGER NOT NULL DEFAULT 0,
+  changed_files INTEGER NOT NULL DEFAULT 0,
+  base_branch TEXT NOT NULL DEFAULT '',
+  UNIQUE(repo_id, number)
+);
+
+CREATE INDEX IF NOT EXISTS pull_requests_repo_merged_at ON pull_requests(repo_id, merged_at);
+CREATE INDEX IF NOT EXISTS pull_requests_author ON pull_requests(author_id);
@@ -0,0 +1,10 @@
+ALTER TABLE repos ADD COLUMN language TEXT;
+ALTER TABLE repos ADD COLUMN topics TEXT NOT NULL DEFAULT '';
+ALTER TABLE repos ADD COLUMN fork BOOLEAN NOT NULL DEFAULT 0;
+ALTER TABLE repos ADD COLUMN archived BOOLEAN NOT NULL DEFAULT 0;
+ALTER TABLE repos ADD COLUMN private BOOLEAN NOT NULL DEFAULT 0;
+ALTER TABLE repos ADD COLUMN visibility TEXT;
+ALTER TABLE repos ADD COLUMN default_branch TEXT;
+ALTER TABLE repos ADD COLUMN size INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE repos ADD COLUMN created_at DATETIME;
+ALTER TABLE repos ADD COLUMN pushed_at DATETIME;
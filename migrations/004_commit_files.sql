@@ -0,0 +1,11 @@
+CREATE TABLE IF NOT EXISTS commit_files (
+  id INTEGER PRIMARY KEY NOT NULL,
+  commit_id INTEGER NOT NULL REFERENCES commits(id) ON DELETE CASCADE,
+  path TEXT NOT NULL,
+  directory TEXT NOT NULL DEFAULT '',
+  additions INTEGER NOT NULL DEFAULT 0,
+  deletions INTEGER NOT NULL DEFAULT 0,
+  UNIQUE(commit_id, path)
+);
+
+CREATE INDEX IF NOT EXISTS commit_files_directory ON commit_files(directory);
@@ -0,0 +1,14 @@
+ALTER TABLE commit_files ADD COLUMN excluded BOOLEAN NOT NULL DEFAULT 0;
+
+CREATE TABLE IF NOT EXISTS pull_request_files (
+  id INTEGER PRIMARY KEY NOT NULL,
+  pull_request_id INTEGER NOT NULL REFERENCES pull_requests(id) ON DELETE CASCADE,
+  path TEXT NOT NULL,
+  additions INTEGER NOT NULL DEFAULT 0,
+  deletions INTEGER NOT NULL DEFAULT 0,
+  excluded BOOLEAN NOT NULL DEFAULT 0,
+  UNIQUE(pull_request_id, path)
+);
+
+ALTER TABLE repo_scrapes ADD COLUMN excluded_lines INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE contributor_scrapes ADD COLUMN excluded_lines INTEGER NOT NULL DEFAULT 0;
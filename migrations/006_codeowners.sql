@@ -0,0 +1,21 @@
+CREATE TABLE IF NOT EXISTS codeowners_rules (
+  id INTEGER PRIMARY KEY NOT NULL,
+  repo_id INTEGER NOT NULL REFERENCES repos(id) ON DELETE CASCADE,
+  position INTEGER NOT NULL,
+  pattern TEXT NOT NULL,
+  owners TEXT NOT NULL DEFAULT '',
+  UNIQUE(repo_id, position)
+);
+
+CREATE TABLE IF NOT EXISTS pull_request_reviews (
+  id INTEGER PRIMARY KEY NOT NULL,
+  pull_request_id INTEGER NOT NULL REFERENCES pull_requests(id) ON DELETE CASCADE,
+  github_id INTEGER NOT NULL,
+  reviewer_id INTEGER NOT NULL REFERENCES contributors(id) ON DELETE CASCADE,
+  state TEXT NOT NULL DEFAULT '',
+  submitted_at DATETIME,
+  UNIQUE(github_id)
+);
+
+CREATE INDEX IF NOT EXISTS pull_request_reviews_pull_request ON pull_request_reviews(pull_request_id);
+CREATE INDEX IF NOT EXISTS pull_request_reviews_reviewer ON pull_request_reviews(reviewer_id);
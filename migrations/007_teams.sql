@@ -0,0 +1,16 @@
+CREATE TABLE IF NOT EXISTS teams (
+  id INTEGER PRIMARY KEY NOT NULL,
+  org_id INTEGER NOT NULL REFERENCES orgs(id) ON DELETE CASCADE,
+  slug TEXT NOT NULL,
+  name TEXT NOT NULL,
+  UNIQUE(org_id, slug)
+);
+
+CREATE TABLE IF NOT EXISTS team_members (
+  id INTEGER PRIMARY KEY NOT NULL,
+  team_id INTEGER NOT NULL REFERENCES teams(id) ON DELETE CASCADE,
+  contributor_id INTEGER NOT NULL REFERENCES contributors(id) ON DELETE CASCADE,
+  UNIQUE(team_id, contributor_id)
+);
+
+CREATE INDEX IF NOT EXISTS team_members_contributor ON team_members(contributor_id);
@@ -0,0 +1,20 @@
+CREATE TABLE IF NOT EXISTS config_groups (
+  id INTEGER PRIMARY KEY NOT NULL,
+  kind TEXT NOT NULL,
+  name TEXT NOT NULL,
+  UNIQUE(kind, name)
+);
+
+CREATE TABLE IF NOT EXISTS config_group_members (
+  id INTEGER PRIMARY KEY NOT NULL,
+  group_id INTEGER NOT NULL REFERENCES config_groups(id) ON DELETE CASCADE,
+  contributor_id INTEGER NOT NULL REFERENCES contributors(id) ON DELETE CASCADE,
+  UNIQUE(group_id, contributor_id)
+);
+
+CREATE TABLE IF NOT EXISTS config_group_repos (
+  id INTEGER PRIMARY KEY NOT NULL,
+  group_id INTEGER NOT NULL REFERENCES config_groups(id) ON DELETE CASCADE,
+  repo_id INTEGER NOT NULL REFERENCES repos(id) ON DELETE CASCADE,
+  UNIQUE(group_id, repo_id)
+);
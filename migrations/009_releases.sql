@@ -0,0 +1,46 @@
+CREATE TABLE IF NOT EXISTS releases (
+  id INTEGER PRIMARY KEY NOT NULL,
+  repo_id INTEGER NOT NULL REFERENCES repos(id) ON DELETE CASCADE,
+  tag_name TEXT NOT NULL,
+  name TEXT NOT NULL DEFAULT '',
+  target_commitish TEXT NOT NULL DEFAULT '',
+  draft BOOLEAN NOT NULL DEFAULT 0,
+  prerelease BOOLEAN NOT NULL DEFAULT 0,
+  created_at DATETIME,
+  published_at DATETIME,
+  UNIQUE(repo_id, tag_name)
+);
+
+CREATE TABLE IF NOT EXISTS tags (
+  id INTEGER PRIMARY KEY NOT NULL,
+  repo_id INTEGER NOT NULL REFERENCES repos(id) ON DELETE CASCADE,
+  name TEXT NOT NULL,
+  sha TEXT NOT NULL,
+  committed_at DATETIME,
+  UNIQUE(repo_id, name)
+);
+
+CREATE TABLE IF NOT EXISTS deployments (
+  id INTEGER PRIMARY KEY NOT NULL,
+  repo_id INTEGER NOT NULL REFERENCES repos(id) ON DELETE CASCADE,
+  github_id INTEGER NOT NULL,
+  environment TEXT NOT NULL DEFAULT '',
+  sha TEXT NOT NULL DEFAULT '',
+  git_ref TEXT NOT NULL DEFAULT '',
+  created_at DATETIME,
+  UNIQUE(github_id)
+);
+
+CREATE INDEX IF NOT EXISTS releases_repo_published_at ON releases(repo_id, published_at);
+CREATE INDEX IF NOT EXISTS deployments_repo_created_at ON deployments(repo_id, created_at);
+
+-- Published, non-prerelease releases, plus tags that never got a GitHub release
+CREATE VIEW IF NOT EXISTS release_events AS
+  SELECT repo_id, tag_name, published_at as released_at
+  FROM releases
+  WHERE NOT draft AND NOT prerelease AND published_at IS NOT NULL
+  UNION ALL
+  SELECT t.repo_id, t.name as tag_name, t.committed_at as released_at
+  FROM tags t
+  WHERE t.committed_at IS NOT NULL
+    AND NOT EXISTS (SELECT 1 FROM releases rl WHERE rl.repo_id = t.repo_id AND rl.tag_name = t.name);
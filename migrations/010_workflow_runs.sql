@@ -0,0 +1,19 @@
+CREATE TABLE IF NOT EXISTS workflow_runs (
+  id INTEGER PRIMARY KEY NOT NULL,
+  repo_id INTEGER NOT NULL REFERENCES repos(id) ON DELETE CASCADE,
+  github_id INTEGER NOT NULL,
+  workflow_id INTEGER NOT NULL,
+  name TEXT NOT NULL DEFAULT '',
+  head_branch TEXT NOT NULL DEFAULT '',
+  head_sha TEXT NOT NULL DEFAULT '',
+  event TEXT NOT NULL DEFAULT '',
+  status TEXT NOT NULL DEFAULT '',
+  conclusion TEXT,
+  run_attempt INTEGER NOT NULL DEFAULT 1,
+  run_started_at DATETIME,
+  created_at DATETIME,
+  updated_at DATETIME,
+  UNIQUE(github_id)
+);
+
+CREATE INDEX IF NOT EXISTS workflow_runs_repo_created_at ON workflow_runs(repo_id, created_at);
@@ -0,0 +1,3 @@
+-- Conventional Commit type parsed from the commit summary / PR title, NULL when it doesn't follow the convention
+ALTER TABLE commits ADD COLUMN change_type TEXT;
+ALTER TABLE pull_requests ADD COLUMN change_type TEXT;
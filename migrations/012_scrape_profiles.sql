@@ -0,0 +1,2 @@
+-- Name of the config profile a scrape ran with, NULL for scrapes of everything
+ALTER TABLE scrapes ADD COLUMN profile TEXT;
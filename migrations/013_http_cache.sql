@@ -0,0 +1,12 @@
+-- GitHub API responses with validators, replayed when a conditional request comes back 304
+CREATE TABLE IF NOT EXISTS http_cache (
+  url TEXT NOT NULL,
+  accept TEXT NOT NULL DEFAULT '',
+  etag TEXT,
+  last_modified TEXT,
+  -- JSON array of [name, value] pairs, e.g. Content-Type and the Link header pagination follows
+  headers TEXT NOT NULL DEFAULT '[]',
+  body BLOB NOT NULL,
+  fetched_at DATETIME NOT NULL,
+  PRIMARY KEY (url, accept)
+);
@@ -0,0 +1,4 @@
+-- Scrape loaders fetch every repo and contributor counter of a scrape in one query
+CREATE INDEX IF NOT EXISTS repo_scrapes_scrape ON repo_scrapes(scrape_id);
+CREATE INDEX IF NOT EXISTS contributor_scrapes_repo_scrape ON contributor_scrapes(repo_scrape_id);
+CREATE INDEX IF NOT EXISTS contributor_scrapes_contributor ON contributor_scrapes(contributor_id);
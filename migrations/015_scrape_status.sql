@@ -0,0 +1,2 @@
+-- 'running' while a scrape is being written, then 'complete' or 'failed'. Earlier scrapes count as complete.
+ALTER TABLE scrapes ADD COLUMN status TEXT NOT NULL DEFAULT 'complete';
@@ -3,6 +3,7 @@ use anyhow::Result;
 
 use super::state::{App, View, SortField};
 
+#[allow(clippy::collapsible_if)]
 pub fn handle_events(app: &mut App) -> Result<()> {
     if event::poll(std::time::Duration::from_millis(50))? {
         if let Event::Key(key) = event::read()? {
@@ -12,18 +13,31 @@ pub fn handle_events(app: &mut App) -> Result<()> {
     Ok(())
 }
 
+#[allow(clippy::collapsible_match)]
 fn handle_key_event(key: KeyEvent, app: &mut App) -> Result<()> {
     if key.kind != KeyEventKind::Press {
         return Ok(());
     }
 
+    // The date range prompt takes all input while it's open
+    if app.date_range_prompt.is_some() {
+        match key.code {
+            KeyCode::Esc => app.close_date_range_prompt(),
+            KeyCode::Enter => app.submit_date_range_prompt(),
+            KeyCode::Backspace => app.date_range_prompt_backspace(),
+            KeyCode::Char(c) => app.date_range_prompt_input(c),
+            _ => {}
+        }
+        return Ok(());
+    }
+
     match key.code {
         KeyCode::Char('q') => app.quit(),
         KeyCode::Esc => {
             // Handle back navigation
             if app.current_view == View::ScrapeSelection {
                 app.request_view_switch(View::Org);
-            } else if matches!(app.current_view, View::OrgDetail | View::RepoDetail | View::ContributorDetail) {
+            } else if matches!(app.current_view, View::OrgDetail | View::RepoDetail | View::ContributorDetail | View::RepoHotspots | View::TeamDetail) {
                 // Navigate back from detail views - will be handled in main loop
                 app.request_navigate_back();
             } else {
@@ -41,6 +55,8 @@ fn handle_key_event(key: KeyEvent, app: &mut App) -> Result<()> {
         KeyCode::Char('o') => app.request_view_switch(View::Org),
         KeyCode::Char('r') => app.request_view_switch(View::Repo),
         KeyCode::Char('u') => app.request_view_switch(View::Contributors),
+        KeyCode::Char('T') => app.request_view_switch(View::Teams),
+        KeyCode::Char('G') => app.request_view_switch(View::Groups),
         KeyCode::F(5) => {
             // Retry/refresh current view with F5
             if !matches!(app.current_view, View::ScrapeSelection) && !app.is_scraping {
@@ -101,12 +117,36 @@ fn handle_key_event(key: KeyEvent, app: &mut App) -> Result<()> {
                 app.set_sort_field(SortField::Repos);
             }
         }
+        KeyCode::Char('g') if matches!(app.current_view, View::Repo | View::OrgDetail) => {
+            app.set_sort_field(SortField::Language);
+        }
+        KeyCode::Char('L') if matches!(app.current_view, View::Repo | View::OrgDetail) && !app.is_scraping => {
+            app.cycle_language_filter();
+        }
+        KeyCode::Char('a') if matches!(app.current_view, View::Repo | View::OrgDetail) && !app.is_scraping => {
+            app.toggle_hide_archived();
+        }
+        KeyCode::Char('f') if matches!(app.current_view, View::Repo | View::OrgDetail) && !app.is_scraping => {
+            app.toggle_hide_forks();
+        }
+        KeyCode::Char('h') if app.current_view == View::RepoDetail && !app.is_scraping => {
+            app.request_hotspots();
+        }
         KeyCode::Char('S') => {
             // Don't start new scrape if already scraping
             if !app.is_scraping {
                 app.request_scraping();
             }
         }
+        KeyCode::Char('P') if app.current_view == View::ScrapeSelection => {
+            app.cycle_scrape_profile_filter();
+        }
+        KeyCode::Char('D') if !app.is_scraping => {
+            app.open_date_range_prompt();
+        }
+        KeyCode::Char('B') if !app.is_scraping => {
+            app.request_database_switch();
+        }
         _ => {}
     }
     Ok(())
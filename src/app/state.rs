@@ -3,16 +3,22 @@
 //! This module handles all application state including current view, selected items,
 //! scrape data management, and navigation between different views and detail screens.
 
-use crate::stats::{ViewData, ScrapeInfo, OrgStats, RepoStats, ContributorStats};
-use crate::db::{new_pool, Scrape, get_org_stats, get_repo_stats, get_contributor_stats, get_org_detail, get_repo_detail, get_contributor_detail};
+use crate::stats::{ViewData, ScrapeInfo, OrgStats, RepoStats, ContributorStats, TeamStats, GroupStats};
+use crate::db::{new_pool, Scrape, get_org_stats, get_repo_stats, get_contributor_stats, get_org_detail, get_repo_detail, get_contributor_detail, get_contributor_commit_times, get_repo_hotspots, get_team_stats, get_team_detail, get_group_stats, sync_config_groups};
+use crate::config::{get_config, DatabaseConfig};
+use crate::paths::{db_path, set_db_path};
+use crate::timing::{activity_timing, contributor_offset};
+use crate::date_range::DateRange;
 use crate::scraper;
 use anyhow::Result;
+use sqlx::SqlitePool;
 
 #[derive(Debug, Clone)]
 enum DrillType {
     Org(String),
     Repo(String, String), // org_name, repo_name
     Contributor(String),
+    Team(String, String), // org_name, team_slug
 }
 
 #[derive(Debug, Clone)]
@@ -37,7 +43,59 @@ pub struct App {
     pub drill_down_requested: bool,
     pub navigate_back_requested: bool,
     pub refresh_requested: bool,
+    pub hotspots_requested: bool,
     pub view_history: Vec<(View, String)>, // (view, context) for back navigation
+    pub repo_filter: RepoFilter,
+    pub available_languages: Vec<String>,
+    /// Open while the user types a custom scrape window
+    pub date_range_prompt: Option<DateRangePrompt>,
+    /// Window for the requested scrape; the last week when unset
+    pub scrape_range: Option<DateRange>,
+    /// Profile the scrape list is narrowed to: `None` shows every scrape, `Some(None)` only
+    /// scrapes run without a profile
+    pub scrape_profile_filter: Option<Option<String>>,
+    /// Databases `B` cycles through: those in config, plus the one opened at startup if it isn't listed
+    pub databases: Vec<DatabaseConfig>,
+    pub database_index: usize,
+    pub database_switch_requested: bool,
+    /// Pool for the open database, shared by every view load and scrape until the database is switched
+    pub db_pool: Option<SqlitePool>,
+    /// Why teams and repo groups from config couldn't be synced when the database was opened
+    pub config_groups_error: Option<String>,
+}
+
+/// Text typed into the `D` prompt, e.g. `2025-07-01..2025-09-30`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DateRangePrompt {
+    pub input: String,
+    pub error: Option<String>,
+}
+
+/// Filters applied to repository lists (the repo view and org detail)
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RepoFilter {
+    pub language: Option<String>,
+    pub hide_archived: bool,
+    pub hide_forks: bool,
+}
+
+impl RepoFilter {
+    pub fn matches(&self, repo: &RepoStats) -> bool {
+        if self.hide_archived && repo.archived {
+            return false;
+        }
+        if self.hide_forks && repo.fork {
+            return false;
+        }
+        match &self.language {
+            Some(language) => repo.language.as_deref() == Some(language.as_str()),
+            None => true,
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        *self != RepoFilter::default()
+    }
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -49,6 +107,10 @@ pub enum View {
     OrgDetail,
     RepoDetail,
     ContributorDetail,
+    RepoHotspots,
+    Teams,
+    TeamDetail,
+    Groups,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -58,6 +120,7 @@ pub enum SortField {
     Lines,
     Repos,
     Prs,
+    Language,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -85,7 +148,18 @@ impl Default for App {
             drill_down_requested: false,
             navigate_back_requested: false,
             refresh_requested: false,
+            hotspots_requested: false,
             view_history: Vec::new(),
+            repo_filter: RepoFilter::default(),
+            available_languages: Vec::new(),
+            date_range_prompt: None,
+            scrape_range: None,
+            scrape_profile_filter: None,
+            databases: Vec::new(),
+            database_index: 0,
+            database_switch_requested: false,
+            db_pool: None,
+            config_groups_error: None,
         }
     }
 }
@@ -95,33 +169,105 @@ impl App {
         Self::default()
     }
 
-    pub async fn new_with_data() -> Result<Self> {
-        let mut app = Self::default();
-        
-        // Initialize database connection and load data
-        let db_pool = new_pool().await?;
-        let mut db_conn = db_pool.acquire().await?;
+    pub async fn new_with_data(db_pool: SqlitePool) -> Result<Self> {
+        let mut app = Self { db_pool: Some(db_pool), ..Self::default() };
+        app.set_databases(&get_config()?.databases, &db_path().to_string_lossy());
+        app.load_database().await?;
+        Ok(app)
+    }
+
+    /// Lists the databases to switch between and selects the current one, adding it first
+    /// when it isn't in config.
+    pub fn set_databases(&mut self, configured: &[DatabaseConfig], current: &str) {
+        self.databases = configured.to_vec();
+        self.database_index = match self.databases.iter().position(|database| database.path == current) {
+            Some(index) => index,
+            None => {
+                let name = std::path::Path::new(current)
+                    .file_stem()
+                    .map_or_else(|| current.to_string(), |stem| stem.to_string_lossy().into_owned());
+                self.databases.insert(0, DatabaseConfig { name, path: current.to_string() });
+                0
+            }
+        };
+    }
+
+    /// The name of the open database, when there are others to switch to
+    pub fn database_name(&self) -> Option<&str> {
+        match self.databases.len() {
+            0 | 1 => None,
+            _ => self.databases.get(self.database_index).map(|database| database.name.as_str()),
+        }
+    }
+
+    pub fn request_database_switch(&mut self) {
+        if self.databases.len() > 1 {
+            self.database_switch_requested = true;
+        }
+    }
+
+    /// Opens the next database and starts over on its latest scrape.
+    pub async fn handle_database_switch(&mut self) -> Result<()> {
+        if !self.database_switch_requested {
+            return Ok(());
+        }
+        self.database_switch_requested = false;
+
+        self.database_index = (self.database_index + 1) % self.databases.len();
+        set_db_path(&self.databases[self.database_index].path);
+        if let Some(pool) = self.db_pool.take() {
+            pool.close().await;
+        }
+        self.db_pool = Some(new_pool().await?);
+
+        self.current_view = View::Org;
+        self.current_scrape = None;
+        self.scrapes.clear();
+        self.selected_index = 0;
+        self.scrape_selected_index = 0;
+        self.scrape_profile_filter = None;
+        self.repo_filter = RepoFilter::default();
+        self.view_history.clear();
+        self.data = ViewData::Loading;
+        self.load_database().await
+    }
+
+    /// Loads the scrape list from the current database and shows its latest scrape.
+    async fn load_database(&mut self) -> Result<()> {
+        // Groups are synced once when config and the database load, so the Groups view only reads
+        self.config_groups_error = self.sync_groups_from_config().await.err().map(|e| e.to_string());
+
+        let mut db_conn = self.get_db_connection().await?;
 
         // Load all available scrapes
-        app.scrapes = Scrape::list_all(&mut db_conn).await?;
+        self.scrapes = Scrape::list_all(&mut db_conn).await?;
 
         // Default to latest scrape if available
         if let Some(latest_scrape) = Scrape::get_latest(&mut db_conn).await? {
-            app.current_scrape = Some(latest_scrape.id);
+            self.current_scrape = Some(latest_scrape.id);
             // Set scrape selection index to the latest scrape
-            if let Some(index) = app.scrapes.iter().position(|s| s.id == latest_scrape.id) {
-                app.scrape_selected_index = index;
+            if let Some(index) = self.scrapes.iter().position(|s| s.id == latest_scrape.id) {
+                self.scrape_selected_index = index;
             }
 
             // Load initial org stats for the latest scrape
             let org_stats = get_org_stats(&mut db_conn, latest_scrape.id).await?;
-            app.data = ViewData::Orgs(org_stats);
+            self.data = ViewData::Orgs(org_stats);
         } else {
             // No scrapes available
-            app.data = ViewData::Error("No scrape data available. Run a scrape first.".to_string());
+            self.data = ViewData::Error("No scrape data available. Run a scrape first.".to_string());
         }
 
-        Ok(app)
+        Ok(())
+    }
+
+    async fn sync_groups_from_config(&self) -> Result<()> {
+        let cfg = get_config()
+            .map_err(|e| anyhow::anyhow!("Failed to load config: {}", e))?;
+        let teams = cfg.configured_teams()
+            .map_err(|e| anyhow::anyhow!("Failed to load teams: {}", e))?;
+        sync_config_groups(&mut *self.get_db_connection().await?, &teams, &cfg.repo_groups).await
+            .map_err(|e| anyhow::anyhow!("Failed to sync config groups: {}", e))
     }
 
     pub fn quit(&mut self) {
@@ -183,12 +329,55 @@ impl App {
             ViewData::ContributorDetail(detail) => {
                 Self::sort_contributor_repos_static(&mut detail.contributions, sort_field, sort_order);
             }
+            ViewData::RepoHotspots(hotspots) => {
+                Self::sort_path_churn_static(&mut hotspots.paths, sort_field, sort_order);
+            }
+            ViewData::Teams(teams) => {
+                Self::sort_teams_static(teams, sort_field, sort_order);
+            }
+            ViewData::TeamDetail(detail) => {
+                Self::sort_team_members_static(&mut detail.members, sort_field, sort_order);
+            }
+            ViewData::Groups(groups) => {
+                Self::sort_groups_static(groups, sort_field, sort_order);
+            }
             ViewData::Loading | ViewData::Error(_) => {}
         }
         // Reset selection to top after sorting
         self.selected_index = 0;
     }
 
+    pub fn toggle_hide_archived(&mut self) {
+        self.repo_filter.hide_archived = !self.repo_filter.hide_archived;
+        self.request_refresh();
+    }
+
+    pub fn toggle_hide_forks(&mut self) {
+        self.repo_filter.hide_forks = !self.repo_filter.hide_forks;
+        self.request_refresh();
+    }
+
+    /// Steps the language filter through every language seen in the current scrape, then back to all
+    pub fn cycle_language_filter(&mut self) {
+        self.repo_filter.language = match &self.repo_filter.language {
+            None => self.available_languages.first().cloned(),
+            Some(current) => self.available_languages.iter()
+                .position(|l| l == current)
+                .and_then(|i| self.available_languages.get(i + 1))
+                .cloned(),
+        };
+        self.request_refresh();
+    }
+
+    /// Records the languages present before filtering, then drops repos that don't match the filter
+    fn filter_repos(&mut self, repos: &mut Vec<RepoStats>) {
+        let mut languages: Vec<String> = repos.iter().filter_map(|r| r.language.clone()).collect();
+        languages.sort();
+        languages.dedup();
+        self.available_languages = languages;
+        repos.retain(|r| self.repo_filter.matches(r));
+    }
+
     pub fn move_selection_up(&mut self) {
         let item_count = self.get_item_count();
         if item_count > 0 {
@@ -208,9 +397,10 @@ impl App {
     }
 
     pub fn move_scrape_selection_up(&mut self) {
-        if !self.scrapes.is_empty() {
+        let scrape_count = self.visible_scrapes().len();
+        if scrape_count > 0 {
             self.scrape_selected_index = if self.scrape_selected_index == 0 {
-                self.scrapes.len() - 1
+                scrape_count - 1
             } else {
                 self.scrape_selected_index - 1
             };
@@ -218,11 +408,41 @@ impl App {
     }
 
     pub fn move_scrape_selection_down(&mut self) {
-        if !self.scrapes.is_empty() {
-            self.scrape_selected_index = (self.scrape_selected_index + 1) % self.scrapes.len();
+        let scrape_count = self.visible_scrapes().len();
+        if scrape_count > 0 {
+            self.scrape_selected_index = (self.scrape_selected_index + 1) % scrape_count;
         }
     }
 
+    /// Scrapes matching the profile filter, newest first
+    pub fn visible_scrapes(&self) -> Vec<&ScrapeInfo> {
+        self.scrapes.iter()
+            .filter(|scrape| match &self.scrape_profile_filter {
+                Some(profile) => scrape.profile == *profile,
+                None => true,
+            })
+            .collect()
+    }
+
+    /// Steps the scrape list through each profile that has scrapes, then back to all scrapes
+    pub fn cycle_scrape_profile_filter(&mut self) {
+        let mut profiles: Vec<Option<String>> = Vec::new();
+        for scrape in &self.scrapes {
+            if !profiles.contains(&scrape.profile) {
+                profiles.push(scrape.profile.clone());
+            }
+        }
+        profiles.sort();
+
+        self.scrape_profile_filter = match &self.scrape_profile_filter {
+            None => profiles.into_iter().next(),
+            Some(current) => profiles.iter()
+                .position(|p| p == current)
+                .and_then(|i| profiles.get(i + 1).cloned()),
+        };
+        self.scrape_selected_index = 0;
+    }
+
     pub fn get_item_count(&self) -> usize {
         match &self.data {
             ViewData::Orgs(orgs) => orgs.len(),
@@ -231,16 +451,31 @@ impl App {
             ViewData::OrgDetail(detail) => detail.repos.len(),
             ViewData::RepoDetail(detail) => detail.contributors.len(),
             ViewData::ContributorDetail(detail) => detail.contributions.len(),
+            ViewData::RepoHotspots(hotspots) => hotspots.paths.len(),
+            ViewData::Teams(teams) => teams.len(),
+            ViewData::TeamDetail(detail) => detail.members.len(),
+            ViewData::Groups(groups) => groups.len(),
             ViewData::Loading | ViewData::Error(_) => 0,
         }
     }
 
     pub async fn refresh_current_view_data(&mut self) -> Result<()> {
+        // Org detail is the only detail view that can be reloaded in place, so repo filters apply to it
+        if self.current_view == View::OrgDetail
+            && let (Some(scrape_id), ViewData::OrgDetail(detail)) = (self.current_scrape, &self.data)
+        {
+            let org_name = detail.org_name.clone();
+            return self.drill_into_org(scrape_id, &org_name).await;
+        }
+
         if let Some(scrape_id) = self.current_scrape {
             self.data = ViewData::Loading;
             
             match self.load_view_data(scrape_id).await {
-                Ok(view_data) => {
+                Ok(mut view_data) => {
+                    if let ViewData::Repos(repos) = &mut view_data {
+                        self.filter_repos(repos);
+                    }
                     self.data = view_data;
                     self.apply_sort();
                 }
@@ -255,10 +490,7 @@ impl App {
     }
 
     async fn load_view_data(&self, scrape_id: i64) -> Result<ViewData> {
-        let db_pool = new_pool().await
-            .map_err(|e| anyhow::anyhow!("Database connection failed: {}", e))?;
-        let mut db_conn = db_pool.acquire().await
-            .map_err(|e| anyhow::anyhow!("Failed to acquire database connection: {}", e))?;
+        let mut db_conn = self.get_db_connection().await?;
 
         match self.current_view {
             View::Org => {
@@ -285,11 +517,30 @@ impl App {
                 }
                 Ok(ViewData::Contributors(contributor_stats))
             }
+            View::Teams => {
+                let team_stats = get_team_stats(&mut db_conn, scrape_id).await
+                    .map_err(|e| anyhow::anyhow!("Failed to load team data: {}", e))?;
+                if team_stats.is_empty() {
+                    return Err(anyhow::anyhow!("No team data found for this scrape. Listing teams needs a token with read:org"));
+                }
+                Ok(ViewData::Teams(team_stats))
+            }
+            View::Groups => {
+                if let Some(error) = &self.config_groups_error {
+                    return Err(anyhow::anyhow!("{}", error));
+                }
+                let group_stats = get_group_stats(&mut db_conn, scrape_id).await
+                    .map_err(|e| anyhow::anyhow!("Failed to load group data: {}", e))?;
+                if group_stats.is_empty() {
+                    return Err(anyhow::anyhow!("No teams or repo groups defined in config.toml"));
+                }
+                Ok(ViewData::Groups(group_stats))
+            }
             View::ScrapeSelection => {
                 // No data loading needed for scrape selection view
                 Ok(ViewData::Loading)
             }
-            View::OrgDetail | View::RepoDetail | View::ContributorDetail => {
+            View::OrgDetail | View::RepoDetail | View::ContributorDetail | View::RepoHotspots | View::TeamDetail => {
                 Err(anyhow::anyhow!("Detail views should not be refreshed through refresh_current_view_data"))
             }
         }
@@ -318,7 +569,7 @@ impl App {
     }
 
     pub async fn select_current_scrape(&mut self) -> Result<()> {
-        if let Some(scrape) = self.scrapes.get(self.scrape_selected_index) {
+        if let Some(scrape) = self.visible_scrapes().get(self.scrape_selected_index) {
             self.current_scrape = Some(scrape.id);
             self.current_view = View::Org; // Return to org view after selecting scrape
             self.selected_index = 0; // Reset selection
@@ -353,8 +604,8 @@ impl App {
                     SortOrder::Descending => b.repo_count.cmp(&a.repo_count),
                 });
             }
-            SortField::Prs => {
-                // PRs not applicable to orgs, fallback to commits
+            SortField::Prs | SortField::Language => {
+                // PRs and language not applicable to orgs, fallback to commits
                 orgs.sort_by(|a, b| match sort_order {
                     SortOrder::Ascending => a.total_commits.cmp(&b.total_commits),
                     SortOrder::Descending => b.total_commits.cmp(&a.total_commits),
@@ -396,6 +647,16 @@ impl App {
                     SortOrder::Descending => b.commits.cmp(&a.commits),
                 });
             }
+            SortField::Language => {
+                // Group repos by language, busiest first within each group
+                repos.sort_by(|a, b| {
+                    let language_order = match sort_order {
+                        SortOrder::Ascending => b.language.cmp(&a.language),
+                        SortOrder::Descending => a.language.cmp(&b.language),
+                    };
+                    language_order.then(b.commits.cmp(&a.commits))
+                });
+            }
         }
     }
 
@@ -425,8 +686,8 @@ impl App {
                     SortOrder::Descending => b.repo_count.cmp(&a.repo_count),
                 });
             }
-            SortField::Prs => {
-                // PRs not applicable to contributors, fallback to commits
+            SortField::Prs | SortField::Language => {
+                // PRs and language not applicable to contributors, fallback to commits
                 contributors.sort_by(|a, b| match sort_order {
                     SortOrder::Ascending => a.total_commits.cmp(&b.total_commits),
                     SortOrder::Descending => b.total_commits.cmp(&a.total_commits),
@@ -461,8 +722,8 @@ impl App {
                     SortOrder::Descending => b.prs.cmp(&a.prs),
                 });
             }
-            SortField::Repos => {
-                // Repos not applicable to repo contributors, fallback to commits
+            SortField::Repos | SortField::Language => {
+                // Repos and language not applicable to repo contributors, fallback to commits
                 contributors.sort_by(|a, b| match sort_order {
                     SortOrder::Ascending => a.commits.cmp(&b.commits),
                     SortOrder::Descending => b.commits.cmp(&a.commits),
@@ -497,8 +758,8 @@ impl App {
                     SortOrder::Descending => b.prs.cmp(&a.prs),
                 });
             }
-            SortField::Repos => {
-                // Sort by repo name when repos field selected
+            SortField::Repos | SortField::Language => {
+                // Sort by repo name when repos or language field selected
                 contributions.sort_by(|a, b| match sort_order {
                     SortOrder::Ascending => a.repo_name.cmp(&b.repo_name),
                     SortOrder::Descending => b.repo_name.cmp(&a.repo_name),
@@ -507,10 +768,193 @@ impl App {
         }
     }
 
+    fn sort_path_churn_static(paths: &mut [crate::stats::PathChurn], sort_field: SortField, sort_order: SortOrder) {
+        match sort_field {
+            SortField::Name => {
+                paths.sort_by(|a, b| match sort_order {
+                    SortOrder::Ascending => a.path.cmp(&b.path),
+                    SortOrder::Descending => b.path.cmp(&a.path),
+                });
+            }
+            SortField::Commits => {
+                paths.sort_by(|a, b| match sort_order {
+                    SortOrder::Ascending => a.commits.cmp(&b.commits),
+                    SortOrder::Descending => b.commits.cmp(&a.commits),
+                });
+            }
+            SortField::Lines | SortField::Repos | SortField::Prs | SortField::Language => {
+                // Churn is the only line metric for paths, other fields fall back to it
+                paths.sort_by(|a, b| match sort_order {
+                    SortOrder::Ascending => (a.additions + a.deletions).cmp(&(b.additions + b.deletions)),
+                    SortOrder::Descending => (b.additions + b.deletions).cmp(&(a.additions + a.deletions)),
+                });
+            }
+        }
+    }
+
+    fn sort_teams_static(teams: &mut [TeamStats], sort_field: SortField, sort_order: SortOrder) {
+        match sort_field {
+            SortField::Name => {
+                teams.sort_by(|a, b| match sort_order {
+                    SortOrder::Ascending => a.team_name.cmp(&b.team_name),
+                    SortOrder::Descending => b.team_name.cmp(&a.team_name),
+                });
+            }
+            SortField::Commits => {
+                teams.sort_by(|a, b| match sort_order {
+                    SortOrder::Ascending => a.commits.cmp(&b.commits),
+                    SortOrder::Descending => b.commits.cmp(&a.commits),
+                });
+            }
+            SortField::Lines => {
+                teams.sort_by(|a, b| match sort_order {
+                    SortOrder::Ascending => a.lines.cmp(&b.lines),
+                    SortOrder::Descending => b.lines.cmp(&a.lines),
+                });
+            }
+            SortField::Prs => {
+                teams.sort_by(|a, b| match sort_order {
+                    SortOrder::Ascending => a.prs.cmp(&b.prs),
+                    SortOrder::Descending => b.prs.cmp(&a.prs),
+                });
+            }
+            SortField::Repos => {
+                // Member count stands in for repos on teams
+                teams.sort_by(|a, b| match sort_order {
+                    SortOrder::Ascending => a.member_count.cmp(&b.member_count),
+                    SortOrder::Descending => b.member_count.cmp(&a.member_count),
+                });
+            }
+            SortField::Language => {
+                // Language not applicable to teams, fallback to commits
+                teams.sort_by(|a, b| match sort_order {
+                    SortOrder::Ascending => a.commits.cmp(&b.commits),
+                    SortOrder::Descending => b.commits.cmp(&a.commits),
+                });
+            }
+        }
+    }
+
+    fn sort_groups_static(groups: &mut [GroupStats], sort_field: SortField, sort_order: SortOrder) {
+        match sort_field {
+            SortField::Name => {
+                groups.sort_by(|a, b| match sort_order {
+                    SortOrder::Ascending => a.name.cmp(&b.name),
+                    SortOrder::Descending => b.name.cmp(&a.name),
+                });
+            }
+            SortField::Commits => {
+                groups.sort_by(|a, b| match sort_order {
+                    SortOrder::Ascending => a.commits.cmp(&b.commits),
+                    SortOrder::Descending => b.commits.cmp(&a.commits),
+                });
+            }
+            SortField::Lines => {
+                groups.sort_by(|a, b| match sort_order {
+                    SortOrder::Ascending => a.lines.cmp(&b.lines),
+                    SortOrder::Descending => b.lines.cmp(&a.lines),
+                });
+            }
+            SortField::Prs => {
+                groups.sort_by(|a, b| match sort_order {
+                    SortOrder::Ascending => a.prs.cmp(&b.prs),
+                    SortOrder::Descending => b.prs.cmp(&a.prs),
+                });
+            }
+            SortField::Repos => {
+                // Group size stands in for repos on groups
+                groups.sort_by(|a, b| match sort_order {
+                    SortOrder::Ascending => a.size.cmp(&b.size),
+                    SortOrder::Descending => b.size.cmp(&a.size),
+                });
+            }
+            SortField::Language => {
+                // Language not applicable to groups, fallback to commits
+                groups.sort_by(|a, b| match sort_order {
+                    SortOrder::Ascending => a.commits.cmp(&b.commits),
+                    SortOrder::Descending => b.commits.cmp(&a.commits),
+                });
+            }
+        }
+    }
+
+    fn sort_team_members_static(members: &mut [crate::stats::TeamMember], sort_field: SortField, sort_order: SortOrder) {
+        match sort_field {
+            SortField::Name => {
+                members.sort_by(|a, b| match sort_order {
+                    SortOrder::Ascending => a.username.cmp(&b.username),
+                    SortOrder::Descending => b.username.cmp(&a.username),
+                });
+            }
+            SortField::Commits => {
+                members.sort_by(|a, b| match sort_order {
+                    SortOrder::Ascending => a.commits.cmp(&b.commits),
+                    SortOrder::Descending => b.commits.cmp(&a.commits),
+                });
+            }
+            SortField::Lines => {
+                members.sort_by(|a, b| match sort_order {
+                    SortOrder::Ascending => a.lines.cmp(&b.lines),
+                    SortOrder::Descending => b.lines.cmp(&a.lines),
+                });
+            }
+            SortField::Prs => {
+                members.sort_by(|a, b| match sort_order {
+                    SortOrder::Ascending => a.prs.cmp(&b.prs),
+                    SortOrder::Descending => b.prs.cmp(&a.prs),
+                });
+            }
+            SortField::Repos | SortField::Language => {
+                // Repos and language not applicable to team members, fallback to commits
+                members.sort_by(|a, b| match sort_order {
+                    SortOrder::Ascending => a.commits.cmp(&b.commits),
+                    SortOrder::Descending => b.commits.cmp(&a.commits),
+                });
+            }
+        }
+    }
+
     pub fn request_scraping(&mut self) {
         self.start_scraping_requested = true;
     }
 
+    pub fn open_date_range_prompt(&mut self) {
+        self.date_range_prompt = Some(DateRangePrompt::default());
+    }
+
+    pub fn close_date_range_prompt(&mut self) {
+        self.date_range_prompt = None;
+    }
+
+    pub fn date_range_prompt_input(&mut self, c: char) {
+        if let Some(prompt) = &mut self.date_range_prompt {
+            prompt.input.push(c);
+            prompt.error = None;
+        }
+    }
+
+    pub fn date_range_prompt_backspace(&mut self) {
+        if let Some(prompt) = &mut self.date_range_prompt {
+            prompt.input.pop();
+            prompt.error = None;
+        }
+    }
+
+    /// Requests a scrape over the typed range, or keeps the prompt open with the parse error.
+    pub fn submit_date_range_prompt(&mut self) {
+        let Some(prompt) = &mut self.date_range_prompt else {
+            return;
+        };
+        match DateRange::parse_input(&prompt.input) {
+            Ok(range) => {
+                self.date_range_prompt = None;
+                self.scrape_range = Some(range);
+                self.request_scraping();
+            }
+            Err(e) => prompt.error = Some(e.to_string()),
+        }
+    }
+
     pub fn request_drill_down(&mut self) {
         self.drill_down_requested = true;
     }
@@ -523,6 +967,10 @@ impl App {
         self.refresh_requested = true;
     }
 
+    pub fn request_hotspots(&mut self) {
+        self.hotspots_requested = true;
+    }
+
     pub fn set_error(&mut self, error: String) {
         self.data = ViewData::Error(error);
         // Reset any pending operations
@@ -530,6 +978,7 @@ impl App {
         self.navigate_back_requested = false;
         self.start_scraping_requested = false;
         self.refresh_requested = false;
+        self.hotspots_requested = false;
         self.is_scraping = false;
     }
 
@@ -549,17 +998,14 @@ impl App {
         self.scraping_error = Some(error);
     }
 
-    pub async fn refresh_after_scrape(&mut self) -> Result<()> {
+    pub async fn refresh_after_scrape(&mut self, scrape_id: i64) -> Result<()> {
         // Reload scrape list to include new scrape
-        let pool = new_pool().await?;
-        let mut db_conn = pool.acquire().await?;
+        let mut db_conn = self.get_db_connection().await?;
         self.scrapes = Scrape::list_all(&mut db_conn).await?;
         
-        // Switch to the latest scrape (which should be the new one)
-        if let Some(latest) = self.scrapes.first() {
-            self.current_scrape = Some(latest.id);
-            self.refresh_current_view_data().await?;
-        }
+        // Switch to the new scrape, which may cover an earlier window than others in the list
+        self.current_scrape = Some(scrape_id);
+        self.refresh_current_view_data().await?;
         
         Ok(())
     }
@@ -569,11 +1015,23 @@ impl App {
             self.start_scraping();
             
             // Run the scrape (this will block the TUI as intended per plan)
-            match scraper::run_scrape().await {
-                Ok(()) => {
+            let range = match self.scrape_range.take() {
+                Some(range) => range,
+                None => match get_config() {
+                    Ok(cfg) => DateRange::for_profile(&cfg, None),
+                    Err(e) => {
+                        self.finish_scraping_error(format!("Scrape failed: {e}"));
+                        return Ok(());
+                    }
+                },
+            };
+            let db_pool = self.db_pool.clone()
+                .ok_or_else(|| anyhow::anyhow!("No database is open"))?;
+            match scraper::run_scrape(&db_pool, range, None).await {
+                Ok(scrape_id) => {
                     self.finish_scraping_success();
                     // Refresh data after successful scrape
-                    self.refresh_after_scrape().await?;
+                    self.refresh_after_scrape(scrape_id).await?;
                 }
                 Err(e) => {
                     self.finish_scraping_error(format!("Scrape failed: {e}"));
@@ -596,6 +1054,10 @@ impl App {
             self.navigate_back_requested = false;
             self.navigate_back().await?;
         }
+        if self.hotspots_requested {
+            self.hotspots_requested = false;
+            self.open_repo_hotspots().await?;
+        }
         Ok(())
     }
 
@@ -635,6 +1097,18 @@ impl App {
                         (self.current_view.clone(), contributor.username.clone(), DrillType::Contributor(contributor.username.clone()))
                     })
                 }
+                ViewData::Teams(teams) => {
+                    teams.get(self.selected_index).map(|team| {
+                        let context = format!("{}/{}", team.org_name, team.team_slug);
+                        (self.current_view.clone(), context, DrillType::Team(team.org_name.clone(), team.team_slug.clone()))
+                    })
+                }
+                ViewData::TeamDetail(detail) => {
+                    detail.members.get(self.selected_index).map(|member| {
+                        let context = format!("{}/{}", detail.org_name, detail.team_slug);
+                        (self.current_view.clone(), context, DrillType::Contributor(member.username.clone()))
+                    })
+                }
                 _ => None,
             };
 
@@ -650,6 +1124,9 @@ impl App {
                     DrillType::Contributor(username) => {
                         self.drill_into_contributor(scrape_id, &username).await?;
                     }
+                    DrillType::Team(org_name, team_slug) => {
+                        self.drill_into_team(scrape_id, &org_name, &team_slug).await?;
+                    }
                 }
             }
         }
@@ -667,9 +1144,12 @@ impl App {
                 View::Org => self.refresh_current_view_data().await?,
                 View::Repo => self.refresh_current_view_data().await?,
                 View::Contributors => self.refresh_current_view_data().await?,
+                View::Teams => self.refresh_current_view_data().await?,
+                View::Groups => self.refresh_current_view_data().await?,
                 View::OrgDetail => {
                     if let Some(scrape_id) = self.current_scrape {
-                        let detail = get_org_detail(&mut self.get_db_connection().await?, scrape_id, &context).await?;
+                        let mut detail = get_org_detail(&mut *self.get_db_connection().await?, scrape_id, &context).await?;
+                        self.filter_repos(&mut detail.repos);
                         self.data = ViewData::OrgDetail(detail);
                     }
                 }
@@ -677,11 +1157,19 @@ impl App {
                     if let Some(scrape_id) = self.current_scrape {
                         let parts: Vec<&str> = context.split('/').collect();
                         if parts.len() == 2 {
-                            let detail = get_repo_detail(&mut self.get_db_connection().await?, scrape_id, parts[0], parts[1]).await?;
+                            let detail = get_repo_detail(&mut *self.get_db_connection().await?, scrape_id, parts[0], parts[1]).await?;
                             self.data = ViewData::RepoDetail(detail);
                         }
                     }
                 }
+                View::TeamDetail => {
+                    if let Some(scrape_id) = self.current_scrape
+                        && let Some((org_name, team_slug)) = context.split_once('/')
+                    {
+                        let detail = get_team_detail(&mut *self.get_db_connection().await?, scrape_id, org_name, team_slug).await?;
+                        self.data = ViewData::TeamDetail(detail);
+                    }
+                }
                 _ => {}
             }
             self.apply_sort();
@@ -692,12 +1180,13 @@ impl App {
     async fn drill_into_org(&mut self, scrape_id: i64, org_name: &str) -> Result<()> {
         let mut db_conn = self.get_db_connection().await
             .map_err(|e| anyhow::anyhow!("Database connection failed: {}", e))?;
-        let detail = get_org_detail(&mut db_conn, scrape_id, org_name).await
+        let mut detail = get_org_detail(&mut db_conn, scrape_id, org_name).await
             .map_err(|e| anyhow::anyhow!("Failed to load organization '{}' details: {}", org_name, e))?;
         
         if detail.repos.is_empty() {
             return Err(anyhow::anyhow!("No repositories found for organization '{}'", org_name));
         }
+        self.filter_repos(&mut detail.repos);
         
         self.data = ViewData::OrgDetail(detail);
         self.current_view = View::OrgDetail;
@@ -726,12 +1215,21 @@ impl App {
     async fn drill_into_contributor(&mut self, scrape_id: i64, username: &str) -> Result<()> {
         let mut db_conn = self.get_db_connection().await
             .map_err(|e| anyhow::anyhow!("Database connection failed: {}", e))?;
-        let detail = get_contributor_detail(&mut db_conn, scrape_id, username).await
+        let mut detail = get_contributor_detail(&mut db_conn, scrape_id, username).await
             .map_err(|e| anyhow::anyhow!("Failed to load contributor '{}' details: {}", username, e))?;
         
         if detail.contributions.is_empty() {
             return Err(anyhow::anyhow!("No contributions found for contributor '{}'", username));
         }
+
+        let cfg = get_config()
+            .map_err(|e| anyhow::anyhow!("Failed to load config: {}", e))?;
+        if cfg.activity_timing {
+            let times = get_contributor_commit_times(&mut db_conn, scrape_id, username).await
+                .map_err(|e| anyhow::anyhow!("Failed to load activity timing for '{}': {}", username, e))?;
+            let offset = contributor_offset(&cfg, username);
+            detail.timing = Some(activity_timing(&times, offset, cfg.working_hours_start, cfg.working_hours_end));
+        }
         
         self.data = ViewData::ContributorDetail(detail);
         self.current_view = View::ContributorDetail;
@@ -740,9 +1238,53 @@ impl App {
         Ok(())
     }
 
+    async fn drill_into_team(&mut self, scrape_id: i64, org_name: &str, team_slug: &str) -> Result<()> {
+        let mut db_conn = self.get_db_connection().await
+            .map_err(|e| anyhow::anyhow!("Database connection failed: {}", e))?;
+        let detail = get_team_detail(&mut db_conn, scrape_id, org_name, team_slug).await
+            .map_err(|e| anyhow::anyhow!("Failed to load team '{}/{}' details: {}", org_name, team_slug, e))?;
+
+        if detail.members.is_empty() {
+            return Err(anyhow::anyhow!("No members found for team '{}/{}'", org_name, team_slug));
+        }
+
+        self.data = ViewData::TeamDetail(detail);
+        self.current_view = View::TeamDetail;
+        self.selected_index = 0;
+        self.apply_sort();
+        Ok(())
+    }
+
+    /// Opens churn hotspots for the repository shown in the repo detail view
+    async fn open_repo_hotspots(&mut self) -> Result<()> {
+        if self.is_scraping {
+            return Ok(());
+        }
+
+        if let (Some(scrape_id), ViewData::RepoDetail(detail)) = (self.current_scrape, &self.data) {
+            let org_name = detail.org_name.clone();
+            let repo_name = detail.repo_name.clone();
+            let mut db_conn = self.get_db_connection().await
+                .map_err(|e| anyhow::anyhow!("Database connection failed: {}", e))?;
+            let hotspots = get_repo_hotspots(&mut db_conn, scrape_id, &org_name, &repo_name).await
+                .map_err(|e| anyhow::anyhow!("Failed to load hotspots for '{}/{}': {}", org_name, repo_name, e))?;
+
+            if hotspots.paths.is_empty() {
+                return Err(anyhow::anyhow!("No file changes recorded for repository '{}/{}'", org_name, repo_name));
+            }
+
+            self.view_history.push((View::RepoDetail, format!("{org_name}/{repo_name}")));
+            self.data = ViewData::RepoHotspots(hotspots);
+            self.current_view = View::RepoHotspots;
+            self.selected_index = 0;
+            self.apply_sort();
+        }
+        Ok(())
+    }
+
     async fn get_db_connection(&self) -> Result<sqlx::pool::PoolConnection<sqlx::Sqlite>> {
-        let pool = new_pool().await
-            .map_err(|e| anyhow::anyhow!("Failed to initialize database pool: {}", e))?;
+        let pool = self.db_pool.as_ref()
+            .ok_or_else(|| anyhow::anyhow!("No database is open"))?;
         pool.acquire().await
             .map_err(|e| anyhow::anyhow!("Failed to acquire database connection: {}", e))
     }
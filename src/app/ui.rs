@@ -6,12 +6,13 @@
 use ratatui::{
     layout::{Alignment, Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
-    widgets::{Block, Borders, Paragraph, Row, Table, Cell},
+    widgets::{Block, Borders, Clear, Paragraph, Row, Table, Cell},
     Frame,
 };
 
-use super::state::{App, View, SortField, SortOrder};
-use crate::stats::ViewData;
+use super::state::{App, DateRangePrompt, View, SortField, SortOrder};
+use crate::db::GROUP_KIND_TEAM;
+use crate::stats::{ActivityTiming, ChangeMix, CiHealth, DeliveryMetrics, OwnershipCoverage, RepoStats, ViewData};
 
 fn format_number(num: i64) -> String {
     if num >= 1_000_000 {
@@ -23,6 +24,149 @@ fn format_number(num: i64) -> String {
     }
 }
 
+fn repo_name_display(repo: &RepoStats) -> String {
+    let mut flags = Vec::new();
+    if repo.archived {
+        flags.push("archived");
+    }
+    if repo.fork {
+        flags.push("fork");
+    }
+    if flags.is_empty() {
+        repo.repo_name.clone()
+    } else {
+        format!("{} [{}]", repo.repo_name, flags.join(", "))
+    }
+}
+
+fn percent_display(part: i64, whole: i64) -> String {
+    if whole == 0 {
+        return "-".to_string();
+    }
+    format!("{:.0}%", part as f64 * 100.0 / whole as f64)
+}
+
+fn deploys_display(delivery: &DeliveryMetrics) -> String {
+    if delivery.releases == 0 && delivery.deployments == 0 {
+        return "-".to_string();
+    }
+    format!("{:.1}", delivery.deploys_per_week)
+}
+
+fn lead_time_display(delivery: &DeliveryMetrics) -> String {
+    match delivery.lead_time_hours {
+        None => "-".to_string(),
+        Some(hours) if hours < 48.0 => format!("{hours:.0}h"),
+        Some(hours) => format!("{:.1}d", hours / 24.0),
+    }
+}
+
+fn ci_pass_display(ci: &CiHealth) -> String {
+    match ci.success_rate() {
+        Some(rate) => format!("{:.0}%", rate * 100.0),
+        None => "-".to_string(),
+    }
+}
+
+fn duration_display(secs: Option<f64>) -> String {
+    match secs {
+        None => "-".to_string(),
+        Some(secs) if secs < 60.0 => format!("{secs:.0}s"),
+        Some(secs) if secs < 3600.0 => format!("{:.1}m", secs / 60.0),
+        Some(secs) => format!("{:.1}h", secs / 3600.0),
+    }
+}
+
+/// Feature/fix/chore shares of a change mix, e.g. `40/35/10%`
+fn change_mix_display(mix: &ChangeMix) -> String {
+    match (mix.share("feat"), mix.share("fix"), mix.share("chore")) {
+        (Some(feat), Some(fix), Some(chore)) => format!("{:.0}/{:.0}/{:.0}%", feat * 100.0, fix * 100.0, chore * 100.0),
+        _ => "-".to_string(),
+    }
+}
+
+/// Renders counts as a row of block characters scaled to the largest count.
+fn histogram_bar(counts: &[i64]) -> String {
+    const BLOCKS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+    let max = counts.iter().copied().max().unwrap_or_default();
+    counts.iter().map(|&count| {
+        if count == 0 || max == 0 {
+            ' '
+        } else {
+            BLOCKS[((count * (BLOCKS.len() as i64 - 1)) / max) as usize]
+        }
+    }).collect()
+}
+
+fn timing_lines(timing: &ActivityTiming) -> Vec<String> {
+    const WEEKDAYS: [&str; 7] = ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"];
+    let weekdays: Vec<String> = WEEKDAYS.iter()
+        .zip(timing.by_weekday.iter())
+        .map(|(day, count)| format!("{day} {count}"))
+        .collect();
+
+    vec![
+        format!(
+            "Outside working hours: {} ({} of {} commits, UTC{})",
+            percent_display(timing.outside_hours, timing.total),
+            format_number(timing.outside_hours),
+            format_number(timing.total),
+            timing.timezone,
+        ),
+        format!("Hour  00 {} 23", histogram_bar(&timing.by_hour)),
+        format!("Day   {}", weekdays.join("  ")),
+    ]
+}
+
+fn ownership_lines(ownership: &OwnershipCoverage) -> Vec<String> {
+    if !ownership.has_codeowners {
+        return vec!["No CODEOWNERS file found".to_string()];
+    }
+
+    let mut lines = vec![
+        format!(
+            "Owned churn: {} ({} of {} lines) | Owner-authored: {} | Owner-reviewed PRs: {} ({} of {})",
+            percent_display(ownership.owned_lines, ownership.total_lines),
+            format_number(ownership.owned_lines),
+            format_number(ownership.total_lines),
+            percent_display(ownership.owner_authored_lines, ownership.owned_lines),
+            percent_display(ownership.owner_reviewed_prs, ownership.owned_prs),
+            ownership.owner_reviewed_prs,
+            ownership.owned_prs,
+        ),
+    ];
+    let owners: Vec<String> = ownership.owners.iter().take(4).map(|owner| {
+        format!(
+            "{} {} lines, {}/{} PRs reviewed",
+            owner.owner,
+            format_number(owner.owned_lines),
+            owner.reviewed_prs,
+            owner.owned_prs,
+        )
+    }).collect();
+    if !owners.is_empty() {
+        lines.push(format!("Top owners: {}", owners.join(" | ")));
+    }
+    lines
+}
+
+fn repo_filter_display(app: &App) -> String {
+    if !app.repo_filter.is_active() {
+        return String::new();
+    }
+    let mut parts = Vec::new();
+    if let Some(language) = &app.repo_filter.language {
+        parts.push(format!("language: {language}"));
+    }
+    if app.repo_filter.hide_archived {
+        parts.push("no archived".to_string());
+    }
+    if app.repo_filter.hide_forks {
+        parts.push("no forks".to_string());
+    }
+    format!(" ({})", parts.join(", "))
+}
+
 pub fn ui(f: &mut Frame, app: &App) {
     let chunks = Layout::default()
         .direction(Direction::Vertical)
@@ -36,13 +180,51 @@ pub fn ui(f: &mut Frame, app: &App) {
     draw_header(f, chunks[0], app);
     draw_main_content(f, chunks[1], app);
     draw_footer(f, chunks[2], app);
+
+    if let Some(prompt) = &app.date_range_prompt {
+        draw_date_range_prompt(f, chunks[1], prompt);
+    }
+}
+
+/// Centered popup for typing a custom scrape window
+fn draw_date_range_prompt(f: &mut Frame, area: Rect, prompt: &DateRangePrompt) {
+    let width = area.width.min(72);
+    let height = area.height.min(8);
+    let popup = Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + (area.height - height) / 2,
+        width,
+        height,
+    };
+
+    let mut text = format!(
+        "Scrape from START..END (YYYY-MM-DD or RFC 3339, UTC)\ne.g. 2025-07-01..2025-09-30\n\n> {}_",
+        prompt.input
+    );
+    let style = match &prompt.error {
+        Some(error) => {
+            text.push_str(&format!("\n{error}"));
+            Style::default().fg(Color::Red)
+        }
+        None => Style::default(),
+    };
+    let paragraph = Paragraph::new(text)
+        .block(Block::default().borders(Borders::ALL).title("Scrape Date Range (Enter: Scrape, Esc: Cancel)"))
+        .style(style)
+        .wrap(ratatui::widgets::Wrap { trim: false });
+
+    f.render_widget(Clear, popup);
+    f.render_widget(paragraph, popup);
 }
 
 fn draw_header(f: &mut Frame, area: Rect, app: &App) {
-    let scrape_info = match app.current_scrape {
+    let mut scrape_info = match app.current_scrape {
         Some(id) => format!("Scrape ID: {id}"),
         None => "No scrape selected".to_string(),
     };
+    if let Some(database) = app.database_name() {
+        scrape_info = format!("DB: {database} | {scrape_info}");
+    }
     
     let view_name = match app.current_view {
         View::Org => "Organizations",
@@ -52,11 +234,15 @@ fn draw_header(f: &mut Frame, area: Rect, app: &App) {
         View::OrgDetail => "Organization Detail",
         View::RepoDetail => "Repository Detail",
         View::ContributorDetail => "Contributor Detail",
+        View::RepoHotspots => "Churn Hotspots",
+        View::Teams => "Teams",
+        View::TeamDetail => "Team Detail",
+        View::Groups => "Groups",
     };
 
     let (_item_count, selection_info) = match app.current_view {
         View::ScrapeSelection => {
-            let count = app.scrapes.len();
+            let count = app.visible_scrapes().len();
             if count > 0 {
                 (count, format!(" | {}/{} scrapes", app.scrape_selected_index + 1, count))
             } else {
@@ -124,13 +310,17 @@ fn draw_main_content(f: &mut Frame, area: Rect, app: &App) {
             ViewData::OrgDetail(_) => draw_org_detail_table(f, area, app),
             ViewData::RepoDetail(_) => draw_repo_detail_table(f, area, app),
             ViewData::ContributorDetail(_) => draw_contributor_detail_table(f, area, app),
+            ViewData::RepoHotspots(_) => draw_repo_hotspots_table(f, area, app),
+            ViewData::Teams(_) => draw_team_table(f, area, app),
+            ViewData::TeamDetail(_) => draw_team_detail_table(f, area, app),
+            ViewData::Groups(_) => draw_group_table(f, area, app),
         }
     }
 }
 
 fn draw_org_table(f: &mut Frame, area: Rect, app: &App) {
     if let ViewData::Orgs(orgs) = &app.data {
-        let header_cells = ["Organization", "Commits", "Lines", "Repos", "Contributors"]
+        let header_cells = ["Organization", "Commits", "Lines", "Excluded", "Repos", "Contributors", "Feat/Fix/Chore", "Deploys/wk", "Lead Time"]
             .iter()
             .map(|h| Cell::from(*h).style(Style::default().add_modifier(Modifier::BOLD)));
         
@@ -143,8 +333,12 @@ fn draw_org_table(f: &mut Frame, area: Rect, app: &App) {
                 Cell::from(org.name.clone()),
                 Cell::from(format_number(org.total_commits)),
                 Cell::from(format_number(org.total_lines)),
+                Cell::from(format_number(org.excluded_lines)),
                 Cell::from(format_number(org.repo_count)),
                 Cell::from(format_number(org.contributor_count)),
+                Cell::from(change_mix_display(&org.change_mix)),
+                Cell::from(deploys_display(&org.delivery)),
+                Cell::from(lead_time_display(&org.delivery)),
             ];
             let mut row = Row::new(cells).height(1);
             if i == app.selected_index {
@@ -156,11 +350,15 @@ fn draw_org_table(f: &mut Frame, area: Rect, app: &App) {
         let table = Table::new(
             rows,
             &[
-                Constraint::Percentage(30), // Organization name
-                Constraint::Percentage(15), // Commits
-                Constraint::Percentage(15), // Lines 
-                Constraint::Percentage(15), // Repos
-                Constraint::Percentage(25), // Contributors
+                Constraint::Percentage(19), // Organization name
+                Constraint::Percentage(9),  // Commits
+                Constraint::Percentage(9),  // Lines 
+                Constraint::Percentage(9),  // Excluded
+                Constraint::Percentage(7),  // Repos
+                Constraint::Percentage(11), // Contributors
+                Constraint::Percentage(14), // Feat/Fix/Chore
+                Constraint::Percentage(11), // Deploys/wk
+                Constraint::Percentage(11), // Lead Time
             ]
         )
             .header(header)
@@ -178,7 +376,7 @@ fn draw_org_table(f: &mut Frame, area: Rect, app: &App) {
 
 fn draw_repo_table(f: &mut Frame, area: Rect, app: &App) {
     if let ViewData::Repos(repos) = &app.data {
-        let header_cells = ["Organization", "Repository", "Commits", "Lines", "PRs", "Contributors"]
+        let header_cells = ["Organization", "Repository", "Language", "Commits", "Lines", "Excluded", "PRs", "Contributors", "Feat/Fix/Chore", "Deploys/wk", "Lead Time", "CI Runs", "CI Pass", "CI p50", "Flaky"]
             .iter()
             .map(|h| Cell::from(*h).style(Style::default().add_modifier(Modifier::BOLD)));
         
@@ -189,11 +387,20 @@ fn draw_repo_table(f: &mut Frame, area: Rect, app: &App) {
         let rows: Vec<Row> = repos.iter().enumerate().map(|(i, repo)| {
             let cells = vec![
                 Cell::from(repo.org_name.clone()),
-                Cell::from(repo.repo_name.clone()),
+                Cell::from(repo_name_display(repo)),
+                Cell::from(repo.language.clone().unwrap_or_default()),
                 Cell::from(format_number(repo.commits)),
                 Cell::from(format_number(repo.lines)),
+                Cell::from(format_number(repo.excluded_lines)),
                 Cell::from(format_number(repo.prs)),
                 Cell::from(format_number(repo.contributor_count)),
+                Cell::from(change_mix_display(&repo.change_mix)),
+                Cell::from(deploys_display(&repo.delivery)),
+                Cell::from(lead_time_display(&repo.delivery)),
+                Cell::from(format_number(repo.ci.runs)),
+                Cell::from(ci_pass_display(&repo.ci)),
+                Cell::from(duration_display(repo.ci.median_duration_secs)),
+                Cell::from(format_number(repo.ci.flaky_reruns)),
             ];
             let mut row = Row::new(cells).height(1);
             if i == app.selected_index {
@@ -205,16 +412,25 @@ fn draw_repo_table(f: &mut Frame, area: Rect, app: &App) {
         let table = Table::new(
             rows,
             &[
-                Constraint::Percentage(20), // Organization name
-                Constraint::Percentage(25), // Repository name
-                Constraint::Percentage(12), // Commits
-                Constraint::Percentage(12), // Lines 
-                Constraint::Percentage(12), // PRs
-                Constraint::Percentage(19), // Contributors
+                Constraint::Percentage(8),  // Organization name
+                Constraint::Percentage(11), // Repository name
+                Constraint::Percentage(6),  // Language
+                Constraint::Percentage(5),  // Commits
+                Constraint::Percentage(5),  // Lines 
+                Constraint::Percentage(5),  // Excluded
+                Constraint::Percentage(4),  // PRs
+                Constraint::Percentage(6),  // Contributors
+                Constraint::Percentage(10), // Feat/Fix/Chore
+                Constraint::Percentage(7),  // Deploys/wk
+                Constraint::Percentage(7),  // Lead Time
+                Constraint::Percentage(7),  // CI Runs
+                Constraint::Percentage(7),  // CI Pass
+                Constraint::Percentage(6),  // CI p50
+                Constraint::Percentage(6),  // Flaky
             ]
         )
             .header(header)
-            .block(Block::default().borders(Borders::ALL).title("Repositories"))
+            .block(Block::default().borders(Borders::ALL).title(format!("Repositories{}", repo_filter_display(app))))
             .column_spacing(1);
 
         f.render_widget(table, area);
@@ -228,7 +444,7 @@ fn draw_repo_table(f: &mut Frame, area: Rect, app: &App) {
 
 fn draw_contributor_table(f: &mut Frame, area: Rect, app: &App) {
     if let ViewData::Contributors(contributors) = &app.data {
-        let header_cells = ["Username", "Commits", "Lines", "Repos", "Organizations"]
+        let header_cells = ["Username", "Commits", "Lines", "Excluded", "Repos", "Feat/Fix/Chore", "Organizations"]
             .iter()
             .map(|h| Cell::from(*h).style(Style::default().add_modifier(Modifier::BOLD)));
         
@@ -250,7 +466,9 @@ fn draw_contributor_table(f: &mut Frame, area: Rect, app: &App) {
                 Cell::from(contributor.username.clone()),
                 Cell::from(format_number(contributor.total_commits)),
                 Cell::from(format_number(contributor.total_lines)),
+                Cell::from(format_number(contributor.excluded_lines)),
                 Cell::from(format_number(contributor.repo_count)),
+                Cell::from(change_mix_display(&contributor.change_mix)),
                 Cell::from(orgs_display),
             ];
             let mut row = Row::new(cells).height(1);
@@ -263,11 +481,13 @@ fn draw_contributor_table(f: &mut Frame, area: Rect, app: &App) {
         let table = Table::new(
             rows,
             &[
-                Constraint::Percentage(20), // Username
-                Constraint::Percentage(15), // Commits
-                Constraint::Percentage(15), // Lines 
-                Constraint::Percentage(15), // Repos
-                Constraint::Percentage(35), // Organizations
+                Constraint::Percentage(18), // Username
+                Constraint::Percentage(11), // Commits
+                Constraint::Percentage(11), // Lines 
+                Constraint::Percentage(10), // Excluded
+                Constraint::Percentage(9),  // Repos
+                Constraint::Percentage(15), // Feat/Fix/Chore
+                Constraint::Percentage(26), // Organizations
             ]
         )
             .header(header)
@@ -284,7 +504,8 @@ fn draw_contributor_table(f: &mut Frame, area: Rect, app: &App) {
 }
 
 fn draw_scrape_selection_table(f: &mut Frame, area: Rect, app: &App) {
-    if app.scrapes.is_empty() {
+    let scrapes = app.visible_scrapes();
+    if scrapes.is_empty() {
         let placeholder = Paragraph::new("No scrapes available")
             .block(Block::default().borders(Borders::ALL))
             .alignment(Alignment::Center);
@@ -292,7 +513,7 @@ fn draw_scrape_selection_table(f: &mut Frame, area: Rect, app: &App) {
         return;
     }
 
-    let header_cells = ["Scrape ID", "Start Date", "End Date", "Repository Count"]
+    let header_cells = ["Scrape ID", "Profile", "Start Date", "End Date", "Repository Count"]
         .iter()
         .map(|h| Cell::from(*h).style(Style::default().add_modifier(Modifier::BOLD)));
     
@@ -300,9 +521,10 @@ fn draw_scrape_selection_table(f: &mut Frame, area: Rect, app: &App) {
         .style(Style::default().bg(Color::Blue).fg(Color::White))
         .height(1);
 
-    let rows: Vec<Row> = app.scrapes.iter().enumerate().map(|(i, scrape)| {
+    let rows: Vec<Row> = scrapes.iter().enumerate().map(|(i, scrape)| {
         let cells = vec![
             Cell::from(scrape.id.to_string()),
+            Cell::from(scrape.profile.clone().unwrap_or_else(|| "-".to_string())),
             Cell::from(scrape.start_dt.format("%Y-%m-%d %H:%M").to_string()),
             Cell::from(scrape.end_dt.format("%Y-%m-%d %H:%M").to_string()),
             Cell::from(scrape.repo_count.to_string()),
@@ -314,17 +536,23 @@ fn draw_scrape_selection_table(f: &mut Frame, area: Rect, app: &App) {
         row
     }).collect();
 
+    let title = match &app.scrape_profile_filter {
+        None => "Available Scrapes".to_string(),
+        Some(Some(profile)) => format!("Available Scrapes | profile: {profile}"),
+        Some(None) => "Available Scrapes | no profile".to_string(),
+    };
     let table = Table::new(
         rows,
         &[
-            Constraint::Percentage(15), // Scrape ID
-            Constraint::Percentage(30), // Start Date
-            Constraint::Percentage(30), // End Date
-            Constraint::Percentage(25), // Repository Count
+            Constraint::Percentage(10), // Scrape ID
+            Constraint::Percentage(20), // Profile
+            Constraint::Percentage(25), // Start Date
+            Constraint::Percentage(25), // End Date
+            Constraint::Percentage(20), // Repository Count
         ]
     )
         .header(header)
-        .block(Block::default().borders(Borders::ALL).title("Available Scrapes"))
+        .block(Block::default().borders(Borders::ALL).title(title))
         .column_spacing(1);
 
     f.render_widget(table, area);
@@ -339,6 +567,7 @@ fn draw_footer(f: &mut Frame, area: Rect, app: &App) {
             SortField::Lines => "Lines", 
             SortField::Repos => "Repos",
             SortField::Prs => "PRs",
+            SortField::Language => "Language",
         },
         match app.sort_order {
             SortOrder::Ascending => "↑",
@@ -348,11 +577,23 @@ fn draw_footer(f: &mut Frame, area: Rect, app: &App) {
 
     // Split footer into two lines for better readability
     let footer_line1 = if app.current_view == View::ScrapeSelection {
-        "Navigation: ↑↓/j/k | Enter: Select | Esc/t: Back | q: Quit"
-    } else if matches!(app.current_view, View::OrgDetail | View::RepoDetail | View::ContributorDetail) {
+        "Navigation: ↑↓/j/k | Enter: Select | P: Filter by Profile | B: Switch Database | Esc/t: Back | q: Quit"
+    } else if app.current_view == View::OrgDetail {
+        "Navigation: ↑↓/j/k | Enter: Drill Down | Esc: Back | Sort: s/n/c/l/p/g | Filter: L/a/f | F5: Refresh | q: Quit"
+    } else if app.current_view == View::Repo {
+        "Navigation: ↑↓/j/k | Enter: Drill Down | Views: o/r/u/T/G | t: Scrapes | Sort: s/n/c/l/p/g | Filter: L/a/f | S: New Scrape | D: Scrape Range | F5: Refresh | q: Quit"
+    } else if app.current_view == View::RepoDetail {
+        "Navigation: ↑↓/j/k | Enter: Drill Down | h: Hotspots | Esc: Back | Sort: s/n/c/l/p | F5: Refresh | q: Quit"
+    } else if app.current_view == View::RepoHotspots {
+        "Navigation: ↑↓/j/k | Esc: Back | Sort: s/n/c/l | q: Quit"
+    } else if app.current_view == View::Groups {
+        "Navigation: ↑↓/j/k | Views: o/r/u/T/G | t: Scrapes | Sort: s/n/c/l/p/R | S: New Scrape | D: Scrape Range | F5: Refresh | q: Quit"
+    } else if app.current_view == View::Teams {
+        "Navigation: ↑↓/j/k | Enter: Drill Down | Views: o/r/u/T/G | t: Scrapes | Sort: s/n/c/l/p/R | S: New Scrape | D: Scrape Range | F5: Refresh | q: Quit"
+    } else if matches!(app.current_view, View::OrgDetail | View::RepoDetail | View::ContributorDetail | View::TeamDetail) {
         "Navigation: ↑↓/j/k | Enter: Drill Down | Esc: Back | Sort: s/n/c/l/p/R | F5: Refresh | q: Quit"
     } else {
-        "Navigation: ↑↓/j/k | Enter: Drill Down | Views: o/r/u | t: Scrapes | Sort: s/n/c/l/p/R | S: New Scrape | F5: Refresh | q: Quit"
+        "Navigation: ↑↓/j/k | Enter: Drill Down | Views: o/r/u/T/G | t: Scrapes | Sort: s/n/c/l/p/R | S: New Scrape | D: Scrape Range | F5: Refresh | q: Quit"
     };
     let footer_line2 = sort_info.to_string();
     
@@ -368,7 +609,7 @@ fn draw_footer(f: &mut Frame, area: Rect, app: &App) {
 
 fn draw_org_detail_table(f: &mut Frame, area: Rect, app: &App) {
     if let ViewData::OrgDetail(detail) = &app.data {
-        let header = Row::new(vec!["Repository", "Commits", "Lines", "PRs", "Contributors"])
+        let header = Row::new(vec!["Repository", "Language", "Commits", "Lines", "Excluded", "PRs", "Contributors", "Feat/Fix/Chore", "Deploys/wk", "Lead Time", "CI Runs", "CI Pass", "CI p50", "Flaky"])
             .style(Style::default().fg(Color::Blue).add_modifier(Modifier::BOLD));
         
         let rows: Vec<Row> = detail.repos.iter().enumerate().map(|(i, repo)| {
@@ -379,26 +620,44 @@ fn draw_org_detail_table(f: &mut Frame, area: Rect, app: &App) {
             };
             
             Row::new(vec![
-                Cell::from(repo.repo_name.clone()),
+                Cell::from(repo_name_display(repo)),
+                Cell::from(repo.language.clone().unwrap_or_default()),
                 Cell::from(format_number(repo.commits)),
                 Cell::from(format_number(repo.lines)),
+                Cell::from(format_number(repo.excluded_lines)),
                 Cell::from(format_number(repo.prs)),
                 Cell::from(format_number(repo.contributor_count)),
+                Cell::from(change_mix_display(&repo.change_mix)),
+                Cell::from(deploys_display(&repo.delivery)),
+                Cell::from(lead_time_display(&repo.delivery)),
+                Cell::from(format_number(repo.ci.runs)),
+                Cell::from(ci_pass_display(&repo.ci)),
+                Cell::from(duration_display(repo.ci.median_duration_secs)),
+                Cell::from(format_number(repo.ci.flaky_reruns)),
             ]).style(style)
         }).collect();
 
         let table = Table::new(
             rows,
             [
-                Constraint::Percentage(30), // Repository
-                Constraint::Percentage(20), // Commits
-                Constraint::Percentage(20), // Lines
-                Constraint::Percentage(15), // PRs
-                Constraint::Percentage(15), // Contributors
+                Constraint::Percentage(13), // Repository
+                Constraint::Percentage(6),  // Language
+                Constraint::Percentage(6),  // Commits
+                Constraint::Percentage(6),  // Lines
+                Constraint::Percentage(6),  // Excluded
+                Constraint::Percentage(5),  // PRs
+                Constraint::Percentage(7),  // Contributors
+                Constraint::Percentage(8),  // Feat/Fix/Chore
+                Constraint::Percentage(7),  // Deploys/wk
+                Constraint::Percentage(7),  // Lead Time
+                Constraint::Percentage(6),  // CI Runs
+                Constraint::Percentage(6),  // CI Pass
+                Constraint::Percentage(6),  // CI p50
+                Constraint::Percentage(5),  // Flaky
             ]
         )
         .header(header)
-        .block(Block::default().borders(Borders::ALL).title(format!("Repositories in {}", detail.org_name)));
+        .block(Block::default().borders(Borders::ALL).title(format!("Repositories in {}{}", detail.org_name, repo_filter_display(app))));
 
         f.render_widget(table, area);
     }
@@ -406,7 +665,20 @@ fn draw_org_detail_table(f: &mut Frame, area: Rect, app: &App) {
 
 fn draw_repo_detail_table(f: &mut Frame, area: Rect, app: &App) {
     if let ViewData::RepoDetail(detail) = &app.data {
-        let header = Row::new(vec!["Contributor", "Commits", "Lines"])
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(4), // Ownership coverage
+                Constraint::Min(0),    // Contributors
+            ])
+            .split(area);
+
+        let ownership = Paragraph::new(ownership_lines(&detail.ownership).join("\n"))
+            .block(Block::default().borders(Borders::ALL).title("CODEOWNERS Coverage"))
+            .wrap(ratatui::widgets::Wrap { trim: true });
+        f.render_widget(ownership, chunks[0]);
+
+        let header = Row::new(vec!["Contributor", "Commits", "Lines", "PRs"])
             .style(Style::default().fg(Color::Blue).add_modifier(Modifier::BOLD));
         
         let rows: Vec<Row> = detail.contributors.iter().enumerate().map(|(i, contributor)| {
@@ -420,6 +692,7 @@ fn draw_repo_detail_table(f: &mut Frame, area: Rect, app: &App) {
                 Cell::from(contributor.username.clone()),
                 Cell::from(format_number(contributor.commits)),
                 Cell::from(format_number(contributor.lines)),
+                Cell::from(format_number(contributor.prs)),
             ]).style(style)
         }).collect();
 
@@ -427,20 +700,39 @@ fn draw_repo_detail_table(f: &mut Frame, area: Rect, app: &App) {
             rows,
             [
                 Constraint::Percentage(40), // Contributor
-                Constraint::Percentage(30), // Commits
-                Constraint::Percentage(30), // Lines
+                Constraint::Percentage(20), // Commits
+                Constraint::Percentage(20), // Lines
+                Constraint::Percentage(20), // PRs
             ]
         )
         .header(header)
         .block(Block::default().borders(Borders::ALL).title(format!("Contributors to {}/{}", detail.org_name, detail.repo_name)));
 
-        f.render_widget(table, area);
+        f.render_widget(table, chunks[1]);
     }
 }
 
 fn draw_contributor_detail_table(f: &mut Frame, area: Rect, app: &App) {
     if let ViewData::ContributorDetail(detail) = &app.data {
-        let header = Row::new(vec!["Repository", "Commits", "Lines"])
+        let area = match &detail.timing {
+            Some(timing) => {
+                let chunks = Layout::default()
+                    .direction(Direction::Vertical)
+                    .constraints([
+                        Constraint::Length(5), // Activity timing
+                        Constraint::Min(0),    // Contributions
+                    ])
+                    .split(area);
+
+                let activity = Paragraph::new(timing_lines(timing).join("\n"))
+                    .block(Block::default().borders(Borders::ALL).title("Activity Timing"));
+                f.render_widget(activity, chunks[0]);
+                chunks[1]
+            }
+            None => area,
+        };
+
+        let header = Row::new(vec!["Repository", "Commits", "Lines", "PRs"])
             .style(Style::default().fg(Color::Blue).add_modifier(Modifier::BOLD));
         
         let rows: Vec<Row> = detail.contributions.iter().enumerate().map(|(i, contribution)| {
@@ -456,15 +748,17 @@ fn draw_contributor_detail_table(f: &mut Frame, area: Rect, app: &App) {
                 Cell::from(repo_display),
                 Cell::from(format_number(contribution.commits)),
                 Cell::from(format_number(contribution.lines)),
+                Cell::from(format_number(contribution.prs)),
             ]).style(style)
         }).collect();
 
         let table = Table::new(
             rows,
             [
-                Constraint::Percentage(50), // Repository
-                Constraint::Percentage(25), // Commits
-                Constraint::Percentage(25), // Lines
+                Constraint::Percentage(46), // Repository
+                Constraint::Percentage(18), // Commits
+                Constraint::Percentage(18), // Lines
+                Constraint::Percentage(18), // PRs
             ]
         )
         .header(header)
@@ -472,4 +766,185 @@ fn draw_contributor_detail_table(f: &mut Frame, area: Rect, app: &App) {
 
         f.render_widget(table, area);
     }
-}
\ No newline at end of file
+}
+
+fn draw_repo_hotspots_table(f: &mut Frame, area: Rect, app: &App) {
+    if let ViewData::RepoHotspots(hotspots) = &app.data {
+        let header = Row::new(vec!["Path", "Type", "Churn", "+/-", "Commits", "Top Contributors"])
+            .style(Style::default().fg(Color::Blue).add_modifier(Modifier::BOLD));
+
+        let rows: Vec<Row> = hotspots.paths.iter().enumerate().map(|(i, path)| {
+            let style = if i == app.selected_index {
+                Style::default().bg(Color::DarkGray).fg(Color::White)
+            } else {
+                Style::default()
+            };
+
+            let path_display = if path.is_directory {
+                format!("{}/", path.path)
+            } else {
+                path.path.clone()
+            };
+
+            Row::new(vec![
+                Cell::from(path_display),
+                Cell::from(if path.is_directory { "dir" } else { "file" }),
+                Cell::from(format_number(path.additions + path.deletions)),
+                Cell::from(format!("+{} -{}", format_number(path.additions), format_number(path.deletions))),
+                Cell::from(format_number(path.commits)),
+                Cell::from(path.top_contributors.join(", ")),
+            ]).style(style)
+        }).collect();
+
+        let table = Table::new(
+            rows,
+            [
+                Constraint::Percentage(36), // Path
+                Constraint::Percentage(6),  // Type
+                Constraint::Percentage(9),  // Churn
+                Constraint::Percentage(14), // +/-
+                Constraint::Percentage(8),  // Commits
+                Constraint::Percentage(27), // Top Contributors
+            ]
+        )
+        .header(header)
+        .block(Block::default().borders(Borders::ALL).title(format!("Churn hotspots in {}/{}", hotspots.org_name, hotspots.repo_name)));
+
+        f.render_widget(table, area);
+    }
+}
+
+fn draw_team_table(f: &mut Frame, area: Rect, app: &App) {
+    if let ViewData::Teams(teams) = &app.data {
+        let header_cells = ["Organization", "Team", "Members", "Commits", "Lines", "PRs", "Reviews"]
+            .iter()
+            .map(|h| Cell::from(*h).style(Style::default().add_modifier(Modifier::BOLD)));
+
+        let header = Row::new(header_cells)
+            .style(Style::default().bg(Color::Blue).fg(Color::White))
+            .height(1);
+
+        let rows: Vec<Row> = teams.iter().enumerate().map(|(i, team)| {
+            let cells = vec![
+                Cell::from(team.org_name.clone()),
+                Cell::from(team.team_name.clone()),
+                Cell::from(format_number(team.member_count)),
+                Cell::from(format_number(team.commits)),
+                Cell::from(format_number(team.lines)),
+                Cell::from(format_number(team.prs)),
+                Cell::from(format_number(team.reviews)),
+            ];
+            let mut row = Row::new(cells).height(1);
+            if i == app.selected_index {
+                row = row.style(Style::default().bg(Color::DarkGray).fg(Color::White));
+            }
+            row
+        }).collect();
+
+        let table = Table::new(
+            rows,
+            &[
+                Constraint::Percentage(18), // Organization name
+                Constraint::Percentage(22), // Team name
+                Constraint::Percentage(10), // Members
+                Constraint::Percentage(12), // Commits
+                Constraint::Percentage(14), // Lines
+                Constraint::Percentage(12), // PRs
+                Constraint::Percentage(12), // Reviews
+            ]
+        )
+            .header(header)
+            .block(Block::default().borders(Borders::ALL).title("Teams"))
+            .column_spacing(1);
+
+        f.render_widget(table, area);
+    }
+}
+
+fn draw_team_detail_table(f: &mut Frame, area: Rect, app: &App) {
+    if let ViewData::TeamDetail(detail) = &app.data {
+        let header = Row::new(vec!["Member", "Commits", "Lines", "PRs", "Reviews"])
+            .style(Style::default().fg(Color::Blue).add_modifier(Modifier::BOLD));
+
+        let rows: Vec<Row> = detail.members.iter().enumerate().map(|(i, member)| {
+            let style = if i == app.selected_index {
+                Style::default().bg(Color::DarkGray).fg(Color::White)
+            } else {
+                Style::default()
+            };
+
+            Row::new(vec![
+                Cell::from(member.username.clone()),
+                Cell::from(format_number(member.commits)),
+                Cell::from(format_number(member.lines)),
+                Cell::from(format_number(member.prs)),
+                Cell::from(format_number(member.reviews)),
+            ]).style(style)
+        }).collect();
+
+        let table = Table::new(
+            rows,
+            [
+                Constraint::Percentage(32), // Member
+                Constraint::Percentage(17), // Commits
+                Constraint::Percentage(17), // Lines
+                Constraint::Percentage(17), // PRs
+                Constraint::Percentage(17), // Reviews
+            ]
+        )
+        .header(header)
+        .block(Block::default().borders(Borders::ALL).title(format!("Members of {} ({})", detail.team_name, detail.org_name)));
+
+        f.render_widget(table, area);
+    }
+}
+
+fn draw_group_table(f: &mut Frame, area: Rect, app: &App) {
+    if let ViewData::Groups(groups) = &app.data {
+        let header_cells = ["Group", "Kind", "Size", "Commits", "Lines", "PRs"]
+            .iter()
+            .map(|h| Cell::from(*h).style(Style::default().add_modifier(Modifier::BOLD)));
+
+        let header = Row::new(header_cells)
+            .style(Style::default().bg(Color::Blue).fg(Color::White))
+            .height(1);
+
+        let rows: Vec<Row> = groups.iter().enumerate().map(|(i, group)| {
+            let size = if group.kind == GROUP_KIND_TEAM {
+                format!("{} members", group.size)
+            } else {
+                format!("{} repos", group.size)
+            };
+            let cells = vec![
+                Cell::from(group.name.clone()),
+                Cell::from(group.kind.clone()),
+                Cell::from(size),
+                Cell::from(format_number(group.commits)),
+                Cell::from(format_number(group.lines)),
+                Cell::from(format_number(group.prs)),
+            ];
+            let mut row = Row::new(cells).height(1);
+            if i == app.selected_index {
+                row = row.style(Style::default().bg(Color::DarkGray).fg(Color::White));
+            }
+            row
+        }).collect();
+
+        let table = Table::new(
+            rows,
+            &[
+                Constraint::Percentage(30), // Group name
+                Constraint::Percentage(10), // Kind
+                Constraint::Percentage(15), // Size
+                Constraint::Percentage(15), // Commits
+                Constraint::Percentage(15), // Lines
+                Constraint::Percentage(15), // PRs
+            ]
+        )
+            .header(header)
+            .block(Block::default().borders(Borders::ALL).title("Teams and Repo Groups from Config"))
+            .column_spacing(1);
+
+        f.render_widget(table, area);
+    }
+}
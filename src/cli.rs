@@ -0,0 +1,163 @@
+//! Command line parsing. Without a subcommand org-pulse starts the TUI.
+
+use anyhow::{anyhow, Result};
+
+use crate::date_range::{parse_bound, DateRange};
+use crate::demo::DemoOptions;
+
+pub const USAGE: &str = "\
+Usage: org-pulse [--db NAME|PATH] [--config PATH] [COMMAND]
+
+Options:
+  --db NAME|PATH                    Database to use: a name from `databases` in the config, or a
+                                    path. Defaults to $ORG_PULSE_DB, then ./org-pulse.db if it
+                                    exists, then $XDG_DATA_HOME/org-pulse/org-pulse.db
+  --config PATH                     Config file to use. Defaults to $ORG_PULSE_CONFIG, then
+                                    ./config.toml if it exists, then
+                                    $XDG_CONFIG_HOME/org-pulse/config.toml
+
+Commands:
+  (none)                            Start the TUI
+  scrape [--profile NAME] [--since DATE] [--until DATE]
+                                    Run one scrape and exit. DATE is YYYY-MM-DD or an RFC 3339
+                                    timestamp; defaults to the week before --until, or the
+                                    profile's window
+  plan [--profile NAME] [--since DATE] [--until DATE]
+                                    Show the repos a scrape would cover and estimate its API
+                                    calls against the remaining quota, without scraping
+  demo [--seed N] [--orgs N] [--repos N] [--contributors N] [--weeks N] [--until DATE]
+                                    Fill an empty database with made-up orgs, repos, contributors
+                                    and weekly scrapes. The same options give the same data
+  daemon                            Run scrapes on `scrape_schedule` and each profile's schedule
+  serve                             Receive GitHub webhooks on `webhook_listen_addr`
+  help                              Show this message";
+
+/// A command and the options that apply to every command
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cli {
+    pub db: Option<String>,
+    pub config: Option<String>,
+    pub command: Command,
+}
+
+impl Cli {
+    /// Parses the arguments following the program name. `--db` and `--config` can go before
+    /// or after the command.
+    pub fn parse<I: IntoIterator<Item = String>>(args: I) -> Result<Cli> {
+        let mut db = None;
+        let mut config = None;
+        let mut rest = Vec::new();
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let (flag, value) = match arg.split_once('=') {
+                Some((flag, value)) => (flag.to_string(), Some(value.to_string())),
+                None => (arg.clone(), None),
+            };
+            let target = match flag.as_str() {
+                "--db" => &mut db,
+                "--config" => &mut config,
+                _ => {
+                    rest.push(arg);
+                    continue;
+                }
+            };
+            let value = value.or_else(|| args.next()).ok_or_else(|| anyhow!("`{flag}` needs a value"))?;
+            *target = Some(value);
+        }
+        Ok(Cli { db, config, command: Command::parse(rest)? })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    Tui,
+    /// `range` is `None` when neither bound was given, so the profile's window applies
+    Scrape { range: Option<DateRange>, profile: Option<String> },
+    /// A dry run of `Scrape` with the same options
+    Plan { range: Option<DateRange>, profile: Option<String> },
+    /// Generate demo data into an empty database
+    Demo(DemoOptions),
+    Daemon,
+    Serve,
+    Help,
+}
+
+impl Command {
+    /// Parses the arguments following the program name.
+    pub fn parse<I: IntoIterator<Item = String>>(args: I) -> Result<Command> {
+        let mut args = args.into_iter();
+        let Some(command) = args.next() else {
+            return Ok(Command::Tui);
+        };
+
+        let command = match command.as_str() {
+            "scrape" => {
+                let (range, profile) = parse_scrape_options(&command, &mut args)?;
+                Command::Scrape { range, profile }
+            }
+            "plan" => {
+                let (range, profile) = parse_scrape_options(&command, &mut args)?;
+                Command::Plan { range, profile }
+            }
+            "demo" => Command::Demo(parse_demo_options(&mut args)?),
+            "daemon" => Command::Daemon,
+            "serve" => Command::Serve,
+            "help" | "--help" | "-h" => Command::Help,
+            other => return Err(anyhow!("Unknown command `{other}`")),
+        };
+
+        if let Some(extra) = args.next() {
+            return Err(anyhow!("Unexpected argument `{extra}`"));
+        }
+        Ok(command)
+    }
+}
+
+/// Parses the `--profile`, `--since` and `--until` options shared by `scrape` and `plan`.
+fn parse_scrape_options<I: Iterator<Item = String>>(command: &str, args: &mut I) -> Result<(Option<DateRange>, Option<String>)> {
+    let mut since = None;
+    let mut until = None;
+    let mut profile = None;
+    while let Some(arg) = args.next() {
+        let (flag, value) = match arg.split_once('=') {
+            Some((flag, value)) => (flag.to_string(), Some(value.to_string())),
+            None => (arg, None),
+        };
+        let target = match flag.as_str() {
+            "--since" | "--from" => &mut since,
+            "--until" | "--to" => &mut until,
+            "--profile" | "-p" => &mut profile,
+            _ => return Err(anyhow!("Unknown option `{flag}` for {command}")),
+        };
+        let value = value.or_else(|| args.next()).ok_or_else(|| anyhow!("`{flag}` needs a value"))?;
+        *target = Some(value);
+    }
+    let range = match (&since, &until) {
+        (None, None) => None,
+        _ => Some(DateRange::parse(since.as_deref(), until.as_deref())?),
+    };
+    Ok((range, profile))
+}
+
+/// Parses the options of `demo`, starting from the defaults.
+fn parse_demo_options<I: Iterator<Item = String>>(args: &mut I) -> Result<DemoOptions> {
+    let mut options = DemoOptions::default();
+    while let Some(arg) = args.next() {
+        let (flag, value) = match arg.split_once('=') {
+            Some((flag, value)) => (flag.to_string(), Some(value.to_string())),
+            None => (arg, None),
+        };
+        let value = value.or_else(|| args.next()).ok_or_else(|| anyhow!("`{flag}` needs a value"))?;
+        let number = || value.parse::<usize>().map_err(|_| anyhow!("`{flag}` needs a number, got `{value}`"));
+        match flag.as_str() {
+            "--seed" => options.seed = value.parse().map_err(|_| anyhow!("`{flag}` needs a number, got `{value}`"))?,
+            "--orgs" => options.orgs = number()?,
+            "--repos" => options.repos_per_org = number()?,
+            "--contributors" => options.contributors = number()?,
+            "--weeks" => options.weeks = number()?,
+            "--until" | "--to" => options.end = parse_bound(&value, true)?,
+            _ => return Err(anyhow!("Unknown option `{flag}` for demo")),
+        }
+    }
+    Ok(options)
+}
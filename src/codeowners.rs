@@ -0,0 +1,99 @@
+//! CODEOWNERS parsing and path ownership lookup.
+//!
+//! Follows GitHub's rules: patterns use gitignore syntax, a pattern without a
+//! leading or inner slash matches at any depth, and the last matching rule wins.
+
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+
+/// Locations GitHub checks for a CODEOWNERS file, in priority order
+pub const CODEOWNERS_PATHS: [&str; 3] = [".github/CODEOWNERS", "CODEOWNERS", "docs/CODEOWNERS"];
+
+#[derive(Debug, Clone)]
+pub struct CodeOwnersRule {
+    pub pattern: String,
+    /// `@user`, `@org/team` or email owners as written in the file. Empty means explicitly unowned.
+    pub owners: Vec<String>,
+    matcher: GlobSet,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct CodeOwners {
+    pub rules: Vec<CodeOwnersRule>,
+}
+
+impl CodeOwnersRule {
+    pub fn new(pattern: &str, owners: Vec<String>) -> Option<CodeOwnersRule> {
+        let matcher = pattern_matcher(pattern)?;
+        Some(CodeOwnersRule { pattern: pattern.to_string(), owners, matcher })
+    }
+
+    pub fn matches(&self, path: &str) -> bool {
+        self.matcher.is_match(path)
+    }
+}
+
+impl CodeOwners {
+    /// Parses the contents of a CODEOWNERS file, skipping comments and patterns
+    /// that can't be compiled.
+    pub fn parse(content: &str) -> CodeOwners {
+        let rules = content.lines()
+            .map(|line| line.split(" #").next().unwrap_or_default().trim())
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .filter_map(|line| {
+                let mut parts = line.split_whitespace();
+                let pattern = parts.next()?;
+                let owners = parts.map(|owner| owner.to_string()).collect();
+                CodeOwnersRule::new(pattern, owners)
+            })
+            .collect();
+
+        CodeOwners { rules }
+    }
+
+    /// Owners of a path according to the last matching rule, if any rule assigns owners.
+    pub fn owners_for(&self, path: &str) -> Option<&[String]> {
+        self.rules.iter()
+            .rev()
+            .find(|rule| rule.matches(path))
+            .map(|rule| rule.owners.as_slice())
+            .filter(|owners| !owners.is_empty())
+    }
+}
+
+/// Checks whether a GitHub username is listed directly as one of the owners.
+/// Team owners (`@org/team`) never match here.
+pub fn is_user_owner(owners: &[String], username: &str) -> bool {
+    owners.iter().any(|owner| {
+        owner.strip_prefix('@').is_some_and(|login| login.eq_ignore_ascii_case(username))
+    })
+}
+
+/// Translates a gitignore-style CODEOWNERS pattern into a glob set over repo-relative paths.
+fn pattern_matcher(pattern: &str) -> Option<GlobSet> {
+    let dir_only = pattern.ends_with('/');
+    let trimmed = pattern.trim_end_matches('/');
+    let anchored = trimmed.starts_with('/') || trimmed.contains('/');
+    let base = trimmed.trim_start_matches('/');
+    if base.is_empty() {
+        return None;
+    }
+
+    let base = if anchored { base.to_string() } else { format!("**/{base}") };
+    let globs = if base.ends_with("**") {
+        vec![base]
+    } else if base.ends_with('*') && !dir_only {
+        // A trailing `*` only covers direct children, e.g. `docs/*` skips `docs/a/b.md`
+        vec![base]
+    } else if dir_only {
+        vec![format!("{base}/**")]
+    } else {
+        // Could name either a file or a directory
+        vec![format!("{base}/**"), base]
+    };
+
+    let mut builder = GlobSetBuilder::new();
+    for glob in globs {
+        builder.add(GlobBuilder::new(&glob).literal_separator(true).build().ok()?);
+    }
+    builder.build().ok()
+}
@@ -1,9 +1,12 @@
+use std::collections::BTreeMap;
+
 use serde::{Deserialize, Serialize};
 use anyhow::Result;
 
-const CONFIG_APTH: &str = "./config.toml";
+use crate::paths::config_path;
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
 pub struct AppConfig {
     pub organizations: Vec<String>,
     pub days: usize,
@@ -12,6 +15,119 @@ pub struct AppConfig {
     pub ignored_org_pattern: String,
     pub ignored_user_patterns: String,
     pub ignored_repo_patterns: String,
+    /// Skip repositories that are forks of another repository
+    pub skip_forks: bool,
+    /// Skip archived repositories
+    pub skip_archived: bool,
+    /// When non-empty, only repositories tagged with at least one of these topics are scraped
+    pub allowed_topics: Vec<String>,
+    /// Repositories tagged with any of these topics are skipped
+    pub denied_topics: Vec<String>,
+    /// How many leading path components make up a directory in churn hotspots
+    pub churn_path_depth: usize,
+    /// Commits per repo whose line stats are fetched, at one request each. Later commits are
+    /// stored from the commit listing without line counts; `0` skips line stats altogether.
+    pub max_commit_details: usize,
+    /// Glob patterns for lockfiles, vendored and generated files whose lines are
+    /// reported separately instead of counting towards contributor line totals
+    pub excluded_line_patterns: Vec<String>,
+    /// Deployment environments counted towards deploy frequency. When empty, deployments
+    /// aren't fetched and releases stand in for deploys.
+    pub deployment_environments: Vec<String>,
+    /// Cron expression (`minute hour day-of-month month day-of-week`, in UTC) for `org-pulse daemon` scrapes
+    pub scrape_schedule: String,
+    /// File `org-pulse daemon` appends its log to
+    pub daemon_log_file: String,
+    /// Address `org-pulse serve` listens on for GitHub webhook deliveries
+    pub webhook_listen_addr: String,
+    /// Optional CSV of `team,username` rows, e.g. exported from an HR system, adding to `teams`
+    pub teams_csv: String,
+    /// Hour-of-day and weekday activity in the contributor detail view. Off by default
+    /// because when people work is sensitive.
+    pub activity_timing: bool,
+    /// UTC offset such as `-05:00` for contributors without an entry in `contributor_timezones`
+    pub default_timezone: String,
+    /// Local hour the working day starts at
+    pub working_hours_start: u32,
+    /// Local hour the working day ends at; commits from this hour on are after hours
+    pub working_hours_end: u32,
+    /// UTC offsets keyed by GitHub username
+    pub contributor_timezones: BTreeMap<String, String>,
+    /// Teams defined by hand, for orgs whose GitHub teams aren't kept accurate
+    pub teams: Vec<TeamConfig>,
+    /// Named groups of repositories, matched by globs over `org/repo`
+    pub repo_groups: Vec<RepoGroupConfig>,
+    /// Named scrapes with their own orgs, repos, user patterns and window
+    pub profiles: Vec<ScrapeProfile>,
+    /// Whether scrapes cache GitHub responses in the database and revalidate them with conditional requests
+    pub http_cache: HttpCacheMode,
+    /// Whether GitHub API traffic is recorded to, or replayed from, `api_recording_dir`
+    pub api_recording: RecordingMode,
+    /// Directory of recorded GitHub responses, one JSON file per request
+    pub api_recording_dir: String,
+    /// Named databases, e.g. one per client, that `--db NAME` opens and the TUI switches between
+    pub databases: Vec<DatabaseConfig>,
+}
+
+/// How scrapes use the HTTP cache
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum HttpCacheMode {
+    /// Every request goes to GitHub
+    Off,
+    /// Cached responses are revalidated with `If-None-Match`/`If-Modified-Since`, and reused on `304`
+    #[default]
+    On,
+    /// Only cached responses are served and nothing is sent to GitHub, for re-processing offline
+    Offline,
+}
+
+/// Recording of GitHub API traffic, for regression tests and demos without network access
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum RecordingMode {
+    #[default]
+    Off,
+    /// Every request and its response are written to the recording directory
+    Record,
+    /// Responses are served from the recording directory, and nothing is sent to GitHub
+    Replay,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct TeamConfig {
+    pub name: String,
+    pub members: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct DatabaseConfig {
+    pub name: String,
+    pub path: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct RepoGroupConfig {
+    pub name: String,
+    pub repos: Vec<String>,
+}
+
+/// A named scrape for a particular audience, e.g. a single org daily or platform repos monthly.
+/// Unset fields fall back to the top-level settings.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(default)]
+pub struct ScrapeProfile {
+    pub name: String,
+    /// Orgs to scrape; every org the token belongs to when empty
+    pub organizations: Vec<String>,
+    /// Globs over `org/repo` to scrape; every repo when empty
+    pub repos: Vec<String>,
+    /// Replaces `ignored_user_patterns` for this profile
+    pub ignored_user_patterns: Option<String>,
+    /// Days the scrape covers, ending now; defaults to `days`
+    pub days: Option<usize>,
+    /// Cron expression `org-pulse daemon` runs this profile on; profiles without one only run on demand
+    pub schedule: Option<String>,
 }
 
 impl Default for AppConfig {
@@ -24,11 +140,109 @@ impl Default for AppConfig {
             ignored_user_patterns: "".to_string(),
             ignored_repo_patterns: "".to_string(),
             ignored_org_pattern: "".to_string(),
+            skip_forks: false,
+            skip_archived: false,
+            allowed_topics: vec![],
+            denied_topics: vec![],
+            churn_path_depth: 2,
+            max_commit_details: 100,
+            excluded_line_patterns: vec![
+                "**/*.lock".to_string(),
+                "**/package-lock.json".to_string(),
+                "**/pnpm-lock.yaml".to_string(),
+                "**/go.sum".to_string(),
+                "**/vendor/**".to_string(),
+                "**/node_modules/**".to_string(),
+                "**/*.generated.*".to_string(),
+                "**/*.min.js".to_string(),
+                "**/__snapshots__/**".to_string(),
+                "**/*.snap".to_string(),
+                "**/*.pb.go".to_string(),
+                "**/*_pb2.py".to_string(),
+            ],
+            deployment_environments: vec!["production".to_string()],
+            scrape_schedule: "0 6 * * 1".to_string(),
+            daemon_log_file: "org-pulse-daemon.log".to_string(),
+            webhook_listen_addr: "127.0.0.1:8787".to_string(),
+            teams_csv: "".to_string(),
+            activity_timing: false,
+            default_timezone: "UTC".to_string(),
+            working_hours_start: 9,
+            working_hours_end: 18,
+            contributor_timezones: BTreeMap::new(),
+            teams: vec![],
+            repo_groups: vec![],
+            profiles: vec![],
+            http_cache: HttpCacheMode::On,
+            api_recording: RecordingMode::Off,
+            api_recording_dir: "fixtures/github".to_string(),
+            databases: vec![],
+        }
+    }
+}
+
+impl AppConfig {
+    /// Looks up a profile by name.
+    pub fn profile(&self, name: &str) -> Result<&ScrapeProfile> {
+        self.profiles.iter()
+            .find(|profile| profile.name == name)
+            .ok_or_else(|| anyhow::anyhow!("No profile named `{name}` in config.toml"))
+    }
+
+    /// The path of a database listed in `databases`, or `name` itself when it isn't listed,
+    /// so `--db` takes either a name or a path.
+    pub fn database_path(&self, name: &str) -> String {
+        self.databases.iter()
+            .find(|database| database.name == name)
+            .map_or_else(|| name.to_string(), |database| database.path.clone())
+    }
+
+    /// Teams from `teams` merged by name with those listed in `teams_csv`.
+    pub fn configured_teams(&self) -> Result<Vec<TeamConfig>> {
+        let mut teams = self.teams.clone();
+        if self.teams_csv.is_empty() {
+            return Ok(teams);
+        }
+
+        for (team_name, username) in load_team_csv(&self.teams_csv)? {
+            match teams.iter_mut().find(|t| t.name == team_name) {
+                Some(team) => {
+                    if !team.members.contains(&username) {
+                        team.members.push(username);
+                    }
+                }
+                None => teams.push(TeamConfig { name: team_name, members: vec![username] }),
+            }
+        }
+        Ok(teams)
+    }
+}
+
+/// Reads `(team, username)` pairs from a CSV with `team` and `username` header columns.
+fn load_team_csv(path: &str) -> Result<Vec<(String, String)>> {
+    let mut reader = csv::Reader::from_path(path)?;
+    let headers = reader.headers()?.clone();
+    let column = |name: &str| {
+        headers.iter()
+            .position(|h| h.trim().eq_ignore_ascii_case(name))
+            .ok_or_else(|| anyhow::anyhow!("{} is missing a '{}' column", path, name))
+    };
+    let team_column = column("team")?;
+    let username_column = column("username")?;
+
+    let mut rows = Vec::new();
+    for record in reader.records() {
+        let record = record?;
+        let team = record.get(team_column).unwrap_or_default().trim();
+        let username = record.get(username_column).unwrap_or_default().trim();
+        if !team.is_empty() && !username.is_empty() {
+            rows.push((team.to_string(), username.to_string()));
         }
     }
+    Ok(rows)
 }
 
 pub fn get_config() -> Result<AppConfig> {
-    let cfg: AppConfig = confy::load_path(CONFIG_APTH)?;
+    let cfg: AppConfig = confy::load_path(config_path())?;
     Ok(cfg)
 }
@@ -0,0 +1,38 @@
+//! Conventional Commit classification of commit summaries and PR titles.
+//!
+//! Summaries look like `type(scope)!: description`. Only the standard types (and a few
+//! common misspellings of them) are recognised, so merge commits and free-form messages
+//! stay unclassified.
+
+/// Change types recognised in `type(scope): description` summaries
+pub const CHANGE_TYPES: [&str; 11] = ["feat", "fix", "chore", "docs", "style", "refactor", "perf", "test", "build", "ci", "revert"];
+
+/// Parses the Conventional Commit type of a commit summary or PR title, e.g. `fix` for
+/// `fix(auth)!: reject expired tokens`. Returns `None` for summaries that don't follow the convention.
+pub fn change_type(summary: &str) -> Option<String> {
+    let summary = summary.trim();
+    // GitHub's revert button produces `Revert "feat: ..."`
+    if summary.starts_with("Revert \"") {
+        return Some("revert".to_string());
+    }
+
+    let (prefix, description) = summary.split_once(':')?;
+    if description.trim().is_empty() {
+        return None;
+    }
+    let prefix = prefix.trim_end().trim_end_matches('!');
+    let kind = match prefix.split_once('(') {
+        Some((kind, scope)) if scope.ends_with(')') => kind,
+        Some(_) => return None,
+        None => prefix,
+    };
+
+    let kind = match kind.to_ascii_lowercase().as_str() {
+        "feature" => "feat".to_string(),
+        "bugfix" | "hotfix" => "fix".to_string(),
+        "tests" => "test".to_string(),
+        "doc" => "docs".to_string(),
+        other => other.to_string(),
+    };
+    CHANGE_TYPES.contains(&kind.as_str()).then_some(kind)
+}
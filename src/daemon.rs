@@ -0,0 +1,160 @@
+//! `org-pulse daemon`: runs scrapes on the cron schedule in `scrape_schedule` without the TUI.
+
+use std::{fs::{File, OpenOptions}, io::Write, time::Instant};
+
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+use sqlx::SqlitePool;
+
+use crate::{
+    config::{get_config, AppConfig, ScrapeProfile},
+    date_range::DateRange,
+    lock::{daemon_lock_path, LockFile},
+    schedule::Schedule,
+    scraper::{fail_interrupted_scrapes, run_scrape},
+};
+
+/// Appends timestamped lines to `daemon_log_file` and echoes them to stdout.
+struct DaemonLog {
+    file: File,
+}
+
+impl DaemonLog {
+    fn open(path: &str) -> Result<DaemonLog> {
+        let file = OpenOptions::new().create(true).append(true).open(path)
+            .map_err(|e| anyhow!("Can't open daemon log {path}: {e}"))?;
+        Ok(DaemonLog { file })
+    }
+
+    fn write(&mut self, message: &str) {
+        let line = format!("[{}] {message}", Utc::now().format("%Y-%m-%d %H:%M:%S UTC"));
+        println!("{line}");
+        if let Err(e) = writeln!(self.file, "{line}") {
+            eprintln!("Failed to write daemon log: {e}");
+        }
+    }
+}
+
+/// Resolves on Ctrl-C, or on SIGTERM where there is one, so the locks get released.
+async fn shutdown_signal() {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+        match signal(SignalKind::terminate()) {
+            Ok(mut terminate) => {
+                tokio::select! {
+                    _ = tokio::signal::ctrl_c() => {}
+                    _ = terminate.recv() => {}
+                }
+            }
+            Err(_) => {
+                let _ = tokio::signal::ctrl_c().await;
+            }
+        }
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = tokio::signal::ctrl_c().await;
+    }
+}
+
+/// A scrape the daemon runs on its own schedule
+struct Job {
+    profile: Option<ScrapeProfile>,
+    expression: String,
+    schedule: Schedule,
+    next_run: Option<DateTime<Utc>>,
+}
+
+impl Job {
+    fn label(&self) -> String {
+        match &self.profile {
+            Some(profile) => format!("profile `{}`", profile.name),
+            None => "default scrape".to_string(),
+        }
+    }
+}
+
+/// The default scrape on `scrape_schedule`, unless it's empty, plus every profile with a `schedule`.
+fn scheduled_jobs(cfg: &AppConfig) -> Result<Vec<Job>> {
+    let now = Utc::now();
+    let mut jobs = Vec::new();
+    if !cfg.scrape_schedule.trim().is_empty() {
+        let schedule = Schedule::parse(&cfg.scrape_schedule)?;
+        jobs.push(Job { profile: None, expression: cfg.scrape_schedule.clone(), next_run: schedule.next_after(now), schedule });
+    }
+    for profile in &cfg.profiles {
+        if let Some(expression) = &profile.schedule {
+            let schedule = Schedule::parse(expression)
+                .map_err(|e| anyhow!("Profile `{}`: {e}", profile.name))?;
+            jobs.push(Job { profile: Some(profile.clone()), expression: expression.clone(), next_run: schedule.next_after(now), schedule });
+        }
+    }
+    if jobs.is_empty() {
+        return Err(anyhow!("Nothing to schedule: set `scrape_schedule` or a profile `schedule` in config.toml"));
+    }
+    Ok(jobs)
+}
+
+/// Runs scrapes on schedule until stopped. Refuses to start while another daemon holds the database.
+pub async fn run_daemon(db_pool: &SqlitePool) -> Result<()> {
+    let cfg = get_config()?;
+    let mut jobs = scheduled_jobs(&cfg)?;
+    let instance_lock = LockFile::acquire(daemon_lock_path())
+        .map_err(|e| anyhow!("Another daemon is already using the database: {e}"))?;
+    let mut log = DaemonLog::open(&cfg.daemon_log_file)?;
+    log.write(&format!("Daemon started (pid {}, lock {})", std::process::id(), instance_lock.path().display()));
+    match fail_interrupted_scrapes(db_pool).await {
+        Ok(0) => {}
+        Ok(count) => log.write(&format!("Marked {count} interrupted scrape(s) as failed")),
+        Err(e) => log.write(&format!("Couldn't check for interrupted scrapes: {e}")),
+    }
+    for job in &jobs {
+        log.write(&format!("Scheduled {} on `{}`", job.label(), job.expression));
+    }
+
+    let shutdown = shutdown_signal();
+    tokio::pin!(shutdown);
+
+    loop {
+        // Jobs due at the same time run back to back, in config order
+        let Some(job) = jobs.iter_mut()
+            .filter(|job| job.next_run.is_some())
+            .min_by_key(|job| job.next_run)
+        else {
+            log.write("No schedule fires again, stopping");
+            return Err(anyhow!("None of the configured schedules fire again"));
+        };
+        let next_run = job.next_run.expect("jobs without a next run are filtered out");
+        log.write(&format!("Next: {} at {}", job.label(), next_run.format("%Y-%m-%d %H:%M UTC")));
+
+        let wait = (next_run - Utc::now()).to_std().unwrap_or_default();
+        tokio::select! {
+            _ = tokio::time::sleep(wait) => {}
+            _ = &mut shutdown => {
+                log.write("Daemon stopped");
+                return Ok(());
+            }
+        }
+
+        let label = job.label();
+        let range = DateRange::for_profile(&cfg, job.profile.as_ref());
+        log.write(&format!("Scrape started: {label}"));
+        let started = Instant::now();
+        tokio::select! {
+            result = run_scrape(db_pool, range, job.profile.as_ref()) => match result {
+                Ok(_) => log.write(&format!("Scrape finished: {label} in {}s", started.elapsed().as_secs())),
+                Err(e) => log.write(&format!("Scrape failed: {label} after {}s: {e}", started.elapsed().as_secs())),
+            },
+            _ = &mut shutdown => {
+                // The scrape and its lock were dropped with the select, so it can be closed off now
+                if let Err(e) = fail_interrupted_scrapes(db_pool).await {
+                    log.write(&format!("Couldn't mark the interrupted scrape as failed: {e}"));
+                }
+                log.write("Daemon stopped during a scrape; it's marked failed and stays hidden");
+                return Ok(());
+            }
+        }
+        job.next_run = job.schedule.next_after(Utc::now().max(next_run));
+    }
+}
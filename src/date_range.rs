@@ -0,0 +1,76 @@
+//! The time window a scrape covers.
+//!
+//! Bounds are given as RFC 3339 timestamps (`2025-07-01T09:00:00Z`) or plain dates. A plain
+//! date as the start means the start of that day, and as the end means the end of it, so
+//! `2025-07-01..2025-09-30` covers all of Q3. Dates are in UTC.
+
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Duration, DurationRound, NaiveDate, Utc};
+
+use crate::config::{AppConfig, ScrapeProfile};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DateRange {
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+}
+
+impl DateRange {
+    /// The last `days` days, ending now
+    pub fn last_days(days: usize) -> DateRange {
+        let end = now();
+        DateRange { start: end - Duration::days(days as i64), end }
+    }
+
+    /// The window a scrape covers when it's run without explicit bounds: the profile's `days`,
+    /// falling back to the top-level `days`.
+    pub fn for_profile(cfg: &AppConfig, profile: Option<&ScrapeProfile>) -> DateRange {
+        DateRange::last_days(profile.and_then(|p| p.days).unwrap_or(cfg.days))
+    }
+
+    /// Builds a range from explicit bounds. An end in the future is clamped to now, and
+    /// a missing start defaults to a week before the end.
+    pub fn parse(start: Option<&str>, end: Option<&str>) -> Result<DateRange> {
+        let now = now();
+        let end = match end {
+            Some(end) => parse_bound(end, true)?.min(now),
+            None => now,
+        };
+        let start = match start {
+            Some(start) => parse_bound(start, false)?,
+            None => end - Duration::days(7),
+        };
+        if start >= end {
+            return Err(anyhow!("Start {} must be before end {}", start.to_rfc3339(), end.to_rfc3339()));
+        }
+        Ok(DateRange { start, end })
+    }
+
+    /// Parses `START..END` or `START END`, as typed into the TUI's date range prompt.
+    pub fn parse_input(input: &str) -> Result<DateRange> {
+        let input = input.trim();
+        let (start, end) = input.split_once("..")
+            .or_else(|| input.split_once(char::is_whitespace))
+            .map(|(start, end)| (start.trim(), end.trim()))
+            .ok_or_else(|| anyhow!("Enter a range as START..END, e.g. 2025-07-01..2025-09-30"))?;
+        DateRange::parse(Some(start), (!end.is_empty()).then_some(end))
+    }
+}
+
+/// The current time, truncated to the minute. Windows ending now are sent to GitHub as `until`, so
+/// scrapes made within the same minute list the same URLs and can revalidate cached responses.
+fn now() -> DateTime<Utc> {
+    let now = Utc::now();
+    now.duration_trunc(Duration::minutes(1)).unwrap_or(now)
+}
+
+/// Parses an RFC 3339 timestamp or a `YYYY-MM-DD` date. Dates used as an end bound run to the end of the day.
+pub fn parse_bound(value: &str, is_end: bool) -> Result<DateTime<Utc>> {
+    if let Ok(time) = DateTime::parse_from_rfc3339(value) {
+        return Ok(time.with_timezone(&Utc));
+    }
+    let date = NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .map_err(|_| anyhow!("`{value}` isn't a date (YYYY-MM-DD) or RFC 3339 timestamp"))?;
+    let date = if is_end { date.succ_opt().ok_or_else(|| anyhow!("`{value}` is out of range"))? } else { date };
+    Ok(date.and_hms_opt(0, 0, 0).expect("midnight is valid").and_utc())
+}
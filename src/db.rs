@@ -1,14 +1,32 @@
+use std::collections::HashMap;
+
 use chrono::{DateTime, Utc};
-use sqlx::{migrate::Migrator, pool::PoolConnection, query, query_as, sqlite::SqlitePoolOptions, Row, Sqlite, SqlitePool};
-use anyhow::{Result};
+use sqlx::{migrate::Migrator, query, query_as, sqlite::{SqliteConnectOptions, SqliteJournalMode, SqlitePoolOptions}, Connection, Row, SqliteConnection, SqlitePool};
+use anyhow::{anyhow, Result};
+
+use crate::codeowners::{is_user_owner, CodeOwners, CodeOwnersRule};
+use crate::config::{RepoGroupConfig, TeamConfig};
+use crate::paths::db_path;
 
-const DB_URL: &str = "sqlite://org-pulse.db?mode=rwc";
 static MIGRATOR: Migrator = sqlx::migrate!();
 
+/// Opens the database at `paths::db_path()`, creating it and its directory if they don't exist.
+///
+/// The app opens one pool per database and shares it between the TUI, scrapes and servers. WAL
+/// mode lets the TUI keep reading while a scrape, the daemon or the webhook receiver writes.
 pub async fn new_pool() -> Result<SqlitePool> {
+    let path = db_path();
+    if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        std::fs::create_dir_all(dir)?;
+    }
     let pool = SqlitePoolOptions::new()
         .max_connections(5)
-        .connect(DB_URL)
+        .connect_with(
+            SqliteConnectOptions::new()
+                .filename(&path)
+                .create_if_missing(true)
+                .journal_mode(SqliteJournalMode::Wal)
+        )
         .await?;
     
     // Run migrations automatically
@@ -17,7 +35,8 @@ pub async fn new_pool() -> Result<SqlitePool> {
     Ok(pool)
 }
 
-type PoolConn = PoolConnection<Sqlite>;
+/// Queries run on a plain connection, so they work on a pooled one and inside a `Transaction` alike
+type DbConn = SqliteConnection;
 
 #[derive(Debug, Clone)]
 pub struct Org {
@@ -26,13 +45,13 @@ pub struct Org {
 }
 
 impl Org {
-    pub async fn get(pool_con: &mut PoolConn, id: &i64) -> Result<Org> {
+    pub async fn get(pool_con: &mut DbConn, id: &i64) -> Result<Org> {
         let org_row: (i64, String) = query_as("
             SELECT id, name
             FROM orgs o
             WHERE o.id = $1 
             LIMIT 1;
-        ").bind(id).fetch_one(pool_con.as_mut()).await?;
+        ").bind(id).fetch_one(&mut *pool_con).await?;
 
         Ok(Org {
             id: org_row.0,
@@ -40,7 +59,7 @@ impl Org {
         })
     }
 
-    pub async fn create(pool_con: &mut PoolConn, name: String) -> Result<Org> {
+    pub async fn create(pool_con: &mut DbConn, name: String) -> Result<Org> {
         let result = query("
             INSERT INTO orgs (name)
             VALUES ($1)
@@ -48,7 +67,7 @@ impl Org {
             RETURNING id
         ")
             .bind(&name)
-            .fetch_one(pool_con.as_mut())
+            .fetch_one(&mut *pool_con)
             .await?;
         
         let id: i64 = result.get(0);
@@ -56,7 +75,7 @@ impl Org {
         Ok(Org { id, name })
     }
 
-    pub async fn save(&self, pool_con: &mut PoolConn) -> Result<()> {
+    pub async fn save(&self, pool_con: &mut DbConn) -> Result<()> {
         let _res = query("
             UPDATE orgs
             set name = $1
@@ -64,7 +83,7 @@ impl Org {
         ")
             .bind(&self.name)
             .bind(self.id)
-            .execute(pool_con.as_mut())
+            .execute(&mut *pool_con)
             .await?;
         Ok(())
     }
@@ -77,13 +96,22 @@ pub struct Contributor {
 }
 
 impl Contributor {
-    pub async fn get(pool_con: &mut PoolConn, id: &i64) -> Result<Contributor> {
+    /// Id of a contributor already seen, without creating one
+    pub async fn find_id(pool_con: &mut DbConn, username: &str) -> Result<Option<i64>> {
+        let contributor_id: Option<(i64,)> = query_as("SELECT id FROM contributors WHERE username = $1 LIMIT 1")
+            .bind(username)
+            .fetch_optional(&mut *pool_con)
+            .await?;
+        Ok(contributor_id.map(|row| row.0))
+    }
+
+    pub async fn get(pool_con: &mut DbConn, id: &i64) -> Result<Contributor> {
         let contributor_row: (i64, String) = query_as("
             SELECT id, username
             FROM contributors c
             WHERE c.id = $1 
             LIMIT 1;
-        ").bind(id).fetch_one(pool_con.as_mut()).await?;
+        ").bind(id).fetch_one(&mut *pool_con).await?;
 
         Ok(Contributor {
             id: contributor_row.0,
@@ -91,7 +119,7 @@ impl Contributor {
         })
     }
 
-    pub async fn create(pool_con: &mut PoolConn, username: String) -> Result<Contributor> {
+    pub async fn create(pool_con: &mut DbConn, username: String) -> Result<Contributor> {
         let result = query("
             INSERT INTO contributors (username)
             VALUES ($1)
@@ -99,7 +127,7 @@ impl Contributor {
             RETURNING id
         ")
             .bind(&username)
-            .fetch_one(pool_con.as_mut())
+            .fetch_one(&mut *pool_con)
             .await?;
         
         let id: i64 = result.get(0);
@@ -107,7 +135,7 @@ impl Contributor {
         Ok(Contributor { id, username })
     }
 
-    pub async fn save(&self, pool_con: &mut PoolConn) -> Result<()> {
+    pub async fn save(&self, pool_con: &mut DbConn) -> Result<()> {
         let _res = query("
             UPDATE contributors
             set username = $1
@@ -115,38 +143,97 @@ impl Contributor {
         ")
             .bind(&self.username)
             .bind(self.id)
-            .execute(pool_con.as_mut())
+            .execute(&mut *pool_con)
             .await?;
         Ok(())
     }
 }
 
+#[derive(Debug, Clone, Default)]
+pub struct RepoMetadata {
+    pub language: Option<String>,
+    pub topics: Vec<String>,
+    pub fork: bool,
+    pub archived: bool,
+    pub private: bool,
+    pub visibility: Option<String>,
+    pub default_branch: Option<String>,
+    pub size: i64,
+    pub created_at: Option<DateTime<Utc>>,
+    pub pushed_at: Option<DateTime<Utc>>,
+}
+
+type RepoRow = (i64, String, i64, String, Option<String>, String, bool, bool, bool, Option<String>, Option<String>, i64, Option<DateTime<Utc>>, Option<DateTime<Utc>>);
+
+/// Selects a `RepoRow`, joining the owning org so loading a repo takes one query
+const REPO_SELECT: &str = "
+    SELECT r.id, r.name, r.org_id, o.name, r.language, r.topics, r.fork, r.archived, r.private,
+           r.visibility, r.default_branch, r.size, r.created_at, r.pushed_at
+    FROM repos r
+    JOIN orgs o ON r.org_id = o.id
+";
+
+fn repo_from_row(row: RepoRow) -> Repo {
+    Repo {
+        id: row.0,
+        name: row.1,
+        org: Org { id: row.2, name: row.3 },
+        metadata: RepoMetadata {
+            language: row.4,
+            topics: split_topics(&row.5),
+            fork: row.6,
+            archived: row.7,
+            private: row.8,
+            visibility: row.9,
+            default_branch: row.10,
+            size: row.11,
+            created_at: row.12,
+            pushed_at: row.13,
+        },
+    }
+}
+
+type RepoScrapeRow = (i64, i64, i64, i64, i64, i64, i64, i64);
+
+type ContributorScrapeRow = (i64, i64, i64, String, i64, i64, i64);
+
+/// Selects a `ContributorScrapeRow`, joining the contributor so a whole repo scrape loads in one query
+const CONTRIBUTOR_SCRAPE_SELECT: &str = "
+    SELECT cs.id, cs.repo_scrape_id, cs.contributor_id, c.username, cs.commits, cs.lines, cs.excluded_lines
+    FROM contributor_scrapes cs
+    JOIN contributors c ON cs.contributor_id = c.id
+";
+
+fn contributor_scrape_from_row(row: ContributorScrapeRow) -> ContributorScrapes {
+    ContributorScrapes {
+        id: row.0,
+        contributor: Contributor { id: row.2, username: row.3 },
+        commits: row.4,
+        lines: row.5,
+        excluded_lines: row.6,
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct Repo {
     pub id: i64,
     pub name: String,
     pub org: Org,
+    pub metadata: RepoMetadata,
 }
 
 impl Repo {
-    pub async fn get(pool_con: &mut PoolConn, id: &i64) -> Result<Repo> {
-        let repo_row: (i64, String, i64) = query_as("
-            SELECT r.id, r.name, r.org_id
-            FROM repos r
-            WHERE r.id = $1 
+    pub async fn get(pool_con: &mut DbConn, id: &i64) -> Result<Repo> {
+        let repo_row: RepoRow = query_as(&format!("
+            {REPO_SELECT}
+            WHERE r.id = $1
             LIMIT 1;
-        ").bind(id).fetch_one(pool_con.as_mut()).await?;
-
-        let org = Org::get(pool_con, &repo_row.2).await?;
+        ")).bind(id).fetch_one(&mut *pool_con).await?;
 
-        Ok(Repo {
-            id: repo_row.0,
-            name: repo_row.1,
-            org
-        })
+        Ok(repo_from_row(repo_row))
     }
 
-    pub async fn create(pool_con: &mut PoolConn, name: String, org: Org) -> Result<Repo> {
+    pub async fn create(pool_con: &mut DbConn, name: String, org: Org) -> Result<Repo> {
         let result = query("
             INSERT INTO repos (name, org_id)
             VALUES ($1, $2)
@@ -155,81 +242,201 @@ impl Repo {
         ")
             .bind(&name)
             .bind(org.id)
-            .fetch_one(pool_con.as_mut())
+            .fetch_one(&mut *pool_con)
             .await?;
         
         let id: i64 = result.get(0);
         
-        Ok(Repo { id, name, org })
+        Ok(Repo { id, name, org, metadata: RepoMetadata::default() })
+    }
+
+    /// Id of a stored repo, for lookups before a scrape has written it
+    pub async fn find_id(pool_con: &mut DbConn, org_name: &str, name: &str) -> Result<Option<i64>> {
+        let repo_id: Option<(i64,)> = query_as("
+            SELECT r.id FROM repos r JOIN orgs o ON r.org_id = o.id WHERE o.name = $1 AND r.name = $2 LIMIT 1;
+        ").bind(org_name).bind(name).fetch_optional(&mut *pool_con).await?;
+        Ok(repo_id.map(|row| row.0))
+    }
+
+    /// Repos with commits stored within a window, so scrape counters can be rebuilt without calling GitHub.
+    pub async fn list_active(pool_con: &mut DbConn, start_dt: DateTime<Utc>, end_dt: DateTime<Utc>) -> Result<Vec<Repo>> {
+        let repo_rows: Vec<RepoRow> = query_as(&format!("
+            {REPO_SELECT}
+            WHERE r.id IN (
+                SELECT cm.repo_id
+                FROM commits cm
+                WHERE cm.committed_at >= $1 AND cm.committed_at <= $2
+            )
+            ORDER BY r.id;
+        ")).bind(start_dt).bind(end_dt).fetch_all(&mut *pool_con).await?;
+
+        Ok(repo_rows.into_iter().map(repo_from_row).collect())
+    }
+
+    /// Loads the CODEOWNERS rules stored for this repo, in file order.
+    pub async fn get_codeowners(&self, pool_con: &mut DbConn) -> Result<CodeOwners> {
+        let rule_rows: Vec<(String, String)> = query_as("
+            SELECT pattern, owners
+            FROM codeowners_rules cr
+            WHERE cr.repo_id = $1
+            ORDER BY cr.position;
+        ").bind(self.id).fetch_all(&mut *pool_con).await?;
+
+        let rules = rule_rows.into_iter()
+            .filter_map(|(pattern, owners)| {
+                CodeOwnersRule::new(&pattern, owners.split_whitespace().map(|o| o.to_string()).collect())
+            })
+            .collect();
+
+        Ok(CodeOwners { rules })
+    }
+
+    /// Replaces the stored CODEOWNERS rules with the ones from the latest scrape.
+    pub async fn save_codeowners(&self, pool_con: &mut DbConn, codeowners: &CodeOwners) -> Result<()> {
+        query("DELETE FROM codeowners_rules WHERE repo_id = $1")
+            .bind(self.id)
+            .execute(&mut *pool_con)
+            .await?;
+
+        for (position, rule) in codeowners.rules.iter().enumerate() {
+            query("
+                INSERT INTO codeowners_rules (repo_id, position, pattern, owners)
+                VALUES ($1, $2, $3, $4)
+            ")
+                .bind(self.id)
+                .bind(position as i64)
+                .bind(&rule.pattern)
+                .bind(rule.owners.join(" "))
+                .execute(&mut *pool_con)
+                .await?;
+        }
+        Ok(())
     }
 
-    pub async fn save(&self, pool_con: &mut PoolConn) -> Result<()> {
+    pub async fn save(&self, pool_con: &mut DbConn) -> Result<()> {
         let _res = query("
             UPDATE repos
-            set name = $1, org_id = $2
-            where id = $3
+            set name = $1, org_id = $2, language = $3, topics = $4, fork = $5, archived = $6, private = $7,
+                visibility = $8, default_branch = $9, size = $10, created_at = $11, pushed_at = $12
+            where id = $13
         ")
             .bind(&self.name)
             .bind(self.org.id)
+            .bind(&self.metadata.language)
+            .bind(self.metadata.topics.join(","))
+            .bind(self.metadata.fork)
+            .bind(self.metadata.archived)
+            .bind(self.metadata.private)
+            .bind(&self.metadata.visibility)
+            .bind(&self.metadata.default_branch)
+            .bind(self.metadata.size)
+            .bind(self.metadata.created_at)
+            .bind(self.metadata.pushed_at)
             .bind(self.id)
-            .execute(pool_con.as_mut())
+            .execute(&mut *pool_con)
             .await?;
         Ok(())
     }
 }
 
+/// Topics are stored as a comma separated list on `repos.topics`.
+fn split_topics(topics: &str) -> Vec<String> {
+    topics.split(',')
+        .filter(|t| !t.is_empty())
+        .map(|t| t.to_string())
+        .collect()
+}
+
+type ScrapeRow = (i64, DateTime<Utc>, DateTime<Utc>, Option<String>, String);
+
+/// Scrape id, window and profile, followed by the number of repos it recorded
+type ScrapeInfoRow = (i64, DateTime<Utc>, DateTime<Utc>, Option<String>, i64);
+
+/// Where a scrape is in its run. Only complete scrapes are listed, so viewers never pick up one
+/// that is still being written or that failed partway through.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScrapeStatus {
+    Running,
+    Complete,
+    Failed,
+}
+
+impl ScrapeStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ScrapeStatus::Running => "running",
+            ScrapeStatus::Complete => "complete",
+            ScrapeStatus::Failed => "failed",
+        }
+    }
+
+    fn from_db(status: &str) -> ScrapeStatus {
+        match status {
+            "running" => ScrapeStatus::Running,
+            "complete" => ScrapeStatus::Complete,
+            _ => ScrapeStatus::Failed,
+        }
+    }
+}
+
 pub struct Scrape {
     pub id: i64,
     pub start_dt: DateTime<Utc>,
     pub end_dt: DateTime<Utc>,
+    /// Config profile the scrape ran with
+    pub profile: Option<String>,
+    pub status: ScrapeStatus,
     pub repo_scrapes: Vec<RepoScrape>,
 }
 
 impl Scrape {
-    pub async fn get(pool_con: &mut PoolConn, id: &i64) -> Result<Scrape> {
-        let scrape_row: (i64, DateTime<Utc>, DateTime<Utc>) = query_as("
-            SELECT id, start_dt, end_dt
+    pub async fn get(pool_con: &mut DbConn, id: &i64) -> Result<Scrape> {
+        let scrape_row: ScrapeRow = query_as("
+            SELECT id, start_dt, end_dt, profile, status
             FROM scrapes s
             WHERE s.id = $1 
             LIMIT 1;
-        ").bind(id).fetch_one(pool_con.as_mut()).await?;
+        ").bind(id).fetch_one(&mut *pool_con).await?;
 
-        let repo_scrape_rows: Vec<(i64, i64, i64, i64, i64, i64, i64)> = query_as("
-            SELECT id, scrape_id, org_id, repo_id, commits, prs, lines
+        let repo_scrape_rows: Vec<RepoScrapeRow> = query_as("
+            SELECT id, scrape_id, org_id, repo_id, commits, prs, lines, excluded_lines
             FROM repo_scrapes rs
-            WHERE rs.scrape_id = $1;
-        ").bind(id).fetch_all(pool_con.as_mut()).await?;
+            WHERE rs.scrape_id = $1
+            ORDER BY rs.id;
+        ").bind(id).fetch_all(&mut *pool_con).await?;
+
+        let repo_rows: Vec<RepoRow> = query_as(&format!("
+            {REPO_SELECT}
+            WHERE r.id IN (SELECT repo_id FROM repo_scrapes WHERE scrape_id = $1);
+        ")).bind(id).fetch_all(&mut *pool_con).await?;
+        let repos: HashMap<i64, Repo> = repo_rows.into_iter()
+            .map(|row| (row.0, repo_from_row(row)))
+            .collect();
+
+        let contributor_scrape_rows: Vec<ContributorScrapeRow> = query_as(&format!("
+            {CONTRIBUTOR_SCRAPE_SELECT}
+            JOIN repo_scrapes rs ON cs.repo_scrape_id = rs.id
+            WHERE rs.scrape_id = $1
+            ORDER BY cs.id;
+        ")).bind(id).fetch_all(&mut *pool_con).await?;
+        let mut contributor_scrapes: HashMap<i64, Vec<ContributorScrapes>> = HashMap::new();
+        for row in contributor_scrape_rows {
+            contributor_scrapes.entry(row.1).or_default().push(contributor_scrape_from_row(row));
+        }
 
         let mut repo_scrapes = Vec::new();
         for row in repo_scrape_rows {
-            let org = Org::get(pool_con, &row.2).await?;
-            let repo = Repo::get(pool_con, &row.3).await?;
-            
-            let contributor_scrape_rows: Vec<(i64, i64, i64, i64, i64)> = query_as("
-                SELECT id, repo_scrape_id, contributor_id, commits, lines
-                FROM contributor_scrapes cs
-                WHERE cs.repo_scrape_id = $1;
-            ").bind(row.0).fetch_all(pool_con.as_mut()).await?;
-
-            let mut contributor_scrapes = Vec::new();
-            for cs_row in contributor_scrape_rows {
-                let contributor = Contributor::get(pool_con, &cs_row.2).await?;
-                contributor_scrapes.push(ContributorScrapes {
-                    id: cs_row.0,
-                    contributor,
-                    commits: cs_row.3,
-                    lines: cs_row.4,
-                });
-            }
-
+            let repo = repos.get(&row.3).cloned()
+                .ok_or_else(|| anyhow!("repo {} of repo scrape {} not found", row.3, row.0))?;
             repo_scrapes.push(RepoScrape {
                 id: row.0,
-                org,
+                org: repo.org.clone(),
                 repo,
                 commits: row.4,
                 prs: row.5,
                 lines: row.6,
-                contributor_scrapes,
+                excluded_lines: row.7,
+                contributor_scrapes: contributor_scrapes.remove(&row.0).unwrap_or_default(),
             });
         }
 
@@ -237,19 +444,23 @@ impl Scrape {
             id: scrape_row.0,
             start_dt: scrape_row.1,
             end_dt: scrape_row.2,
+            profile: scrape_row.3,
+            status: ScrapeStatus::from_db(&scrape_row.4),
             repo_scrapes,
         })
     }
 
-    pub async fn create(pool_con: &mut PoolConn, start_dt: DateTime<Utc>, end_dt: DateTime<Utc>) -> Result<Scrape> {
+    pub async fn create(pool_con: &mut DbConn, start_dt: DateTime<Utc>, end_dt: DateTime<Utc>, profile: Option<String>, status: ScrapeStatus) -> Result<Scrape> {
         let result = query("
-            INSERT INTO scrapes (start_dt, end_dt)
-            VALUES ($1, $2)
+            INSERT INTO scrapes (start_dt, end_dt, profile, status)
+            VALUES ($1, $2, $3, $4)
             RETURNING id
         ")
             .bind(start_dt)
             .bind(end_dt)
-            .fetch_one(pool_con.as_mut())
+            .bind(&profile)
+            .bind(status.as_str())
+            .fetch_one(&mut *pool_con)
             .await?;
         
         let id: i64 = result.get(0);
@@ -258,20 +469,85 @@ impl Scrape {
             id, 
             start_dt, 
             end_dt, 
+            profile,
+            status,
             repo_scrapes: Vec::new() 
         })
     }
 
-    pub async fn save(&self, pool_con: &mut PoolConn) -> Result<()> {
+    /// The newest scrape stored under a profile, whatever its status
+    pub async fn find_by_profile(pool_con: &mut DbConn, profile: &str) -> Result<Option<Scrape>> {
+        let scrape_id: Option<(i64,)> = query_as("SELECT id FROM scrapes WHERE profile = $1 ORDER BY id DESC LIMIT 1")
+            .bind(profile)
+            .fetch_optional(&mut *pool_con)
+            .await?;
+        match scrape_id {
+            Some((id,)) => Ok(Some(Scrape::get(pool_con, &id).await?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Marks scrapes left running by an interrupted process as failed. Only call this while
+    /// holding the scrape lock, so a scrape that's still being written isn't caught.
+    pub async fn fail_interrupted(pool_con: &mut DbConn) -> Result<u64> {
+        let result = query("UPDATE scrapes SET status = $1 WHERE status = $2")
+            .bind(ScrapeStatus::Failed.as_str())
+            .bind(ScrapeStatus::Running.as_str())
+            .execute(&mut *pool_con)
+            .await?;
+        Ok(result.rows_affected())
+    }
+
+    /// Records how the scrape ended. Marking it complete is what makes it visible to viewers.
+    pub async fn set_status(&mut self, pool_con: &mut DbConn, status: ScrapeStatus) -> Result<()> {
+        query("UPDATE scrapes SET status = $1 WHERE id = $2")
+            .bind(status.as_str())
+            .bind(self.id)
+            .execute(&mut *pool_con)
+            .await?;
+        self.status = status;
+        Ok(())
+    }
+
+    /// Drops one repo's counters from this scrape so they can be recorded again.
+    pub async fn clear_repo_scrape(&self, pool_con: &mut DbConn, repo_id: i64) -> Result<()> {
+        query("DELETE FROM contributor_scrapes WHERE repo_scrape_id IN (SELECT id FROM repo_scrapes WHERE scrape_id = $1 AND repo_id = $2)")
+            .bind(self.id)
+            .bind(repo_id)
+            .execute(&mut *pool_con)
+            .await?;
+        query("DELETE FROM repo_scrapes WHERE scrape_id = $1 AND repo_id = $2")
+            .bind(self.id)
+            .bind(repo_id)
+            .execute(&mut *pool_con)
+            .await?;
+        Ok(())
+    }
+
+    /// Drops this scrape's repo and contributor counters so they can be recorded again.
+    pub async fn clear_repo_scrapes(&self, pool_con: &mut DbConn) -> Result<()> {
+        query("DELETE FROM contributor_scrapes WHERE repo_scrape_id IN (SELECT id FROM repo_scrapes WHERE scrape_id = $1)")
+            .bind(self.id)
+            .execute(&mut *pool_con)
+            .await?;
+        query("DELETE FROM repo_scrapes WHERE scrape_id = $1")
+            .bind(self.id)
+            .execute(&mut *pool_con)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn save(&self, pool_con: &mut DbConn) -> Result<()> {
         let _res = query("
             UPDATE scrapes
-            set start_dt = $1, end_dt = $2
-            where id = $3
+            set start_dt = $1, end_dt = $2, status = $3
+            where id = $4
         ")
             .bind(self.start_dt)
             .bind(self.end_dt)
+            .bind(self.status.as_str())
             .bind(self.id)
-            .execute(pool_con.as_mut())
+            .execute(&mut *pool_con)
             .await?;
         
         for repo_scrape in &self.repo_scrapes {
@@ -281,40 +557,39 @@ impl Scrape {
         Ok(())
     }
 
-    pub async fn list_all(pool_con: &mut PoolConn) -> Result<Vec<crate::stats::ScrapeInfo>> {
-        let scrape_rows: Vec<(i64, DateTime<Utc>, DateTime<Utc>)> = query_as("
-            SELECT id, start_dt, end_dt
-            FROM scrapes
-            ORDER BY start_dt DESC;
-        ").fetch_all(pool_con.as_mut()).await?;
-
-        let mut scrape_infos = Vec::new();
-        for row in scrape_rows {
-            // Count repos for this scrape
-            let repo_count: (i64,) = query_as("
-                SELECT COUNT(*) 
-                FROM repo_scrapes 
-                WHERE scrape_id = $1;
-            ").bind(row.0).fetch_one(pool_con.as_mut()).await?;
-
-            scrape_infos.push(crate::stats::ScrapeInfo {
+    /// Complete scrapes, most recently made first
+    pub async fn list_all(pool_con: &mut DbConn) -> Result<Vec<crate::stats::ScrapeInfo>> {
+        let scrape_rows: Vec<ScrapeInfoRow> = query_as("
+            SELECT s.id, s.start_dt, s.end_dt, s.profile, COUNT(rs.id) as repo_count
+            FROM scrapes s
+            LEFT JOIN repo_scrapes rs ON rs.scrape_id = s.id
+            WHERE s.status = 'complete'
+            GROUP BY s.id
+            ORDER BY s.id DESC;
+        ").fetch_all(&mut *pool_con).await?;
+
+        let scrape_infos = scrape_rows.into_iter()
+            .map(|row| crate::stats::ScrapeInfo {
                 id: row.0,
                 start_dt: row.1,
                 end_dt: row.2,
-                repo_count: repo_count.0,
-            });
-        }
+                profile: row.3,
+                repo_count: row.4,
+            })
+            .collect();
 
         Ok(scrape_infos)
     }
 
-    pub async fn get_latest(pool_con: &mut PoolConn) -> Result<Option<crate::stats::ScrapeInfo>> {
-        let latest_scrape_result: Result<(i64, DateTime<Utc>, DateTime<Utc>), _> = query_as("
-            SELECT id, start_dt, end_dt
+    /// The most recently made complete scrape, whichever window it covers
+    pub async fn get_latest(pool_con: &mut DbConn) -> Result<Option<crate::stats::ScrapeInfo>> {
+        let latest_scrape_result: Result<ScrapeRow, _> = query_as("
+            SELECT id, start_dt, end_dt, profile, status
             FROM scrapes
-            ORDER BY start_dt DESC
+            WHERE status = 'complete'
+            ORDER BY id DESC
             LIMIT 1;
-        ").fetch_one(pool_con.as_mut()).await;
+        ").fetch_one(&mut *pool_con).await;
 
         match latest_scrape_result {
             Ok(row) => {
@@ -323,12 +598,13 @@ impl Scrape {
                     SELECT COUNT(*) 
                     FROM repo_scrapes 
                     WHERE scrape_id = $1;
-                ").bind(row.0).fetch_one(pool_con.as_mut()).await?;
+                ").bind(row.0).fetch_one(&mut *pool_con).await?;
 
                 Ok(Some(crate::stats::ScrapeInfo {
                     id: row.0,
                     start_dt: row.1,
                     end_dt: row.2,
+                    profile: row.3,
                     repo_count: repo_count.0,
                 }))
             }
@@ -344,53 +620,45 @@ pub struct RepoScrape {
     pub commits: i64,
     pub prs: i64,
     pub lines: i64,
+    pub excluded_lines: i64,
     pub contributor_scrapes: Vec<ContributorScrapes>,
 }
 
 impl RepoScrape {
-    pub async fn get(pool_con: &mut PoolConn, id: &i64) -> Result<RepoScrape> {
-        let repo_scrape_row: (i64, i64, i64, i64, i64, i64, i64) = query_as("
-            SELECT id, scrape_id, org_id, repo_id, commits, prs, lines
+    pub async fn get(pool_con: &mut DbConn, id: &i64) -> Result<RepoScrape> {
+        let repo_scrape_row: RepoScrapeRow = query_as("
+            SELECT id, scrape_id, org_id, repo_id, commits, prs, lines, excluded_lines
             FROM repo_scrapes rs
             WHERE rs.id = $1 
             LIMIT 1;
-        ").bind(id).fetch_one(pool_con.as_mut()).await?;
+        ").bind(id).fetch_one(&mut *pool_con).await?;
 
-        let org = Org::get(pool_con, &repo_scrape_row.2).await?;
         let repo = Repo::get(pool_con, &repo_scrape_row.3).await?;
 
-        let contributor_scrape_rows: Vec<(i64, i64, i64, i64, i64)> = query_as("
-            SELECT id, repo_scrape_id, contributor_id, commits, lines
-            FROM contributor_scrapes cs
-            WHERE cs.repo_scrape_id = $1;
-        ").bind(id).fetch_all(pool_con.as_mut()).await?;
-
-        let mut contributor_scrapes = Vec::new();
-        for cs_row in contributor_scrape_rows {
-            let contributor = Contributor::get(pool_con, &cs_row.2).await?;
-            contributor_scrapes.push(ContributorScrapes {
-                id: cs_row.0,
-                contributor,
-                commits: cs_row.3,
-                lines: cs_row.4,
-            });
-        }
+        let contributor_scrape_rows: Vec<ContributorScrapeRow> = query_as(&format!("
+            {CONTRIBUTOR_SCRAPE_SELECT}
+            WHERE cs.repo_scrape_id = $1
+            ORDER BY cs.id;
+        ")).bind(id).fetch_all(&mut *pool_con).await?;
+        let contributor_scrapes = contributor_scrape_rows.into_iter().map(contributor_scrape_from_row).collect();
 
         Ok(RepoScrape {
             id: repo_scrape_row.0,
-            org,
+            org: repo.org.clone(),
             repo,
             commits: repo_scrape_row.4,
             prs: repo_scrape_row.5,
             lines: repo_scrape_row.6,
+            excluded_lines: repo_scrape_row.7,
             contributor_scrapes,
         })
     }
 
-    pub async fn create(pool_con: &mut PoolConn, scrape_id: i64, org: Org, repo: Repo, commits: i64, prs: i64, lines: i64) -> Result<RepoScrape> {
+    #[allow(clippy::too_many_arguments)]
+    pub async fn create(pool_con: &mut DbConn, scrape_id: i64, org: Org, repo: Repo, commits: i64, prs: i64, lines: i64, excluded_lines: i64) -> Result<RepoScrape> {
         let result = query("
-            INSERT INTO repo_scrapes (scrape_id, org_id, repo_id, commits, prs, lines)
-            VALUES ($1, $2, $3, $4, $5, $6)
+            INSERT INTO repo_scrapes (scrape_id, org_id, repo_id, commits, prs, lines, excluded_lines)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
             RETURNING id
         ")
             .bind(scrape_id)
@@ -399,7 +667,8 @@ impl RepoScrape {
             .bind(commits)
             .bind(prs)
             .bind(lines)
-            .fetch_one(pool_con.as_mut())
+            .bind(excluded_lines)
+            .fetch_one(&mut *pool_con)
             .await?;
         
         let id: i64 = result.get(0);
@@ -411,23 +680,25 @@ impl RepoScrape {
             commits, 
             prs, 
             lines, 
+            excluded_lines,
             contributor_scrapes: Vec::new() 
         })
     }
 
-    pub async fn save(&self, pool_con: &mut PoolConn) -> Result<()> {
+    pub async fn save(&self, pool_con: &mut DbConn) -> Result<()> {
         let _res = query("
             UPDATE repo_scrapes
-            set org_id = $1, repo_id = $2, commits = $3, prs = $4, lines = $5
-            where id = $6
+            set org_id = $1, repo_id = $2, commits = $3, prs = $4, lines = $5, excluded_lines = $6
+            where id = $7
         ")
             .bind(self.org.id)
             .bind(self.repo.id)
             .bind(self.commits)
             .bind(self.prs)
             .bind(self.lines)
+            .bind(self.excluded_lines)
             .bind(self.id)
-            .execute(pool_con.as_mut())
+            .execute(&mut *pool_con)
             .await?;
         
         for contributor_scrape in &self.contributor_scrapes {
@@ -443,38 +714,32 @@ pub struct ContributorScrapes {
     pub contributor: Contributor,
     pub commits: i64,
     pub lines: i64,
+    pub excluded_lines: i64,
 }
 
 impl ContributorScrapes {
-    pub async fn get(pool_con: &mut PoolConn, id: &i64) -> Result<ContributorScrapes> {
-        let contributor_scrapes_row: (i64, i64, i64, i64, i64) = query_as("
-            SELECT id, repo_scrape_id, contributor_id, commits, lines
-            FROM contributor_scrapes cs
-            WHERE cs.id = $1 
+    pub async fn get(pool_con: &mut DbConn, id: &i64) -> Result<ContributorScrapes> {
+        let contributor_scrapes_row: ContributorScrapeRow = query_as(&format!("
+            {CONTRIBUTOR_SCRAPE_SELECT}
+            WHERE cs.id = $1
             LIMIT 1;
-        ").bind(id).fetch_one(pool_con.as_mut()).await?;
-
-        let contributor = Contributor::get(pool_con, &contributor_scrapes_row.2).await?;
+        ")).bind(id).fetch_one(&mut *pool_con).await?;
 
-        Ok(ContributorScrapes {
-            id: contributor_scrapes_row.0,
-            contributor,
-            commits: contributor_scrapes_row.3,
-            lines: contributor_scrapes_row.4,
-        })
+        Ok(contributor_scrape_from_row(contributor_scrapes_row))
     }
 
-    pub async fn create(pool_con: &mut PoolConn, repo_scrape_id: i64, contributor: Contributor, commits: i64, lines: i64) -> Result<ContributorScrapes> {
+    pub async fn create(pool_con: &mut DbConn, repo_scrape_id: i64, contributor: Contributor, commits: i64, lines: i64, excluded_lines: i64) -> Result<ContributorScrapes> {
         let result = query("
-            INSERT INTO contributor_scrapes (repo_scrape_id, contributor_id, commits, lines)
-            VALUES ($1, $2, $3, $4)
+            INSERT INTO contributor_scrapes (repo_scrape_id, contributor_id, commits, lines, excluded_lines)
+            VALUES ($1, $2, $3, $4, $5)
             RETURNING id
         ")
             .bind(repo_scrape_id)
             .bind(contributor.id)
             .bind(commits)
             .bind(lines)
-            .fetch_one(pool_con.as_mut())
+            .bind(excluded_lines)
+            .fetch_one(&mut *pool_con)
             .await?;
         
         let id: i64 = result.get(0);
@@ -483,226 +748,2166 @@ impl ContributorScrapes {
             id, 
             contributor, 
             commits, 
-            lines 
+            lines,
+            excluded_lines,
         })
     }
 
-    pub async fn save(&self, pool_con: &mut PoolConn) -> Result<()> {
+    pub async fn save(&self, pool_con: &mut DbConn) -> Result<()> {
         let _res = query("
             UPDATE contributor_scrapes
-            set contributor_id = $1, commits = $2, lines = $3
-            where id = $4
+            set contributor_id = $1, commits = $2, lines = $3, excluded_lines = $4
+            where id = $5
         ")
             .bind(self.contributor.id)
             .bind(self.commits)
             .bind(self.lines)
+            .bind(self.excluded_lines)
             .bind(self.id)
-            .execute(pool_con.as_mut())
+            .execute(&mut *pool_con)
             .await?;
         Ok(())
     }
 }
 
-// Statistics query functions for TUI
+type CommitRow = (i64, i64, String, i64, Option<i64>, Option<DateTime<Utc>>, Option<DateTime<Utc>>, i64, i64, String, Option<String>);
 
-pub async fn get_org_stats(pool_con: &mut PoolConn, scrape_id: i64) -> Result<Vec<crate::stats::OrgStats>> {
-    let org_stats_rows: Vec<(String, i64, i64, i64, i64)> = query_as("
-        SELECT 
-            o.name,
-            SUM(rs.commits) as total_commits,
-            SUM(rs.lines) as total_lines,
-            COUNT(DISTINCT rs.repo_id) as repo_count,
-            COUNT(DISTINCT cs.contributor_id) as contributor_count
-        FROM orgs o
-        JOIN repo_scrapes rs ON o.id = rs.org_id
-        LEFT JOIN contributor_scrapes cs ON rs.id = cs.repo_scrape_id
-        WHERE rs.scrape_id = $1
-        GROUP BY o.id, o.name
-        ORDER BY total_commits DESC;
-    ").bind(scrape_id).fetch_all(pool_con.as_mut()).await?;
+pub struct Commit {
+    pub id: i64,
+    pub repo: Repo,
+    pub sha: String,
+    pub author: Contributor,
+    pub committer: Option<Contributor>,
+    pub authored_at: Option<DateTime<Utc>>,
+    pub committed_at: Option<DateTime<Utc>>,
+    pub additions: i64,
+    pub deletions: i64,
+    pub message_summary: String,
+    /// Conventional Commit type of the summary, e.g. `feat` or `fix`
+    pub change_type: Option<String>,
+    pub files: Vec<CommitFile>,
+}
 
-    let mut org_stats = Vec::new();
-    for row in org_stats_rows {
-        org_stats.push(crate::stats::OrgStats {
-            name: row.0,
-            total_commits: row.1,
-            total_lines: row.2,
-            repo_count: row.3,
-            contributor_count: row.4,
-        });
+impl Commit {
+    pub async fn get(pool_con: &mut DbConn, id: &i64) -> Result<Commit> {
+        let commit_row: CommitRow = query_as("
+            SELECT id, repo_id, sha, author_id, committer_id, authored_at, committed_at, additions, deletions, message_summary, change_type
+            FROM commits c
+            WHERE c.id = $1
+            LIMIT 1;
+        ").bind(id).fetch_one(&mut *pool_con).await?;
+
+        let repo = Repo::get(pool_con, &commit_row.1).await?;
+        let author = Contributor::get(pool_con, &commit_row.3).await?;
+        let committer = match commit_row.4 {
+            Some(committer_id) => Some(Contributor::get(pool_con, &committer_id).await?),
+            None => None,
+        };
+
+        let file_rows: Vec<(i64, String, String, i64, i64, bool)> = query_as("
+            SELECT id, path, directory, additions, deletions, excluded
+            FROM commit_files cf
+            WHERE cf.commit_id = $1;
+        ").bind(id).fetch_all(&mut *pool_con).await?;
+
+        let files = file_rows.into_iter().map(|row| CommitFile {
+            id: row.0,
+            path: row.1,
+            directory: row.2,
+            additions: row.3,
+            deletions: row.4,
+            excluded: row.5,
+        }).collect();
+
+        Ok(Commit {
+            id: commit_row.0,
+            repo,
+            sha: commit_row.2,
+            author,
+            committer,
+            authored_at: commit_row.5,
+            committed_at: commit_row.6,
+            additions: commit_row.7,
+            deletions: commit_row.8,
+            message_summary: commit_row.9,
+            change_type: commit_row.10,
+            files,
+        })
     }
 
-    Ok(org_stats)
-}
+    /// Inserts a commit, or refreshes the stored fields if the sha was already
+    /// recorded for this repo by an earlier scrape.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn create(
+        pool_con: &mut DbConn,
+        repo: Repo,
+        sha: String,
+        author: Contributor,
+        committer: Option<Contributor>,
+        authored_at: Option<DateTime<Utc>>,
+        committed_at: Option<DateTime<Utc>>,
+        additions: i64,
+        deletions: i64,
+        message_summary: String,
+        change_type: Option<String>,
+    ) -> Result<Commit> {
+        let result = query("
+            INSERT INTO commits (repo_id, sha, author_id, committer_id, authored_at, committed_at, additions, deletions, message_summary, change_type)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+            ON CONFLICT(repo_id, sha) DO UPDATE SET
+                author_id = excluded.author_id,
+                committer_id = excluded.committer_id,
+                authored_at = excluded.authored_at,
+                committed_at = excluded.committed_at,
+                additions = excluded.additions,
+                deletions = excluded.deletions,
+                message_summary = excluded.message_summary,
+                change_type = excluded.change_type
+            RETURNING id
+        ")
+            .bind(repo.id)
+            .bind(&sha)
+            .bind(author.id)
+            .bind(committer.as_ref().map(|c| c.id))
+            .bind(authored_at)
+            .bind(committed_at)
+            .bind(additions)
+            .bind(deletions)
+            .bind(&message_summary)
+            .bind(&change_type)
+            .fetch_one(&mut *pool_con)
+            .await?;
 
-pub async fn get_repo_stats(pool_con: &mut PoolConn, scrape_id: i64) -> Result<Vec<crate::stats::RepoStats>> {
-    let repo_stats_rows: Vec<(String, String, i64, i64, i64, i64)> = query_as("
-        SELECT 
-            o.name as org_name,
-            r.name as repo_name,
-            rs.commits,
-            rs.lines,
-            rs.prs,
-            COUNT(DISTINCT cs.contributor_id) as contributor_count
-        FROM repo_scrapes rs
-        JOIN orgs o ON rs.org_id = o.id
-        JOIN repos r ON rs.repo_id = r.id
-        LEFT JOIN contributor_scrapes cs ON rs.id = cs.repo_scrape_id
-        WHERE rs.scrape_id = $1
-        GROUP BY rs.id, o.name, r.name, rs.commits, rs.lines, rs.prs
-        ORDER BY rs.commits DESC;
-    ").bind(scrape_id).fetch_all(pool_con.as_mut()).await?;
+        let id: i64 = result.get(0);
 
-    let mut repo_stats = Vec::new();
-    for row in repo_stats_rows {
-        repo_stats.push(crate::stats::RepoStats {
-            org_name: row.0,
-            repo_name: row.1,
-            commits: row.2,
-            lines: row.3,
-            prs: row.4,
-            contributor_count: row.5,
-        });
+        Ok(Commit {
+            id,
+            repo,
+            sha,
+            author,
+            committer,
+            authored_at,
+            committed_at,
+            additions,
+            deletions,
+            message_summary,
+            change_type,
+            files: Vec::new(),
+        })
     }
 
-    Ok(repo_stats)
-}
+    pub async fn save(&self, pool_con: &mut DbConn) -> Result<()> {
+        let _res = query("
+            UPDATE commits
+            set repo_id = $1, sha = $2, author_id = $3, committer_id = $4, authored_at = $5,
+                committed_at = $6, additions = $7, deletions = $8, message_summary = $9, change_type = $10
+            where id = $11
+        ")
+            .bind(self.repo.id)
+            .bind(&self.sha)
+            .bind(self.author.id)
+            .bind(self.committer.as_ref().map(|c| c.id))
+            .bind(self.authored_at)
+            .bind(self.committed_at)
+            .bind(self.additions)
+            .bind(self.deletions)
+            .bind(&self.message_summary)
+            .bind(&self.change_type)
+            .bind(self.id)
+            .execute(&mut *pool_con)
+            .await?;
 
-pub async fn get_contributor_stats(pool_con: &mut PoolConn, scrape_id: i64) -> Result<Vec<crate::stats::ContributorStats>> {
-    let contributor_stats_rows: Vec<(String, i64, i64, i64)> = query_as("
-        SELECT 
-            c.username,
-            SUM(cs.commits) as total_commits,
-            SUM(cs.lines) as total_lines,
-            COUNT(DISTINCT rs.repo_id) as repo_count
-        FROM contributors c
-        JOIN contributor_scrapes cs ON c.id = cs.contributor_id
-        JOIN repo_scrapes rs ON cs.repo_scrape_id = rs.id
-        WHERE rs.scrape_id = $1
-        GROUP BY c.id, c.username
-        ORDER BY total_commits DESC;
-    ").bind(scrape_id).fetch_all(pool_con.as_mut()).await?;
+        for file in &self.files {
+            file.save(pool_con).await?;
+        }
 
-    let mut contributor_stats = Vec::new();
-    for row in contributor_stats_rows {
-        // Get the list of orgs this contributor worked in
-        let org_names: Vec<(String,)> = query_as("
-            SELECT DISTINCT o.name
-            FROM contributors c
-            JOIN contributor_scrapes cs ON c.id = cs.contributor_id
-            JOIN repo_scrapes rs ON cs.repo_scrape_id = rs.id
-            JOIN orgs o ON rs.org_id = o.id
-            WHERE c.username = $1 AND rs.scrape_id = $2
-            ORDER BY o.name;
-        ").bind(&row.0).bind(scrape_id).fetch_all(pool_con.as_mut()).await?;
+        Ok(())
+    }
 
-        let orgs: Vec<String> = org_names.into_iter().map(|org| org.0).collect();
+    /// Looks up the id of a stored commit by repo and sha.
+    pub async fn find_id(pool_con: &mut DbConn, repo_id: i64, sha: &str) -> Result<Option<i64>> {
+        let commit_id: Option<(i64,)> = query_as("
+            SELECT id FROM commits WHERE repo_id = $1 AND sha = $2 LIMIT 1;
+        ").bind(repo_id).bind(sha).fetch_optional(&mut *pool_con).await?;
+        Ok(commit_id.map(|row| row.0))
+    }
 
-        contributor_stats.push(crate::stats::ContributorStats {
-            username: row.0,
-            total_commits: row.1,
-            total_lines: row.2,
-            repo_count: row.3,
-            orgs,
-        });
+    /// Counts stored commits per author for a repo within a window.
+    /// This is what the `commits` counters on scrapes are derived from.
+    pub async fn count_by_author(pool_con: &mut DbConn, repo_id: i64, start_dt: DateTime<Utc>, end_dt: DateTime<Utc>) -> Result<Vec<(String, i64)>> {
+        let rows: Vec<(String, i64)> = query_as("
+            SELECT c.username, COUNT(*) as commits
+            FROM commits cm
+            JOIN contributors c ON cm.author_id = c.id
+            WHERE cm.repo_id = $1 AND cm.committed_at >= $2 AND cm.committed_at <= $3
+            GROUP BY c.id, c.username
+            ORDER BY commits DESC;
+        ")
+            .bind(repo_id)
+            .bind(start_dt)
+            .bind(end_dt)
+            .fetch_all(&mut *pool_con)
+            .await?;
+
+        Ok(rows)
     }
+}
 
-    Ok(contributor_stats)
+pub struct CommitFile {
+    pub id: i64,
+    pub path: String,
+    pub directory: String,
+    pub additions: i64,
+    pub deletions: i64,
+    /// Matched an excluded line pattern (lockfiles, vendored or generated code)
+    pub excluded: bool,
 }
 
-// Detail view queries for drill-down functionality
+impl CommitFile {
+    pub async fn get(pool_con: &mut DbConn, id: &i64) -> Result<CommitFile> {
+        let file_row: (i64, String, String, i64, i64, bool) = query_as("
+            SELECT id, path, directory, additions, deletions, excluded
+            FROM commit_files cf
+            WHERE cf.id = $1
+            LIMIT 1;
+        ").bind(id).fetch_one(&mut *pool_con).await?;
+
+        Ok(CommitFile {
+            id: file_row.0,
+            path: file_row.1,
+            directory: file_row.2,
+            additions: file_row.3,
+            deletions: file_row.4,
+            excluded: file_row.5,
+        })
+    }
 
-pub async fn get_org_detail(pool_con: &mut PoolConn, scrape_id: i64, org_name: &str) -> Result<crate::stats::OrgDetail> {
-    // Get all repos for this organization
-    let repo_rows: Vec<(String, String, i64, i64, i64, i64)> = query_as("
-        SELECT 
-            o.name as org_name,
-            r.name as repo_name,
-            rs.commits,
-            rs.lines,
-            rs.prs,
-            COUNT(DISTINCT cs.contributor_id) as contributor_count
-        FROM orgs o
-        JOIN repo_scrapes rs ON o.id = rs.org_id
-        JOIN repos r ON rs.repo_id = r.id
-        LEFT JOIN contributor_scrapes cs ON rs.id = cs.repo_scrape_id
-        WHERE rs.scrape_id = $1 AND o.name = $2
-        GROUP BY o.name, r.name, rs.commits, rs.lines, rs.prs
-        ORDER BY rs.commits DESC;
-    ").bind(scrape_id).bind(org_name).fetch_all(pool_con.as_mut()).await?;
+    pub async fn create(pool_con: &mut DbConn, commit_id: i64, path: String, directory: String, additions: i64, deletions: i64, excluded: bool) -> Result<CommitFile> {
+        let result = query("
+            INSERT INTO commit_files (commit_id, path, directory, additions, deletions, excluded)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            ON CONFLICT(commit_id, path) DO UPDATE SET
+                directory = excluded.directory,
+                additions = excluded.additions,
+                deletions = excluded.deletions,
+                excluded = excluded.excluded
+            RETURNING id
+        ")
+            .bind(commit_id)
+            .bind(&path)
+            .bind(&directory)
+            .bind(additions)
+            .bind(deletions)
+            .bind(excluded)
+            .fetch_one(&mut *pool_con)
+            .await?;
 
-    let repos = repo_rows.into_iter().map(|row| crate::stats::RepoStats {
-        org_name: row.0,
-        repo_name: row.1,
-        commits: row.2,
-        lines: row.3,
-        prs: row.4,
-        contributor_count: row.5,
-    }).collect();
+        let id: i64 = result.get(0);
 
-    Ok(crate::stats::OrgDetail {
-        org_name: org_name.to_string(),
-        repos,
-    })
+        Ok(CommitFile { id, path, directory, additions, deletions, excluded })
+    }
+
+    pub async fn save(&self, pool_con: &mut DbConn) -> Result<()> {
+        let _res = query("
+            UPDATE commit_files
+            set path = $1, directory = $2, additions = $3, deletions = $4, excluded = $5
+            where id = $6
+        ")
+            .bind(&self.path)
+            .bind(&self.directory)
+            .bind(self.additions)
+            .bind(self.deletions)
+            .bind(self.excluded)
+            .bind(self.id)
+            .execute(&mut *pool_con)
+            .await?;
+        Ok(())
+    }
 }
 
-pub async fn get_repo_detail(pool_con: &mut PoolConn, scrape_id: i64, org_name: &str, repo_name: &str) -> Result<crate::stats::RepoDetail> {
-    // Get all contributors for this specific repository
-    let contributor_rows: Vec<(String, i64, i64)> = query_as("
-        SELECT 
-            c.username,
-            cs.commits,
-            cs.lines
-        FROM contributors c
-        JOIN contributor_scrapes cs ON c.id = cs.contributor_id
-        JOIN repo_scrapes rs ON cs.repo_scrape_id = rs.id
-        JOIN repos r ON rs.repo_id = r.id
-        JOIN orgs o ON rs.org_id = o.id
-        WHERE rs.scrape_id = $1 AND o.name = $2 AND r.name = $3
-        ORDER BY cs.commits DESC;
-    ").bind(scrape_id).bind(org_name).bind(repo_name).fetch_all(pool_con.as_mut()).await?;
+type PullRequestRow = (i64, i64, i64, String, i64, String, Option<DateTime<Utc>>, Option<DateTime<Utc>>, Option<DateTime<Utc>>, Option<DateTime<Utc>>, i64, i64, i64, String, Option<String>);
 
-    let contributors = contributor_rows.into_iter().map(|row| crate::stats::RepoContributor {
-        username: row.0,
-        commits: row.1,
-        lines: row.2,
-        prs: 0, // PRs not tracked at contributor level
-    }).collect();
+pub struct PullRequest {
+    pub id: i64,
+    pub repo: Repo,
+    pub number: i64,
+    pub title: String,
+    pub author: Contributor,
+    pub state: String,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+    pub closed_at: Option<DateTime<Utc>>,
+    pub merged_at: Option<DateTime<Utc>>,
+    pub additions: i64,
+    pub deletions: i64,
+    pub changed_files: i64,
+    pub base_branch: String,
+    /// Conventional Commit type of the title, e.g. `feat` or `fix`
+    pub change_type: Option<String>,
+    pub files: Vec<PullRequestFile>,
+    pub reviews: Vec<PullRequestReview>,
+}
 
-    Ok(crate::stats::RepoDetail {
-        org_name: org_name.to_string(),
-        repo_name: repo_name.to_string(),
+impl PullRequest {
+    pub async fn get(pool_con: &mut DbConn, id: &i64) -> Result<PullRequest> {
+        let pr_row: PullRequestRow = query_as("
+            SELECT id, repo_id, number, title, author_id, state, created_at, updated_at, closed_at, merged_at,
+                   additions, deletions, changed_files, base_branch, change_type
+            FROM pull_requests pr
+            WHERE pr.id = $1
+            LIMIT 1;
+        ").bind(id).fetch_one(&mut *pool_con).await?;
+
+        let repo = Repo::get(pool_con, &pr_row.1).await?;
+        let author = Contributor::get(pool_con, &pr_row.4).await?;
+
+        let file_rows: Vec<(i64, String, i64, i64, bool)> = query_as("
+            SELECT id, path, additions, deletions, excluded
+            FROM pull_request_files prf
+            WHERE prf.pull_request_id = $1;
+        ").bind(id).fetch_all(&mut *pool_con).await?;
+
+        let files = file_rows.into_iter().map(|row| PullRequestFile {
+            id: row.0,
+            path: row.1,
+            additions: row.2,
+            deletions: row.3,
+            excluded: row.4,
+        }).collect();
+
+        let review_ids: Vec<(i64,)> = query_as("
+            SELECT id
+            FROM pull_request_reviews prr
+            WHERE prr.pull_request_id = $1
+            ORDER BY prr.submitted_at;
+        ").bind(id).fetch_all(&mut *pool_con).await?;
+
+        let mut reviews = Vec::new();
+        for (review_id,) in review_ids {
+            reviews.push(PullRequestReview::get(pool_con, &review_id).await?);
+        }
+
+        Ok(PullRequest {
+            id: pr_row.0,
+            repo,
+            number: pr_row.2,
+            title: pr_row.3,
+            author,
+            state: pr_row.5,
+            created_at: pr_row.6,
+            updated_at: pr_row.7,
+            closed_at: pr_row.8,
+            merged_at: pr_row.9,
+            additions: pr_row.10,
+            deletions: pr_row.11,
+            changed_files: pr_row.12,
+            base_branch: pr_row.13,
+            change_type: pr_row.14,
+            files,
+            reviews,
+        })
+    }
+
+    /// Inserts a pull request, or refreshes it if this repo/number was already stored.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn create(
+        pool_con: &mut DbConn,
+        repo: Repo,
+        number: i64,
+        title: String,
+        author: Contributor,
+        state: String,
+        created_at: Option<DateTime<Utc>>,
+        updated_at: Option<DateTime<Utc>>,
+        closed_at: Option<DateTime<Utc>>,
+        merged_at: Option<DateTime<Utc>>,
+        additions: i64,
+        deletions: i64,
+        changed_files: i64,
+        base_branch: String,
+        change_type: Option<String>,
+    ) -> Result<PullRequest> {
+        let result = query("
+            INSERT INTO pull_requests (repo_id, number, title, author_id, state, created_at, updated_at, closed_at, merged_at,
+                                       additions, deletions, changed_files, base_branch, change_type)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14)
+            ON CONFLICT(repo_id, number) DO UPDATE SET
+                title = excluded.title,
+                author_id = excluded.author_id,
+                state = excluded.state,
+                created_at = excluded.created_at,
+                updated_at = excluded.updated_at,
+                closed_at = excluded.closed_at,
+                merged_at = excluded.merged_at,
+                additions = excluded.additions,
+                deletions = excluded.deletions,
+                changed_files = excluded.changed_files,
+                base_branch = excluded.base_branch,
+                change_type = excluded.change_type
+            RETURNING id
+        ")
+            .bind(repo.id)
+            .bind(number)
+            .bind(&title)
+            .bind(author.id)
+            .bind(&state)
+            .bind(created_at)
+            .bind(updated_at)
+            .bind(closed_at)
+            .bind(merged_at)
+            .bind(additions)
+            .bind(deletions)
+            .bind(changed_files)
+            .bind(&base_branch)
+            .bind(&change_type)
+            .fetch_one(&mut *pool_con)
+            .await?;
+
+        let id: i64 = result.get(0);
+
+        Ok(PullRequest {
+            id,
+            repo,
+            number,
+            title,
+            author,
+            state,
+            created_at,
+            updated_at,
+            closed_at,
+            merged_at,
+            additions,
+            deletions,
+            changed_files,
+            base_branch,
+            change_type,
+            files: Vec::new(),
+            reviews: Vec::new(),
+        })
+    }
+
+    pub async fn save(&self, pool_con: &mut DbConn) -> Result<()> {
+        let _res = query("
+            UPDATE pull_requests
+            set repo_id = $1, number = $2, title = $3, author_id = $4, state = $5, created_at = $6, updated_at = $7,
+                closed_at = $8, merged_at = $9, additions = $10, deletions = $11, changed_files = $12, base_branch = $13,
+                change_type = $14
+            where id = $15
+        ")
+            .bind(self.repo.id)
+            .bind(self.number)
+            .bind(&self.title)
+            .bind(self.author.id)
+            .bind(&self.state)
+            .bind(self.created_at)
+            .bind(self.updated_at)
+            .bind(self.closed_at)
+            .bind(self.merged_at)
+            .bind(self.additions)
+            .bind(self.deletions)
+            .bind(self.changed_files)
+            .bind(&self.base_branch)
+            .bind(&self.change_type)
+            .bind(self.id)
+            .execute(&mut *pool_con)
+            .await?;
+
+        for file in &self.files {
+            file.save(pool_con).await?;
+        }
+        for review in &self.reviews {
+            review.save(pool_con).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Looks up the id of a stored pull request by repo and number.
+    pub async fn find_id(pool_con: &mut DbConn, repo_id: i64, number: i64) -> Result<Option<i64>> {
+        let pr_id: Option<(i64,)> = query_as("
+            SELECT id FROM pull_requests WHERE repo_id = $1 AND number = $2 LIMIT 1;
+        ").bind(repo_id).bind(number).fetch_optional(&mut *pool_con).await?;
+        Ok(pr_id.map(|row| row.0))
+    }
+
+    /// Sums PRs merged within a window per author as `(username, prs, lines, excluded_lines)`.
+    /// This is what the `prs` and `lines` counters on scrapes are derived from. Lines in
+    /// files matching an excluded pattern are reported separately rather than counted.
+    pub async fn totals_by_author(pool_con: &mut DbConn, repo_id: i64, start_dt: DateTime<Utc>, end_dt: DateTime<Utc>) -> Result<Vec<(String, i64, i64, i64)>> {
+        let rows: Vec<(String, i64, i64, i64)> = query_as("
+            SELECT
+                c.username,
+                COUNT(*) as prs,
+                SUM(pr.additions + pr.deletions - COALESCE(ex.lines, 0)) as lines,
+                SUM(COALESCE(ex.lines, 0)) as excluded_lines
+            FROM pull_requests pr
+            JOIN contributors c ON pr.author_id = c.id
+            LEFT JOIN (
+                SELECT pull_request_id, SUM(additions + deletions) as lines
+                FROM pull_request_files
+                WHERE excluded
+                GROUP BY pull_request_id
+            ) ex ON ex.pull_request_id = pr.id
+            WHERE pr.repo_id = $1 AND pr.merged_at >= $2 AND pr.merged_at <= $3
+            GROUP BY c.id, c.username
+            ORDER BY prs DESC;
+        ")
+            .bind(repo_id)
+            .bind(start_dt)
+            .bind(end_dt)
+            .fetch_all(&mut *pool_con)
+            .await?;
+
+        Ok(rows)
+    }
+}
+
+pub struct PullRequestFile {
+    pub id: i64,
+    pub path: String,
+    pub additions: i64,
+    pub deletions: i64,
+    /// Matched an excluded line pattern (lockfiles, vendored or generated code)
+    pub excluded: bool,
+}
+
+impl PullRequestFile {
+    pub async fn get(pool_con: &mut DbConn, id: &i64) -> Result<PullRequestFile> {
+        let file_row: (i64, String, i64, i64, bool) = query_as("
+            SELECT id, path, additions, deletions, excluded
+            FROM pull_request_files prf
+            WHERE prf.id = $1
+            LIMIT 1;
+        ").bind(id).fetch_one(&mut *pool_con).await?;
+
+        Ok(PullRequestFile {
+            id: file_row.0,
+            path: file_row.1,
+            additions: file_row.2,
+            deletions: file_row.3,
+            excluded: file_row.4,
+        })
+    }
+
+    pub async fn create(pool_con: &mut DbConn, pull_request_id: i64, path: String, additions: i64, deletions: i64, excluded: bool) -> Result<PullRequestFile> {
+        let result = query("
+            INSERT INTO pull_request_files (pull_request_id, path, additions, deletions, excluded)
+            VALUES ($1, $2, $3, $4, $5)
+            ON CONFLICT(pull_request_id, path) DO UPDATE SET
+                additions = excluded.additions,
+                deletions = excluded.deletions,
+                excluded = excluded.excluded
+            RETURNING id
+        ")
+            .bind(pull_request_id)
+            .bind(&path)
+            .bind(additions)
+            .bind(deletions)
+            .bind(excluded)
+            .fetch_one(&mut *pool_con)
+            .await?;
+
+        let id: i64 = result.get(0);
+
+        Ok(PullRequestFile { id, path, additions, deletions, excluded })
+    }
+
+    pub async fn save(&self, pool_con: &mut DbConn) -> Result<()> {
+        let _res = query("
+            UPDATE pull_request_files
+            set path = $1, additions = $2, deletions = $3, excluded = $4
+            where id = $5
+        ")
+            .bind(&self.path)
+            .bind(self.additions)
+            .bind(self.deletions)
+            .bind(self.excluded)
+            .bind(self.id)
+            .execute(&mut *pool_con)
+            .await?;
+        Ok(())
+    }
+}
+
+pub struct PullRequestReview {
+    pub id: i64,
+    pub github_id: i64,
+    pub reviewer: Contributor,
+    /// GitHub review state, e.g. `approved`, `changes_requested` or `commented`
+    pub state: String,
+    pub submitted_at: Option<DateTime<Utc>>,
+}
+
+impl PullRequestReview {
+    pub async fn get(pool_con: &mut DbConn, id: &i64) -> Result<PullRequestReview> {
+        let review_row: (i64, i64, i64, String, Option<DateTime<Utc>>) = query_as("
+            SELECT id, github_id, reviewer_id, state, submitted_at
+            FROM pull_request_reviews prr
+            WHERE prr.id = $1
+            LIMIT 1;
+        ").bind(id).fetch_one(&mut *pool_con).await?;
+
+        let reviewer = Contributor::get(pool_con, &review_row.2).await?;
+
+        Ok(PullRequestReview {
+            id: review_row.0,
+            github_id: review_row.1,
+            reviewer,
+            state: review_row.3,
+            submitted_at: review_row.4,
+        })
+    }
+
+    /// Inserts a review, or refreshes it if this GitHub review was already stored.
+    pub async fn create(pool_con: &mut DbConn, pull_request_id: i64, github_id: i64, reviewer: Contributor, state: String, submitted_at: Option<DateTime<Utc>>) -> Result<PullRequestReview> {
+        let result = query("
+            INSERT INTO pull_request_reviews (pull_request_id, github_id, reviewer_id, state, submitted_at)
+            VALUES ($1, $2, $3, $4, $5)
+            ON CONFLICT(github_id) DO UPDATE SET
+                reviewer_id = excluded.reviewer_id,
+                state = excluded.state,
+                submitted_at = excluded.submitted_at
+            RETURNING id
+        ")
+            .bind(pull_request_id)
+            .bind(github_id)
+            .bind(reviewer.id)
+            .bind(&state)
+            .bind(submitted_at)
+            .fetch_one(&mut *pool_con)
+            .await?;
+
+        let id: i64 = result.get(0);
+
+        Ok(PullRequestReview { id, github_id, reviewer, state, submitted_at })
+    }
+
+    pub async fn save(&self, pool_con: &mut DbConn) -> Result<()> {
+        let _res = query("
+            UPDATE pull_request_reviews
+            set github_id = $1, reviewer_id = $2, state = $3, submitted_at = $4
+            where id = $5
+        ")
+            .bind(self.github_id)
+            .bind(self.reviewer.id)
+            .bind(&self.state)
+            .bind(self.submitted_at)
+            .bind(self.id)
+            .execute(&mut *pool_con)
+            .await?;
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Team {
+    pub id: i64,
+    pub org: Org,
+    pub slug: String,
+    pub name: String,
+    pub members: Vec<Contributor>,
+}
+
+impl Team {
+    pub async fn get(pool_con: &mut DbConn, id: &i64) -> Result<Team> {
+        let team_row: (i64, i64, String, String) = query_as("
+            SELECT id, org_id, slug, name
+            FROM teams t
+            WHERE t.id = $1
+            LIMIT 1;
+        ").bind(id).fetch_one(&mut *pool_con).await?;
+
+        let org = Org::get(pool_con, &team_row.1).await?;
+
+        let member_rows: Vec<(i64, String)> = query_as("
+            SELECT c.id, c.username
+            FROM team_members tm
+            JOIN contributors c ON tm.contributor_id = c.id
+            WHERE tm.team_id = $1
+            ORDER BY c.username;
+        ").bind(id).fetch_all(&mut *pool_con).await?;
+
+        let members = member_rows.into_iter()
+            .map(|row| Contributor { id: row.0, username: row.1 })
+            .collect();
+
+        Ok(Team {
+            id: team_row.0,
+            org,
+            slug: team_row.2,
+            name: team_row.3,
+            members,
+        })
+    }
+
+    /// Inserts a team, or refreshes its name if this org/slug was already stored.
+    pub async fn create(pool_con: &mut DbConn, org: Org, slug: String, name: String) -> Result<Team> {
+        let result = query("
+            INSERT INTO teams (org_id, slug, name)
+            VALUES ($1, $2, $3)
+            ON CONFLICT(org_id, slug) DO UPDATE SET name = excluded.name
+            RETURNING id
+        ")
+            .bind(org.id)
+            .bind(&slug)
+            .bind(&name)
+            .fetch_one(&mut *pool_con)
+            .await?;
+
+        let id: i64 = result.get(0);
+
+        Ok(Team { id, org, slug, name, members: Vec::new() })
+    }
+
+    /// Saves the team and replaces its membership with `members`.
+    pub async fn save(&self, pool_con: &mut DbConn) -> Result<()> {
+        let _res = query("
+            UPDATE teams
+            set org_id = $1, slug = $2, name = $3
+            where id = $4
+        ")
+            .bind(self.org.id)
+            .bind(&self.slug)
+            .bind(&self.name)
+            .bind(self.id)
+            .execute(&mut *pool_con)
+            .await?;
+
+        query("DELETE FROM team_members WHERE team_id = $1")
+            .bind(self.id)
+            .execute(&mut *pool_con)
+            .await?;
+
+        for member in &self.members {
+            query("
+                INSERT INTO team_members (team_id, contributor_id)
+                VALUES ($1, $2)
+                ON CONFLICT(team_id, contributor_id) DO NOTHING
+            ")
+                .bind(self.id)
+                .bind(member.id)
+                .execute(&mut *pool_con)
+                .await?;
+        }
+        Ok(())
+    }
+}
+
+/// Kind of a config-defined group: a team of usernames or a set of repositories
+pub const GROUP_KIND_TEAM: &str = "team";
+pub const GROUP_KIND_REPOS: &str = "repos";
+
+/// A team or repo group defined in config rather than on GitHub
+#[derive(Debug, Clone)]
+pub struct ConfigGroup {
+    pub id: i64,
+    pub kind: String,
+    pub name: String,
+    pub members: Vec<Contributor>,
+    pub repos: Vec<Repo>,
+}
+
+impl ConfigGroup {
+    pub async fn get(pool_con: &mut DbConn, id: &i64) -> Result<ConfigGroup> {
+        let group_row: (i64, String, String) = query_as("
+            SELECT id, kind, name
+            FROM config_groups g
+            WHERE g.id = $1
+            LIMIT 1;
+        ").bind(id).fetch_one(&mut *pool_con).await?;
+
+        let member_rows: Vec<(i64, String)> = query_as("
+            SELECT c.id, c.username
+            FROM config_group_members gm
+            JOIN contributors c ON gm.contributor_id = c.id
+            WHERE gm.group_id = $1
+            ORDER BY c.username;
+        ").bind(id).fetch_all(&mut *pool_con).await?;
+
+        let members = member_rows.into_iter()
+            .map(|row| Contributor { id: row.0, username: row.1 })
+            .collect();
+
+        let repo_ids: Vec<(i64,)> = query_as("
+            SELECT repo_id
+            FROM config_group_repos gr
+            WHERE gr.group_id = $1;
+        ").bind(id).fetch_all(&mut *pool_con).await?;
+
+        let mut repos = Vec::new();
+        for (repo_id,) in repo_ids {
+            repos.push(Repo::get(pool_con, &repo_id).await?);
+        }
+
+        Ok(ConfigGroup {
+            id: group_row.0,
+            kind: group_row.1,
+            name: group_row.2,
+            members,
+            repos,
+        })
+    }
+
+    pub async fn create(pool_con: &mut DbConn, kind: String, name: String) -> Result<ConfigGroup> {
+        let result = query("
+            INSERT INTO config_groups (kind, name)
+            VALUES ($1, $2)
+            ON CONFLICT(kind, name) DO UPDATE SET name = excluded.name
+            RETURNING id
+        ")
+            .bind(&kind)
+            .bind(&name)
+            .fetch_one(&mut *pool_con)
+            .await?;
+
+        let id: i64 = result.get(0);
+
+        Ok(ConfigGroup { id, kind, name, members: Vec::new(), repos: Vec::new() })
+    }
+
+    /// Saves the group and replaces its members and repos.
+    pub async fn save(&self, pool_con: &mut DbConn) -> Result<()> {
+        let _res = query("
+            UPDATE config_groups
+            set kind = $1, name = $2
+            where id = $3
+        ")
+            .bind(&self.kind)
+            .bind(&self.name)
+            .bind(self.id)
+            .execute(&mut *pool_con)
+            .await?;
+
+        query("DELETE FROM config_group_members WHERE group_id = $1")
+            .bind(self.id)
+            .execute(&mut *pool_con)
+            .await?;
+        for member in &self.members {
+            query("
+                INSERT INTO config_group_members (group_id, contributor_id)
+                VALUES ($1, $2)
+                ON CONFLICT(group_id, contributor_id) DO NOTHING
+            ")
+                .bind(self.id)
+                .bind(member.id)
+                .execute(&mut *pool_con)
+                .await?;
+        }
+
+        query("DELETE FROM config_group_repos WHERE group_id = $1")
+            .bind(self.id)
+            .execute(&mut *pool_con)
+            .await?;
+        for repo in &self.repos {
+            query("
+                INSERT INTO config_group_repos (group_id, repo_id)
+                VALUES ($1, $2)
+                ON CONFLICT(group_id, repo_id) DO NOTHING
+            ")
+                .bind(self.id)
+                .bind(repo.id)
+                .execute(&mut *pool_con)
+                .await?;
+        }
+        Ok(())
+    }
+}
+
+/// Replaces the stored config groups with the teams and repo groups currently in config, in one
+/// transaction. Members and repo patterns are resolved against the contributors and repos seen so
+/// far, so this runs when config loads and after each scrape; usernames without any activity yet
+/// are left out until a scrape stores them.
+pub async fn sync_config_groups(pool_con: &mut DbConn, teams: &[TeamConfig], repo_groups: &[RepoGroupConfig]) -> Result<()> {
+    let mut tx = pool_con.begin().await?;
+    query("DELETE FROM config_groups").execute(&mut *tx).await?;
+
+    for team in teams {
+        let mut group = ConfigGroup::create(&mut tx, GROUP_KIND_TEAM.to_string(), team.name.clone()).await?;
+        for username in &team.members {
+            if let Some(id) = Contributor::find_id(&mut tx, username).await? {
+                group.members.push(Contributor { id, username: username.clone() });
+            }
+        }
+        group.save(&mut tx).await?;
+    }
+
+    let repo_rows: Vec<(i64, String, String)> = query_as("
+        SELECT r.id, o.name, r.name
+        FROM repos r
+        JOIN orgs o ON r.org_id = o.id;
+    ").fetch_all(&mut *tx).await?;
+
+    for repo_group in repo_groups {
+        let mut builder = globset::GlobSetBuilder::new();
+        for pattern in &repo_group.repos {
+            builder.add(globset::GlobBuilder::new(pattern).literal_separator(true).build()?);
+        }
+        let globs = builder.build()?;
+
+        let mut group = ConfigGroup::create(&mut tx, GROUP_KIND_REPOS.to_string(), repo_group.name.clone()).await?;
+        for (repo_id, org_name, repo_name) in &repo_rows {
+            if globs.is_match(format!("{org_name}/{repo_name}")) {
+                group.repos.push(Repo::get(&mut tx, repo_id).await?);
+            }
+        }
+        group.save(&mut tx).await?;
+    }
+    tx.commit().await?;
+    Ok(())
+}
+
+type ReleaseRow = (i64, String, String, String, bool, bool, Option<DateTime<Utc>>, Option<DateTime<Utc>>);
+
+pub struct Release {
+    pub id: i64,
+    pub tag_name: String,
+    pub name: String,
+    pub target_commitish: String,
+    pub draft: bool,
+    pub prerelease: bool,
+    pub created_at: Option<DateTime<Utc>>,
+    pub published_at: Option<DateTime<Utc>>,
+}
+
+impl Release {
+    pub async fn get(pool_con: &mut DbConn, id: &i64) -> Result<Release> {
+        let release_row: ReleaseRow = query_as("
+            SELECT id, tag_name, name, target_commitish, draft, prerelease, created_at, published_at
+            FROM releases rl
+            WHERE rl.id = $1
+            LIMIT 1;
+        ").bind(id).fetch_one(&mut *pool_con).await?;
+
+        Ok(Release {
+            id: release_row.0,
+            tag_name: release_row.1,
+            name: release_row.2,
+            target_commitish: release_row.3,
+            draft: release_row.4,
+            prerelease: release_row.5,
+            created_at: release_row.6,
+            published_at: release_row.7,
+        })
+    }
+
+    /// Inserts a release, or refreshes it if this repo/tag was already stored.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn create(
+        pool_con: &mut DbConn,
+        repo_id: i64,
+        tag_name: String,
+        name: String,
+        target_commitish: String,
+        draft: bool,
+        prerelease: bool,
+        created_at: Option<DateTime<Utc>>,
+        published_at: Option<DateTime<Utc>>,
+    ) -> Result<Release> {
+        let result = query("
+            INSERT INTO releases (repo_id, tag_name, name, target_commitish, draft, prerelease, created_at, published_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            ON CONFLICT(repo_id, tag_name) DO UPDATE SET
+                name = excluded.name,
+                target_commitish = excluded.target_commitish,
+                draft = excluded.draft,
+                prerelease = excluded.prerelease,
+                created_at = excluded.created_at,
+                published_at = excluded.published_at
+            RETURNING id
+        ")
+            .bind(repo_id)
+            .bind(&tag_name)
+            .bind(&name)
+            .bind(&target_commitish)
+            .bind(draft)
+            .bind(prerelease)
+            .bind(created_at)
+            .bind(published_at)
+            .fetch_one(&mut *pool_con)
+            .await?;
+
+        let id: i64 = result.get(0);
+
+        Ok(Release { id, tag_name, name, target_commitish, draft, prerelease, created_at, published_at })
+    }
+
+    pub async fn save(&self, pool_con: &mut DbConn) -> Result<()> {
+        let _res = query("
+            UPDATE releases
+            set tag_name = $1, name = $2, target_commitish = $3, draft = $4, prerelease = $5, created_at = $6, published_at = $7
+            where id = $8
+        ")
+            .bind(&self.tag_name)
+            .bind(&self.name)
+            .bind(&self.target_commitish)
+            .bind(self.draft)
+            .bind(self.prerelease)
+            .bind(self.created_at)
+            .bind(self.published_at)
+            .bind(self.id)
+            .execute(&mut *pool_con)
+            .await?;
+        Ok(())
+    }
+}
+
+pub struct Tag {
+    pub id: i64,
+    pub name: String,
+    pub sha: String,
+    /// Commit date of the tagged commit, used as the release date for tags without a release
+    pub committed_at: Option<DateTime<Utc>>,
+}
+
+impl Tag {
+    pub async fn get(pool_con: &mut DbConn, id: &i64) -> Result<Tag> {
+        let tag_row: (i64, String, String, Option<DateTime<Utc>>) = query_as("
+            SELECT id, name, sha, committed_at
+            FROM tags t
+            WHERE t.id = $1
+            LIMIT 1;
+        ").bind(id).fetch_one(&mut *pool_con).await?;
+
+        Ok(Tag {
+            id: tag_row.0,
+            name: tag_row.1,
+            sha: tag_row.2,
+            committed_at: tag_row.3,
+        })
+    }
+
+    /// Looks a tag up by name, so known tags don't need their commit fetched again.
+    pub async fn get_by_name(pool_con: &mut DbConn, repo_id: i64, name: &str) -> Result<Option<Tag>> {
+        let tag_id: Option<(i64,)> = query_as("
+            SELECT id
+            FROM tags t
+            WHERE t.repo_id = $1 AND t.name = $2
+            LIMIT 1;
+        ").bind(repo_id).bind(name).fetch_optional(&mut *pool_con).await?;
+
+        match tag_id {
+            Some((id,)) => Ok(Some(Tag::get(pool_con, &id).await?)),
+            None => Ok(None),
+        }
+    }
+
+    pub async fn create(pool_con: &mut DbConn, repo_id: i64, name: String, sha: String, committed_at: Option<DateTime<Utc>>) -> Result<Tag> {
+        let result = query("
+            INSERT INTO tags (repo_id, name, sha, committed_at)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT(repo_id, name) DO UPDATE SET
+                sha = excluded.sha,
+                committed_at = excluded.committed_at
+            RETURNING id
+        ")
+            .bind(repo_id)
+            .bind(&name)
+            .bind(&sha)
+            .bind(committed_at)
+            .fetch_one(&mut *pool_con)
+            .await?;
+
+        let id: i64 = result.get(0);
+
+        Ok(Tag { id, name, sha, committed_at })
+    }
+
+    pub async fn save(&self, pool_con: &mut DbConn) -> Result<()> {
+        let _res = query("
+            UPDATE tags
+            set name = $1, sha = $2, committed_at = $3
+            where id = $4
+        ")
+            .bind(&self.name)
+            .bind(&self.sha)
+            .bind(self.committed_at)
+            .bind(self.id)
+            .execute(&mut *pool_con)
+            .await?;
+        Ok(())
+    }
+}
+
+pub struct Deployment {
+    pub id: i64,
+    pub github_id: i64,
+    pub environment: String,
+    pub sha: String,
+    pub git_ref: String,
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+impl Deployment {
+    pub async fn get(pool_con: &mut DbConn, id: &i64) -> Result<Deployment> {
+        let deployment_row: (i64, i64, String, String, String, Option<DateTime<Utc>>) = query_as("
+            SELECT id, github_id, environment, sha, git_ref, created_at
+            FROM deployments d
+            WHERE d.id = $1
+            LIMIT 1;
+        ").bind(id).fetch_one(&mut *pool_con).await?;
+
+        Ok(Deployment {
+            id: deployment_row.0,
+            github_id: deployment_row.1,
+            environment: deployment_row.2,
+            sha: deployment_row.3,
+            git_ref: deployment_row.4,
+            created_at: deployment_row.5,
+        })
+    }
+
+    /// Inserts a deployment, or refreshes it if this GitHub deployment was already stored.
+    pub async fn create(pool_con: &mut DbConn, repo_id: i64, github_id: i64, environment: String, sha: String, git_ref: String, created_at: Option<DateTime<Utc>>) -> Result<Deployment> {
+        let result = query("
+            INSERT INTO deployments (repo_id, github_id, environment, sha, git_ref, created_at)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            ON CONFLICT(github_id) DO UPDATE SET
+                environment = excluded.environment,
+                sha = excluded.sha,
+                git_ref = excluded.git_ref,
+                created_at = excluded.created_at
+            RETURNING id
+        ")
+            .bind(repo_id)
+            .bind(github_id)
+            .bind(&environment)
+            .bind(&sha)
+            .bind(&git_ref)
+            .bind(created_at)
+            .fetch_one(&mut *pool_con)
+            .await?;
+
+        let id: i64 = result.get(0);
+
+        Ok(Deployment { id, github_id, environment, sha, git_ref, created_at })
+    }
+
+    pub async fn save(&self, pool_con: &mut DbConn) -> Result<()> {
+        let _res = query("
+            UPDATE deployments
+            set github_id = $1, environment = $2, sha = $3, git_ref = $4, created_at = $5
+            where id = $6
+        ")
+            .bind(self.github_id)
+            .bind(&self.environment)
+            .bind(&self.sha)
+            .bind(&self.git_ref)
+            .bind(self.created_at)
+            .bind(self.id)
+            .execute(&mut *pool_con)
+            .await?;
+        Ok(())
+    }
+}
+
+type WorkflowRunRow = (i64, i64, i64, String, String, String, String, String, Option<String>, i64, Option<DateTime<Utc>>, Option<DateTime<Utc>>, Option<DateTime<Utc>>);
+
+pub struct WorkflowRun {
+    pub id: i64,
+    pub github_id: i64,
+    pub workflow_id: i64,
+    pub name: String,
+    pub head_branch: String,
+    pub head_sha: String,
+    pub event: String,
+    /// `queued`, `in_progress` or `completed`
+    pub status: String,
+    /// Set once the run completes, e.g. `success`, `failure` or `cancelled`
+    pub conclusion: Option<String>,
+    pub run_attempt: i64,
+    pub run_started_at: Option<DateTime<Utc>>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+impl WorkflowRun {
+    pub async fn get(pool_con: &mut DbConn, id: &i64) -> Result<WorkflowRun> {
+        let run_row: WorkflowRunRow = query_as("
+            SELECT id, github_id, workflow_id, name, head_branch, head_sha, event, status, conclusion, run_attempt, run_started_at, created_at, updated_at
+            FROM workflow_runs wr
+            WHERE wr.id = $1
+            LIMIT 1;
+        ").bind(id).fetch_one(&mut *pool_con).await?;
+
+        Ok(WorkflowRun {
+            id: run_row.0,
+            github_id: run_row.1,
+            workflow_id: run_row.2,
+            name: run_row.3,
+            head_branch: run_row.4,
+            head_sha: run_row.5,
+            event: run_row.6,
+            status: run_row.7,
+            conclusion: run_row.8,
+            run_attempt: run_row.9,
+            run_started_at: run_row.10,
+            created_at: run_row.11,
+            updated_at: run_row.12,
+        })
+    }
+
+    /// Inserts a workflow run, or refreshes it if this GitHub run was already stored.
+    /// Re-runs keep their id, so the latest attempt replaces the earlier one.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn create(
+        pool_con: &mut DbConn,
+        repo_id: i64,
+        github_id: i64,
+        workflow_id: i64,
+        name: String,
+        head_branch: String,
+        head_sha: String,
+        event: String,
+        status: String,
+        conclusion: Option<String>,
+        run_attempt: i64,
+        run_started_at: Option<DateTime<Utc>>,
+        created_at: Option<DateTime<Utc>>,
+        updated_at: Option<DateTime<Utc>>,
+    ) -> Result<WorkflowRun> {
+        let result = query("
+            INSERT INTO workflow_runs (repo_id, github_id, workflow_id, name, head_branch, head_sha, event, status, conclusion, run_attempt, run_started_at, created_at, updated_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)
+            ON CONFLICT(github_id) DO UPDATE SET
+                workflow_id = excluded.workflow_id,
+                name = excluded.name,
+                head_branch = excluded.head_branch,
+                head_sha = excluded.head_sha,
+                event = excluded.event,
+                status = excluded.status,
+                conclusion = excluded.conclusion,
+                run_attempt = excluded.run_attempt,
+                run_started_at = excluded.run_started_at,
+                created_at = excluded.created_at,
+                updated_at = excluded.updated_at
+            RETURNING id
+        ")
+            .bind(repo_id)
+            .bind(github_id)
+            .bind(workflow_id)
+            .bind(&name)
+            .bind(&head_branch)
+            .bind(&head_sha)
+            .bind(&event)
+            .bind(&status)
+            .bind(&conclusion)
+            .bind(run_attempt)
+            .bind(run_started_at)
+            .bind(created_at)
+            .bind(updated_at)
+            .fetch_one(&mut *pool_con)
+            .await?;
+
+        let id: i64 = result.get(0);
+
+        Ok(WorkflowRun {
+            id,
+            github_id,
+            workflow_id,
+            name,
+            head_branch,
+            head_sha,
+            event,
+            status,
+            conclusion,
+            run_attempt,
+            run_started_at,
+            created_at,
+            updated_at,
+        })
+    }
+
+    pub async fn save(&self, pool_con: &mut DbConn) -> Result<()> {
+        let _res = query("
+            UPDATE workflow_runs
+            set github_id = $1, workflow_id = $2, name = $3, head_branch = $4, head_sha = $5, event = $6, status = $7,
+                conclusion = $8, run_attempt = $9, run_started_at = $10, created_at = $11, updated_at = $12
+            where id = $13
+        ")
+            .bind(self.github_id)
+            .bind(self.workflow_id)
+            .bind(&self.name)
+            .bind(&self.head_branch)
+            .bind(&self.head_sha)
+            .bind(&self.event)
+            .bind(&self.status)
+            .bind(&self.conclusion)
+            .bind(self.run_attempt)
+            .bind(self.run_started_at)
+            .bind(self.created_at)
+            .bind(self.updated_at)
+            .bind(self.id)
+            .execute(&mut *pool_con)
+            .await?;
+        Ok(())
+    }
+}
+
+type HttpCacheRow = (String, String, Option<String>, Option<String>, String, Vec<u8>, DateTime<Utc>);
+
+/// A cached GitHub API response, keyed by URL and `Accept` header
+#[derive(Debug, Clone, PartialEq)]
+pub struct HttpCacheEntry {
+    pub url: String,
+    pub accept: String,
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+    /// Response headers worth replaying, such as `Content-Type` and `Link`
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+    pub fetched_at: DateTime<Utc>,
+}
+
+impl HttpCacheEntry {
+    pub async fn get(pool_con: &mut DbConn, url: &str, accept: &str) -> Result<Option<HttpCacheEntry>> {
+        let row: Option<HttpCacheRow> = query_as("
+            SELECT url, accept, etag, last_modified, headers, body, fetched_at
+            FROM http_cache
+            WHERE url = $1 AND accept = $2
+            LIMIT 1;
+        ").bind(url).bind(accept).fetch_optional(&mut *pool_con).await?;
+
+        row.map(|row| Ok(HttpCacheEntry {
+            url: row.0,
+            accept: row.1,
+            etag: row.2,
+            last_modified: row.3,
+            headers: serde_json::from_str(&row.4)?,
+            body: row.5,
+            fetched_at: row.6,
+        })).transpose()
+    }
+
+    /// Inserts the entry, replacing any earlier response for the same URL and `Accept` header.
+    pub async fn save(&self, pool_con: &mut DbConn) -> Result<()> {
+        let _res = query("
+            INSERT INTO http_cache (url, accept, etag, last_modified, headers, body, fetched_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            ON CONFLICT(url, accept) DO UPDATE SET
+                etag = excluded.etag,
+                last_modified = excluded.last_modified,
+                headers = excluded.headers,
+                body = excluded.body,
+                fetched_at = excluded.fetched_at;
+        ")
+            .bind(&self.url)
+            .bind(&self.accept)
+            .bind(&self.etag)
+            .bind(&self.last_modified)
+            .bind(serde_json::to_string(&self.headers)?)
+            .bind(&self.body)
+            .bind(self.fetched_at)
+            .execute(&mut *pool_con)
+            .await?;
+
+        Ok(())
+    }
+}
+
+// Statistics query functions for TUI
+
+/// Release/deployment counts and PR lead times for every repo in a scrape, keyed by `(org, repo)`
+struct DeliveryData {
+    weeks: f64,
+    counts: HashMap<(String, String), (i64, i64)>,
+    lead_times: HashMap<(String, String), Vec<f64>>,
+}
+
+impl DeliveryData {
+    async fn load(pool_con: &mut DbConn, scrape_id: i64) -> Result<DeliveryData> {
+        let window: (DateTime<Utc>, DateTime<Utc>) = query_as("
+            SELECT start_dt, end_dt FROM scrapes WHERE id = $1;
+        ").bind(scrape_id).fetch_one(&mut *pool_con).await?;
+        let weeks = ((window.1 - window.0).num_seconds() as f64 / (7.0 * 86_400.0)).max(1.0 / 7.0);
+
+        let count_rows: Vec<(String, String, i64, i64)> = query_as("
+            SELECT
+                o.name,
+                r.name,
+                (
+                    SELECT COUNT(*)
+                    FROM release_events re
+                    WHERE re.repo_id = r.id AND re.released_at >= s.start_dt AND re.released_at <= s.end_dt
+                ) as releases,
+                (
+                    SELECT COUNT(*)
+                    FROM deployments d
+                    WHERE d.repo_id = r.id AND d.created_at >= s.start_dt AND d.created_at <= s.end_dt
+                ) as deployments
+            FROM repo_scrapes rs
+            JOIN scrapes s ON rs.scrape_id = s.id
+            JOIN repos r ON rs.repo_id = r.id
+            JOIN orgs o ON r.org_id = o.id
+            WHERE rs.scrape_id = $1;
+        ").bind(scrape_id).fetch_all(&mut *pool_con).await?;
+
+        // Lead time runs from merge to the first release published at or after it
+        let lead_time_rows: Vec<(String, String, f64)> = query_as("
+            SELECT
+                o.name,
+                r.name,
+                (julianday(MIN(re.released_at)) - julianday(pr.merged_at)) * 24.0 as lead_time_hours
+            FROM pull_requests pr
+            JOIN repos r ON pr.repo_id = r.id
+            JOIN orgs o ON r.org_id = o.id
+            JOIN scrapes s ON s.id = $1
+            JOIN release_events re ON re.repo_id = pr.repo_id AND re.released_at >= pr.merged_at
+            WHERE pr.repo_id IN (SELECT repo_id FROM repo_scrapes WHERE scrape_id = $1)
+              AND pr.merged_at >= s.start_dt AND pr.merged_at <= s.end_dt
+            GROUP BY pr.id, o.name, r.name, pr.merged_at;
+        ").bind(scrape_id).fetch_all(&mut *pool_con).await?;
+
+        let counts = count_rows.into_iter()
+            .map(|row| ((row.0, row.1), (row.2, row.3)))
+            .collect();
+        let mut lead_times: HashMap<(String, String), Vec<f64>> = HashMap::new();
+        for (org_name, repo_name, hours) in lead_time_rows {
+            lead_times.entry((org_name, repo_name)).or_default().push(hours);
+        }
+
+        Ok(DeliveryData { weeks, counts, lead_times })
+    }
+
+    fn repo(&self, org_name: &str, repo_name: &str) -> crate::stats::DeliveryMetrics {
+        let key = (org_name.to_string(), repo_name.to_string());
+        let (releases, deployments) = self.counts.get(&key).copied().unwrap_or_default();
+        let lead_times = self.lead_times.get(&key).cloned().unwrap_or_default();
+        crate::stats::DeliveryMetrics::new(releases, deployments, crate::stats::deploy_events(releases, deployments), self.weeks, lead_times)
+    }
+
+    fn org(&self, org_name: &str) -> crate::stats::DeliveryMetrics {
+        let (releases, deployments, events) = self.counts.iter()
+            .filter(|((org, _), _)| org == org_name)
+            .fold((0, 0, 0), |acc, (_, &(releases, deployments))| {
+                (acc.0 + releases, acc.1 + deployments, acc.2 + crate::stats::deploy_events(releases, deployments))
+            });
+        let lead_times = self.lead_times.iter()
+            .filter(|((org, _), _)| org == org_name)
+            .flat_map(|(_, hours)| hours.iter().copied())
+            .collect();
+        crate::stats::DeliveryMetrics::new(releases, deployments, events, self.weeks, lead_times)
+    }
+}
+
+type CiRunRow = (String, String, Option<String>, i64, Option<f64>);
+/// `(conclusion, run_attempt, duration_secs)` of a workflow run
+type RunOutcome = (Option<String>, i64, Option<f64>);
+
+/// Actions health for every repo in a scrape, from runs created within the scrape window
+async fn get_ci_health(pool_con: &mut DbConn, scrape_id: i64) -> Result<HashMap<(String, String), crate::stats::CiHealth>> {
+    let run_rows: Vec<CiRunRow> = query_as("
+        SELECT
+            o.name,
+            r.name,
+            wr.conclusion,
+            wr.run_attempt,
+            (julianday(wr.updated_at) - julianday(wr.run_started_at)) * 86400.0 as duration_secs
+        FROM workflow_runs wr
+        JOIN repo_scrapes rs ON rs.repo_id = wr.repo_id
+        JOIN scrapes s ON rs.scrape_id = s.id
+        JOIN repos r ON wr.repo_id = r.id
+        JOIN orgs o ON r.org_id = o.id
+        WHERE rs.scrape_id = $1
+          AND wr.created_at >= s.start_dt AND wr.created_at <= s.end_dt;
+    ").bind(scrape_id).fetch_all(&mut *pool_con).await?;
+
+    let mut runs_by_repo: HashMap<(String, String), Vec<RunOutcome>> = HashMap::new();
+    for (org_name, repo_name, conclusion, run_attempt, duration_secs) in run_rows {
+        runs_by_repo.entry((org_name, repo_name)).or_default().push((conclusion, run_attempt, duration_secs));
+    }
+
+    Ok(runs_by_repo.into_iter()
+        .map(|(key, runs)| {
+            let health = crate::stats::CiHealth::from_runs(
+                runs.iter().map(|(conclusion, run_attempt, duration_secs)| (conclusion.as_deref(), *run_attempt, *duration_secs))
+            );
+            (key, health)
+        })
+        .collect())
+}
+
+/// Conventional Commit mix of the commits in a scrape window, rolled up per repo, org and author
+struct ChangeMixData {
+    by_repo: HashMap<(String, String), crate::stats::ChangeMix>,
+    by_org: HashMap<String, crate::stats::ChangeMix>,
+    by_contributor: HashMap<String, crate::stats::ChangeMix>,
+}
+
+impl ChangeMixData {
+    async fn load(pool_con: &mut DbConn, scrape_id: i64) -> Result<ChangeMixData> {
+        let mix_rows: Vec<(String, String, String, Option<String>, i64)> = query_as("
+            SELECT o.name, r.name, c.username, cm.change_type, COUNT(*) as commits
+            FROM commits cm
+            JOIN repo_scrapes rs ON rs.repo_id = cm.repo_id
+            JOIN scrapes s ON rs.scrape_id = s.id
+            JOIN repos r ON cm.repo_id = r.id
+            JOIN orgs o ON r.org_id = o.id
+            JOIN contributors c ON cm.author_id = c.id
+            WHERE rs.scrape_id = $1
+              AND cm.committed_at >= s.start_dt AND cm.committed_at <= s.end_dt
+            GROUP BY o.name, r.name, c.username, cm.change_type;
+        ").bind(scrape_id).fetch_all(&mut *pool_con).await?;
+
+        let mut data = ChangeMixData {
+            by_repo: HashMap::new(),
+            by_org: HashMap::new(),
+            by_contributor: HashMap::new(),
+        };
+        for (org_name, repo_name, username, change_type, commits) in mix_rows {
+            let change_type = change_type.as_deref();
+            data.by_org.entry(org_name.clone()).or_default().add(change_type, commits);
+            data.by_repo.entry((org_name, repo_name)).or_default().add(change_type, commits);
+            data.by_contributor.entry(username).or_default().add(change_type, commits);
+        }
+
+        Ok(data)
+    }
+}
+
+pub async fn get_org_stats(pool_con: &mut DbConn, scrape_id: i64) -> Result<Vec<crate::stats::OrgStats>> {
+    let org_stats_rows: Vec<(String, i64, i64, i64, i64, i64)> = query_as("
+        SELECT 
+            o.name,
+            totals.commits,
+            totals.lines,
+            totals.repo_count,
+            (SELECT COUNT(DISTINCT cs.contributor_id)
+             FROM contributor_scrapes cs
+             JOIN repo_scrapes rs ON rs.id = cs.repo_scrape_id
+             WHERE rs.scrape_id = $1 AND rs.org_id = o.id) as contributor_count,
+            totals.excluded_lines
+        FROM orgs o
+        -- Summed before joining contributors, so each repo is counted once
+        JOIN (
+            SELECT org_id, SUM(commits) as commits, SUM(lines) as lines,
+                COUNT(DISTINCT repo_id) as repo_count, SUM(excluded_lines) as excluded_lines
+            FROM repo_scrapes
+            WHERE scrape_id = $1
+            GROUP BY org_id
+        ) totals ON totals.org_id = o.id
+        ORDER BY totals.commits DESC;
+    ").bind(scrape_id).fetch_all(&mut *pool_con).await?;
+
+    let delivery = DeliveryData::load(pool_con, scrape_id).await?;
+    let change_mix = ChangeMixData::load(pool_con, scrape_id).await?;
+
+    let mut org_stats = Vec::new();
+    for row in org_stats_rows {
+        org_stats.push(crate::stats::OrgStats {
+            delivery: delivery.org(&row.0),
+            change_mix: change_mix.by_org.get(&row.0).cloned().unwrap_or_default(),
+            name: row.0,
+            total_commits: row.1,
+            total_lines: row.2,
+            repo_count: row.3,
+            contributor_count: row.4,
+            excluded_lines: row.5,
+        });
+    }
+
+    Ok(org_stats)
+}
+
+type RepoStatsRow = (String, String, i64, i64, i64, i64, Option<String>, String, bool, bool, i64);
+
+fn repo_stats_from_row(row: RepoStatsRow, delivery: &DeliveryData, ci: &HashMap<(String, String), crate::stats::CiHealth>, change_mix: &ChangeMixData) -> crate::stats::RepoStats {
+    let key = (row.0.clone(), row.1.clone());
+    crate::stats::RepoStats {
+        delivery: delivery.repo(&row.0, &row.1),
+        ci: ci.get(&key).cloned().unwrap_or_default(),
+        change_mix: change_mix.by_repo.get(&key).cloned().unwrap_or_default(),
+        org_name: row.0,
+        repo_name: row.1,
+        commits: row.2,
+        lines: row.3,
+        prs: row.4,
+        contributor_count: row.5,
+        language: row.6,
+        topics: split_topics(&row.7),
+        fork: row.8,
+        archived: row.9,
+        excluded_lines: row.10,
+    }
+}
+
+pub async fn get_repo_stats(pool_con: &mut DbConn, scrape_id: i64) -> Result<Vec<crate::stats::RepoStats>> {
+    let repo_stats_rows: Vec<RepoStatsRow> = query_as("
+        SELECT 
+            o.name as org_name,
+            r.name as repo_name,
+            rs.commits,
+            rs.lines,
+            rs.prs,
+            COUNT(DISTINCT cs.contributor_id) as contributor_count,
+            r.language,
+            r.topics,
+            r.fork,
+            r.archived,
+            rs.excluded_lines
+        FROM repo_scrapes rs
+        JOIN orgs o ON rs.org_id = o.id
+        JOIN repos r ON rs.repo_id = r.id
+        LEFT JOIN contributor_scrapes cs ON rs.id = cs.repo_scrape_id
+        WHERE rs.scrape_id = $1
+        GROUP BY rs.id, o.name, r.name, rs.commits, rs.lines, rs.prs
+        ORDER BY rs.commits DESC;
+    ").bind(scrape_id).fetch_all(&mut *pool_con).await?;
+
+    let delivery = DeliveryData::load(pool_con, scrape_id).await?;
+    let ci = get_ci_health(pool_con, scrape_id).await?;
+    let change_mix = ChangeMixData::load(pool_con, scrape_id).await?;
+    let repo_stats = repo_stats_rows.into_iter().map(|row| repo_stats_from_row(row, &delivery, &ci, &change_mix)).collect();
+
+    Ok(repo_stats)
+}
+
+pub async fn get_contributor_stats(pool_con: &mut DbConn, scrape_id: i64) -> Result<Vec<crate::stats::ContributorStats>> {
+    let contributor_stats_rows: Vec<(String, i64, i64, i64, i64)> = query_as("
+        SELECT 
+            c.username,
+            SUM(cs.commits) as total_commits,
+            SUM(cs.lines) as total_lines,
+            COUNT(DISTINCT rs.repo_id) as repo_count,
+            SUM(cs.excluded_lines) as excluded_lines
+        FROM contributors c
+        JOIN contributor_scrapes cs ON c.id = cs.contributor_id
+        JOIN repo_scrapes rs ON cs.repo_scrape_id = rs.id
+        WHERE rs.scrape_id = $1
+        GROUP BY c.id, c.username
+        ORDER BY total_commits DESC;
+    ").bind(scrape_id).fetch_all(&mut *pool_con).await?;
+
+    let change_mix = ChangeMixData::load(pool_con, scrape_id).await?;
+
+    // Every (contributor, org) pair in the scrape, so each contributor's orgs come from one query
+    let org_rows: Vec<(String, String)> = query_as("
+        SELECT DISTINCT c.username, o.name
+        FROM contributors c
+        JOIN contributor_scrapes cs ON c.id = cs.contributor_id
+        JOIN repo_scrapes rs ON cs.repo_scrape_id = rs.id
+        JOIN orgs o ON rs.org_id = o.id
+        WHERE rs.scrape_id = $1
+        ORDER BY o.name;
+    ").bind(scrape_id).fetch_all(&mut *pool_con).await?;
+    let mut orgs_by_contributor: HashMap<String, Vec<String>> = HashMap::new();
+    for (username, org_name) in org_rows {
+        orgs_by_contributor.entry(username).or_default().push(org_name);
+    }
+
+    let mut contributor_stats = Vec::new();
+    for row in contributor_stats_rows {
+        let orgs = orgs_by_contributor.remove(&row.0).unwrap_or_default();
+
+        contributor_stats.push(crate::stats::ContributorStats {
+            change_mix: change_mix.by_contributor.get(&row.0).cloned().unwrap_or_default(),
+            username: row.0,
+            total_commits: row.1,
+            total_lines: row.2,
+            repo_count: row.3,
+            excluded_lines: row.4,
+            orgs,
+        });
+    }
+
+    Ok(contributor_stats)
+}
+
+// Detail view queries for drill-down functionality
+
+pub async fn get_org_detail(pool_con: &mut DbConn, scrape_id: i64, org_name: &str) -> Result<crate::stats::OrgDetail> {
+    // Get all repos for this organization
+    let repo_rows: Vec<RepoStatsRow> = query_as("
+        SELECT 
+            o.name as org_name,
+            r.name as repo_name,
+            rs.commits,
+            rs.lines,
+            rs.prs,
+            COUNT(DISTINCT cs.contributor_id) as contributor_count,
+            r.language,
+            r.topics,
+            r.fork,
+            r.archived,
+            rs.excluded_lines
+        FROM orgs o
+        JOIN repo_scrapes rs ON o.id = rs.org_id
+        JOIN repos r ON rs.repo_id = r.id
+        LEFT JOIN contributor_scrapes cs ON rs.id = cs.repo_scrape_id
+        WHERE rs.scrape_id = $1 AND o.name = $2
+        GROUP BY o.name, r.name, rs.commits, rs.lines, rs.prs
+        ORDER BY rs.commits DESC;
+    ").bind(scrape_id).bind(org_name).fetch_all(&mut *pool_con).await?;
+
+    let delivery = DeliveryData::load(pool_con, scrape_id).await?;
+    let ci = get_ci_health(pool_con, scrape_id).await?;
+    let change_mix = ChangeMixData::load(pool_con, scrape_id).await?;
+    let repos = repo_rows.into_iter().map(|row| repo_stats_from_row(row, &delivery, &ci, &change_mix)).collect();
+
+    Ok(crate::stats::OrgDetail {
+        org_name: org_name.to_string(),
+        repos,
+    })
+}
+
+pub async fn get_repo_detail(pool_con: &mut DbConn, scrape_id: i64, org_name: &str, repo_name: &str) -> Result<crate::stats::RepoDetail> {
+    // Get all contributors for this specific repository
+    let contributor_rows: Vec<(String, i64, i64, i64)> = query_as("
+        SELECT 
+            c.username,
+            cs.commits,
+            cs.lines,
+            (
+                SELECT COUNT(*)
+                FROM pull_requests pr
+                WHERE pr.repo_id = rs.repo_id AND pr.author_id = c.id
+                  AND pr.merged_at >= s.start_dt AND pr.merged_at <= s.end_dt
+            ) as prs
+        FROM contributors c
+        JOIN contributor_scrapes cs ON c.id = cs.contributor_id
+        JOIN repo_scrapes rs ON cs.repo_scrape_id = rs.id
+        JOIN scrapes s ON rs.scrape_id = s.id
+        JOIN repos r ON rs.repo_id = r.id
+        JOIN orgs o ON rs.org_id = o.id
+        WHERE rs.scrape_id = $1 AND o.name = $2 AND r.name = $3
+        ORDER BY cs.commits DESC;
+    ").bind(scrape_id).bind(org_name).bind(repo_name).fetch_all(&mut *pool_con).await?;
+
+    let contributors = contributor_rows.into_iter().map(|row| crate::stats::RepoContributor {
+        username: row.0,
+        commits: row.1,
+        lines: row.2,
+        prs: row.3,
+    }).collect();
+
+    let ownership = get_repo_ownership(pool_con, scrape_id, org_name, repo_name).await?;
+
+    Ok(crate::stats::RepoDetail {
+        org_name: org_name.to_string(),
+        repo_name: repo_name.to_string(),
         contributors,
+        ownership,
     })
 }
 
-pub async fn get_contributor_detail(pool_con: &mut PoolConn, scrape_id: i64, username: &str) -> Result<crate::stats::ContributorDetail> {
+/// Measures how much of a scrape window's churn falls under CODEOWNERS rules, and whether
+/// the listed owners authored or reviewed those changes. Only user owners can be matched
+/// against authors and reviewers; team owners still count towards coverage.
+pub async fn get_repo_ownership(pool_con: &mut DbConn, scrape_id: i64, org_name: &str, repo_name: &str) -> Result<crate::stats::OwnershipCoverage> {
+    let repo_id: Option<(i64,)> = query_as("
+        SELECT r.id
+        FROM repos r
+        JOIN orgs o ON r.org_id = o.id
+        WHERE o.name = $1 AND r.name = $2
+        LIMIT 1;
+    ").bind(org_name).bind(repo_name).fetch_optional(&mut *pool_con).await?;
+
+    let Some((repo_id,)) = repo_id else {
+        return Ok(crate::stats::OwnershipCoverage::default());
+    };
+    let codeowners = Repo::get(pool_con, &repo_id).await?.get_codeowners(pool_con).await?;
+
+    let file_rows: Vec<(String, i64, String)> = query_as("
+        SELECT cf.path, cf.additions + cf.deletions, c.username
+        FROM commit_files cf
+        JOIN commits cm ON cf.commit_id = cm.id
+        JOIN contributors c ON cm.author_id = c.id
+        JOIN scrapes s ON s.id = $1
+        WHERE cm.repo_id = $2 AND NOT cf.excluded
+          AND cm.committed_at >= s.start_dt AND cm.committed_at <= s.end_dt;
+    ").bind(scrape_id).bind(repo_id).fetch_all(&mut *pool_con).await?;
+
+    let pr_file_rows: Vec<(i64, String)> = query_as("
+        SELECT pr.id, prf.path
+        FROM pull_request_files prf
+        JOIN pull_requests pr ON prf.pull_request_id = pr.id
+        JOIN scrapes s ON s.id = $1
+        WHERE pr.repo_id = $2 AND NOT prf.excluded
+          AND pr.merged_at >= s.start_dt AND pr.merged_at <= s.end_dt;
+    ").bind(scrape_id).bind(repo_id).fetch_all(&mut *pool_con).await?;
+
+    let reviewer_rows: Vec<(i64, String)> = query_as("
+        SELECT DISTINCT pr.id, c.username
+        FROM pull_request_reviews prr
+        JOIN pull_requests pr ON prr.pull_request_id = pr.id
+        JOIN contributors c ON prr.reviewer_id = c.id
+        JOIN scrapes s ON s.id = $1
+        WHERE pr.repo_id = $2
+          AND pr.merged_at >= s.start_dt AND pr.merged_at <= s.end_dt;
+    ").bind(scrape_id).bind(repo_id).fetch_all(&mut *pool_con).await?;
+
+    let mut coverage = crate::stats::OwnershipCoverage {
+        has_codeowners: !codeowners.rules.is_empty(),
+        ..Default::default()
+    };
+    let mut owners: std::collections::BTreeMap<String, crate::stats::OwnerCoverage> = std::collections::BTreeMap::new();
+
+    for (path, lines, author) in &file_rows {
+        coverage.total_lines += lines;
+        let Some(path_owners) = codeowners.owners_for(path) else {
+            continue;
+        };
+
+        coverage.owned_lines += lines;
+        let authored = is_user_owner(path_owners, author);
+        if authored {
+            coverage.owner_authored_lines += lines;
+        }
+        for owner in path_owners {
+            let entry = owners.entry(owner.clone()).or_insert_with(|| crate::stats::OwnerCoverage {
+                owner: owner.clone(),
+                ..Default::default()
+            });
+            entry.owned_lines += lines;
+            if is_user_owner(std::slice::from_ref(owner), author) {
+                entry.authored_lines += lines;
+            }
+        }
+    }
+
+    // A PR counts once per owner whose paths it touched
+    let mut pr_owners: std::collections::BTreeMap<i64, std::collections::BTreeSet<String>> = std::collections::BTreeMap::new();
+    for (pr_id, path) in &pr_file_rows {
+        if let Some(path_owners) = codeowners.owners_for(path) {
+            pr_owners.entry(*pr_id).or_default().extend(path_owners.iter().cloned());
+        }
+    }
+
+    for (pr_id, path_owners) in pr_owners {
+        let reviewers: Vec<&String> = reviewer_rows.iter()
+            .filter(|(id, _)| *id == pr_id)
+            .map(|(_, username)| username)
+            .collect();
+        let reviewed_by = |owner: &String| reviewers.iter().any(|r| is_user_owner(std::slice::from_ref(owner), r));
+
+        coverage.owned_prs += 1;
+        if path_owners.iter().any(reviewed_by) {
+            coverage.owner_reviewed_prs += 1;
+        }
+        for owner in &path_owners {
+            let entry = owners.entry(owner.clone()).or_insert_with(|| crate::stats::OwnerCoverage {
+                owner: owner.clone(),
+                ..Default::default()
+            });
+            entry.owned_prs += 1;
+            if reviewed_by(owner) {
+                entry.reviewed_prs += 1;
+            }
+        }
+    }
+
+    coverage.owners = owners.into_values().collect();
+    coverage.owners.sort_by_key(|owner| std::cmp::Reverse(owner.owned_lines));
+
+    Ok(coverage)
+}
+
+pub async fn get_contributor_detail(pool_con: &mut DbConn, scrape_id: i64, username: &str) -> Result<crate::stats::ContributorDetail> {
     // Get all repositories this contributor worked on
-    let contribution_rows: Vec<(String, String, i64, i64)> = query_as("
+    let contribution_rows: Vec<(String, String, i64, i64, i64)> = query_as("
         SELECT 
             o.name as org_name,
             r.name as repo_name,
             cs.commits,
-            cs.lines
+            cs.lines,
+            (
+                SELECT COUNT(*)
+                FROM pull_requests pr
+                WHERE pr.repo_id = rs.repo_id AND pr.author_id = c.id
+                  AND pr.merged_at >= s.start_dt AND pr.merged_at <= s.end_dt
+            ) as prs
         FROM contributors c
         JOIN contributor_scrapes cs ON c.id = cs.contributor_id
         JOIN repo_scrapes rs ON cs.repo_scrape_id = rs.id
+        JOIN scrapes s ON rs.scrape_id = s.id
         JOIN repos r ON rs.repo_id = r.id
         JOIN orgs o ON rs.org_id = o.id
         WHERE rs.scrape_id = $1 AND c.username = $2
         ORDER BY cs.commits DESC;
-    ").bind(scrape_id).bind(username).fetch_all(pool_con.as_mut()).await?;
+    ").bind(scrape_id).bind(username).fetch_all(&mut *pool_con).await?;
 
     let contributions = contribution_rows.into_iter().map(|row| crate::stats::ContributorRepo {
         org_name: row.0,
         repo_name: row.1,
         commits: row.2,
         lines: row.3,
-        prs: 0, // PRs not tracked at contributor level
+        prs: row.4,
     }).collect();
 
     Ok(crate::stats::ContributorDetail {
         username: username.to_string(),
         contributions,
+        timing: None,
+    })
+}
+
+/// Author timestamps of a contributor's commits within a scrape window, across the scrape's repos.
+pub async fn get_contributor_commit_times(pool_con: &mut DbConn, scrape_id: i64, username: &str) -> Result<Vec<DateTime<Utc>>> {
+    let time_rows: Vec<(DateTime<Utc>,)> = query_as("
+        SELECT COALESCE(cm.authored_at, cm.committed_at)
+        FROM commits cm
+        JOIN contributors c ON cm.author_id = c.id
+        JOIN repo_scrapes rs ON rs.repo_id = cm.repo_id
+        JOIN scrapes s ON rs.scrape_id = s.id
+        WHERE rs.scrape_id = $1 AND c.username = $2
+          AND cm.committed_at >= s.start_dt AND cm.committed_at <= s.end_dt;
+    ").bind(scrape_id).bind(username).fetch_all(&mut *pool_con).await?;
+
+    Ok(time_rows.into_iter().map(|row| row.0).collect())
+}
+
+type TeamMemberRow = (String, String, String, String, i64, i64, i64, i64);
+
+/// Per-member totals for every GitHub team in an org covered by the scrape, as
+/// `(org, team_slug, team_name, username, commits, lines, prs, reviews)`. Only
+/// activity in the team's own org counts towards it.
+async fn team_member_rows(pool_con: &mut DbConn, scrape_id: i64) -> Result<Vec<TeamMemberRow>> {
+    let rows: Vec<TeamMemberRow> = query_as("
+        SELECT
+            o.name as org_name,
+            t.slug,
+            t.name,
+            c.username,
+            COALESCE(cs.commits, 0) as commits,
+            COALESCE(cs.lines, 0) as lines,
+            -- PRs and reviews cover the same repos as commits: those the scrape recorded
+            (
+                SELECT COUNT(*)
+                FROM pull_requests pr
+                JOIN repos r ON pr.repo_id = r.id
+                WHERE r.org_id = t.org_id AND pr.author_id = c.id
+                  AND pr.repo_id IN (SELECT repo_id FROM repo_scrapes WHERE scrape_id = $1)
+                  AND pr.merged_at >= s.start_dt AND pr.merged_at <= s.end_dt
+            ) as prs,
+            (
+                SELECT COUNT(*)
+                FROM pull_request_reviews prr
+                JOIN pull_requests pr ON prr.pull_request_id = pr.id
+                JOIN repos r ON pr.repo_id = r.id
+                WHERE r.org_id = t.org_id AND prr.reviewer_id = c.id
+                  AND pr.repo_id IN (SELECT repo_id FROM repo_scrapes WHERE scrape_id = $1)
+                  AND prr.submitted_at >= s.start_dt AND prr.submitted_at <= s.end_dt
+            ) as reviews
+        FROM teams t
+        JOIN orgs o ON t.org_id = o.id
+        JOIN team_members tm ON tm.team_id = t.id
+        JOIN contributors c ON tm.contributor_id = c.id
+        JOIN scrapes s ON s.id = $1
+        LEFT JOIN (
+            SELECT rs.org_id, cs.contributor_id, SUM(cs.commits) as commits, SUM(cs.lines) as lines
+            FROM contributor_scrapes cs
+            JOIN repo_scrapes rs ON cs.repo_scrape_id = rs.id
+            WHERE rs.scrape_id = $1
+            GROUP BY rs.org_id, cs.contributor_id
+        ) cs ON cs.org_id = t.org_id AND cs.contributor_id = c.id
+        WHERE t.org_id IN (SELECT org_id FROM repo_scrapes WHERE scrape_id = $1)
+        ORDER BY o.name, t.name, t.slug, c.username;
+    ").bind(scrape_id).fetch_all(&mut *pool_con).await?;
+
+    Ok(rows)
+}
+
+pub async fn get_team_stats(pool_con: &mut DbConn, scrape_id: i64) -> Result<Vec<crate::stats::TeamStats>> {
+    let mut team_stats: Vec<crate::stats::TeamStats> = Vec::new();
+
+    for row in team_member_rows(pool_con, scrape_id).await? {
+        let team = match team_stats.last_mut() {
+            Some(team) if team.org_name == row.0 && team.team_slug == row.1 => team,
+            _ => {
+                team_stats.push(crate::stats::TeamStats {
+                    org_name: row.0,
+                    team_slug: row.1,
+                    team_name: row.2,
+                    ..Default::default()
+                });
+                team_stats.last_mut().unwrap()
+            }
+        };
+        team.member_count += 1;
+        team.commits += row.4;
+        team.lines += row.5;
+        team.prs += row.6;
+        team.reviews += row.7;
+    }
+
+    Ok(team_stats)
+}
+
+pub async fn get_team_detail(pool_con: &mut DbConn, scrape_id: i64, org_name: &str, team_slug: &str) -> Result<crate::stats::TeamDetail> {
+    let mut detail = crate::stats::TeamDetail {
+        org_name: org_name.to_string(),
+        team_slug: team_slug.to_string(),
+        ..Default::default()
+    };
+
+    for row in team_member_rows(pool_con, scrape_id).await? {
+        if row.0 != org_name || row.1 != team_slug {
+            continue;
+        }
+        detail.team_name = row.2;
+        detail.members.push(crate::stats::TeamMember {
+            username: row.3,
+            commits: row.4,
+            lines: row.5,
+            prs: row.6,
+            reviews: row.7,
+        });
+    }
+
+    Ok(detail)
+}
+
+/// Rolls a scrape up by config-defined groups. Teams sum their members' activity across
+/// every scraped repo; repo groups sum the activity in their repos.
+pub async fn get_group_stats(pool_con: &mut DbConn, scrape_id: i64) -> Result<Vec<crate::stats::GroupStats>> {
+    let team_rows: Vec<(String, i64, i64, i64, i64)> = query_as("
+        SELECT
+            g.name,
+            COUNT(gm.contributor_id) as size,
+            COALESCE(SUM(cs.commits), 0) as commits,
+            COALESCE(SUM(cs.lines), 0) as lines,
+            COALESCE(SUM((
+                SELECT COUNT(*)
+                FROM pull_requests pr
+                JOIN scrapes s ON s.id = $1
+                WHERE pr.author_id = gm.contributor_id
+                  AND pr.repo_id IN (SELECT repo_id FROM repo_scrapes WHERE scrape_id = $1)
+                  AND pr.merged_at >= s.start_dt AND pr.merged_at <= s.end_dt
+            )), 0) as prs
+        FROM config_groups g
+        LEFT JOIN config_group_members gm ON gm.group_id = g.id
+        LEFT JOIN (
+            SELECT cs.contributor_id, SUM(cs.commits) as commits, SUM(cs.lines) as lines
+            FROM contributor_scrapes cs
+            JOIN repo_scrapes rs ON cs.repo_scrape_id = rs.id
+            WHERE rs.scrape_id = $1
+            GROUP BY cs.contributor_id
+        ) cs ON cs.contributor_id = gm.contributor_id
+        WHERE g.kind = $2
+        GROUP BY g.id, g.name
+        ORDER BY commits DESC;
+    ").bind(scrape_id).bind(GROUP_KIND_TEAM).fetch_all(&mut *pool_con).await?;
+
+    let repo_rows: Vec<(String, i64, i64, i64, i64)> = query_as("
+        SELECT
+            g.name,
+            COUNT(gr.repo_id) as size,
+            COALESCE(SUM(rs.commits), 0) as commits,
+            COALESCE(SUM(rs.lines), 0) as lines,
+            COALESCE(SUM(rs.prs), 0) as prs
+        FROM config_groups g
+        LEFT JOIN config_group_repos gr ON gr.group_id = g.id
+        LEFT JOIN repo_scrapes rs ON rs.repo_id = gr.repo_id AND rs.scrape_id = $1
+        WHERE g.kind = $2
+        GROUP BY g.id, g.name
+        ORDER BY commits DESC;
+    ").bind(scrape_id).bind(GROUP_KIND_REPOS).fetch_all(&mut *pool_con).await?;
+
+    let group_stats = team_rows.into_iter()
+        .map(|row| (GROUP_KIND_TEAM, row))
+        .chain(repo_rows.into_iter().map(|row| (GROUP_KIND_REPOS, row)))
+        .map(|(kind, row)| crate::stats::GroupStats {
+            kind: kind.to_string(),
+            name: row.0,
+            size: row.1,
+            commits: row.2,
+            lines: row.3,
+            prs: row.4,
+        })
+        .collect();
+
+    Ok(group_stats)
+}
+
+pub async fn get_repo_hotspots(pool_con: &mut DbConn, scrape_id: i64, org_name: &str, repo_name: &str) -> Result<crate::stats::RepoHotspots> {
+    let mut paths = Vec::new();
+
+    // Directories first, then individual files, each limited to the hottest entries
+    for (is_directory, column) in [(true, "cf.directory"), (false, "cf.path")] {
+        let churn_rows: Vec<(String, i64, i64, i64)> = query_as(&format!("
+            SELECT
+                {column} as churn_path,
+                SUM(cf.additions) as additions,
+                SUM(cf.deletions) as deletions,
+                COUNT(DISTINCT cf.commit_id) as commits
+            FROM commit_files cf
+            JOIN commits cm ON cf.commit_id = cm.id
+            JOIN repos r ON cm.repo_id = r.id
+            JOIN orgs o ON r.org_id = o.id
+            JOIN scrapes s ON s.id = $1
+            WHERE o.name = $2 AND r.name = $3 AND NOT cf.excluded
+              AND cm.committed_at >= s.start_dt AND cm.committed_at <= s.end_dt
+            GROUP BY churn_path
+            ORDER BY SUM(cf.additions + cf.deletions) DESC
+            LIMIT 50;
+        ")).bind(scrape_id).bind(org_name).bind(repo_name).fetch_all(&mut *pool_con).await?;
+
+        let contributor_rows: Vec<(String, String)> = query_as(&format!("
+            SELECT
+                {column} as churn_path,
+                c.username
+            FROM commit_files cf
+            JOIN commits cm ON cf.commit_id = cm.id
+            JOIN contributors c ON cm.author_id = c.id
+            JOIN repos r ON cm.repo_id = r.id
+            JOIN orgs o ON r.org_id = o.id
+            JOIN scrapes s ON s.id = $1
+            WHERE o.name = $2 AND r.name = $3 AND NOT cf.excluded
+              AND cm.committed_at >= s.start_dt AND cm.committed_at <= s.end_dt
+            GROUP BY churn_path, c.id, c.username
+            ORDER BY churn_path, SUM(cf.additions + cf.deletions) DESC;
+        ")).bind(scrape_id).bind(org_name).bind(repo_name).fetch_all(&mut *pool_con).await?;
+
+        for row in churn_rows {
+            let top_contributors = contributor_rows.iter()
+                .filter(|(path, _)| *path == row.0)
+                .take(3)
+                .map(|(_, username)| username.clone())
+                .collect();
+
+            paths.push(crate::stats::PathChurn {
+                path: row.0,
+                is_directory,
+                additions: row.1,
+                deletions: row.2,
+                commits: row.3,
+                top_contributors,
+            });
+        }
+    }
+
+    Ok(crate::stats::RepoHotspots {
+        org_name: org_name.to_string(),
+        repo_name: repo_name.to_string(),
+        paths,
     })
 }
+
+/// Past activity of every scraped repo, for estimating what a scrape will fetch. A repo's
+/// commits and merged PRs are averaged over every scrape of its org, since repos without
+/// commits in a window aren't recorded in it.
+pub async fn get_repo_activity(pool_con: &mut DbConn) -> Result<Vec<crate::plan::RepoActivity>> {
+    let rows: Vec<(String, String, i64, i64, f64)> = query_as("
+        WITH org_days AS (
+            SELECT so.org_id, SUM(julianday(s.end_dt) - julianday(s.start_dt)) as days
+            FROM (SELECT DISTINCT scrape_id, org_id FROM repo_scrapes) so
+            JOIN scrapes s ON so.scrape_id = s.id
+            GROUP BY so.org_id
+        )
+        SELECT o.name, r.name, SUM(rs.commits), SUM(rs.prs), od.days
+        FROM repo_scrapes rs
+        JOIN orgs o ON rs.org_id = o.id
+        JOIN repos r ON rs.repo_id = r.id
+        JOIN org_days od ON rs.org_id = od.org_id
+        GROUP BY rs.repo_id, o.name, r.name, od.days;
+    ").fetch_all(&mut *pool_con).await?;
+
+    Ok(rows.into_iter()
+        .filter(|row| row.4 > 0.0)
+        .map(|row| crate::plan::RepoActivity {
+            org_name: row.0,
+            repo_name: row.1,
+            commits_per_day: row.2 as f64 / row.4,
+            prs_per_day: row.3 as f64 / row.4,
+        })
+        .collect())
+}
@@ -0,0 +1,538 @@
+//! Synthetic demo data: orgs, teams, repos, contributors and weekly scrapes made up from a seed,
+//! so the TUI and every stats query can be tried without a token or real company data.
+//!
+//! Activity follows a power law across both repos and contributors, so a few of each do most of
+//! the work. Some contributors join or leave partway through, and bots bump lockfiles. Raw commits,
+//! PRs, reviews, releases, deployments and workflow runs are stored like a scrape stores them,
+//! then each week's counters are derived from them the same way.
+
+use std::fmt;
+
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Datelike, Duration, Utc};
+use globset::GlobSet;
+use rand::distributions::{Distribution, WeightedIndex};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use regex::Regex;
+use sqlx::{Connection, SqliteConnection, SqlitePool};
+
+use crate::codeowners::CodeOwners;
+use crate::config::AppConfig;
+use crate::conventional::change_type;
+use crate::db::{Commit, CommitFile, Contributor, Deployment, Org, PullRequest, PullRequestFile, PullRequestReview, Release, Repo, RepoMetadata, Scrape, ScrapeStatus, Team, WorkflowRun};
+use crate::scraper::{excluded_line_globs, record_repo_scrape};
+
+const ORG_NAMES: [&str; 5] = ["acme", "globex", "initech", "umbrella", "hooli"];
+const REPO_NAMES: [&str; 16] = [
+    "api", "web", "billing", "auth", "search", "mobile", "infra", "docs",
+    "payments", "notifications", "gateway", "analytics", "scheduler", "ledger", "catalog", "sdk",
+];
+const TEAM_NAMES: [(&str, &str); 4] = [("platform", "Platform"), ("product", "Product"), ("data", "Data"), ("mobile", "Mobile")];
+const FIRST_NAMES: [&str; 24] = [
+    "alice", "bob", "carol", "dave", "erin", "frank", "grace", "heidi", "ivan", "judy", "mallory", "niaj",
+    "olivia", "peggy", "rupert", "sybil", "trent", "ursula", "victor", "wendy", "xavier", "yvonne", "zack", "quinn",
+];
+const LAST_NAMES: [&str; 12] = ["chen", "garcia", "kim", "novak", "okafor", "patel", "rossi", "silva", "tanaka", "weber", "young", "ziegler"];
+const BOT_NAMES: [&str; 3] = ["dependabot[bot]", "renovate[bot]", "github-actions[bot]"];
+/// Matches `BOT_NAMES`, so bots show up in raw commits and PRs but not in contributor counters
+const BOT_PATTERN: &str = "\\[bot\\]$";
+/// `(language, extension, lockfile)`
+const LANGUAGES: [(&str, &str, &str); 4] = [
+    ("Rust", "rs", "Cargo.lock"),
+    ("TypeScript", "ts", "package-lock.json"),
+    ("Go", "go", "go.sum"),
+    ("Python", "py", "poetry.lock"),
+];
+const MODULES: [&str; 6] = ["api", "core", "db", "auth", "ui", "jobs"];
+const FILES: [&str; 5] = ["mod", "handlers", "models", "service", "utils"];
+/// Conventional Commit types and how often they're used
+const CHANGE_TYPES: [(&str, u32); 8] = [("feat", 30), ("fix", 25), ("chore", 10), ("refactor", 10), ("docs", 8), ("test", 7), ("perf", 3), ("ci", 3)];
+const SUBJECTS: [&str; 10] = [
+    "handle empty responses", "add pagination to listings", "update error messages", "cache lookups",
+    "simplify retry logic", "tidy up logging", "support dark mode", "validate input", "speed up startup",
+    "rename config option",
+];
+const DEPENDENCIES: [&str; 6] = ["serde", "tokio", "react", "lodash", "requests", "grpc"];
+/// Commits a week in the busiest repo; other repos get a power-law share of it
+const BUSIEST_REPO_COMMITS_PER_WEEK: f64 = 40.0;
+
+/// What to generate. The same options always produce the same data.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DemoOptions {
+    pub seed: u64,
+    pub orgs: usize,
+    pub repos_per_org: usize,
+    pub contributors: usize,
+    /// One scrape per week, the last one ending at `end`
+    pub weeks: usize,
+    pub end: DateTime<Utc>,
+}
+
+impl Default for DemoOptions {
+    fn default() -> Self {
+        Self {
+            seed: 42,
+            orgs: 3,
+            repos_per_org: 8,
+            contributors: 60,
+            weeks: 6,
+            end: Utc::now().date_naive().and_hms_opt(0, 0, 0).expect("midnight is valid").and_utc(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DemoSummary {
+    pub orgs: usize,
+    pub repos: usize,
+    pub contributors: usize,
+    pub commits: usize,
+    pub prs: usize,
+    pub scrapes: usize,
+}
+
+impl fmt::Display for DemoSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Generated {} weekly scrapes of {} orgs, {} repos and {} contributors ({} commits, {} pull requests)",
+            self.scrapes, self.orgs, self.repos, self.contributors, self.commits, self.prs,
+        )
+    }
+}
+
+struct DemoPerson {
+    contributor: Contributor,
+    /// Relative share of the work in the orgs they belong to
+    weight: f64,
+    orgs: Vec<usize>,
+    /// Active from `joined_week` up to, but not including, `left_week`
+    joined_week: usize,
+    left_week: usize,
+}
+
+impl DemoPerson {
+    fn is_active(&self, org: usize, week: usize) -> bool {
+        self.orgs.contains(&org) && (self.joined_week..self.left_week).contains(&week)
+    }
+}
+
+struct DemoRepo {
+    repo: Repo,
+    org: usize,
+    weight: f64,
+    paths: Vec<String>,
+    lockfile: String,
+    next_pr_number: i64,
+    next_minor_version: i64,
+}
+
+struct DemoGenerator {
+    rng: StdRng,
+    options: DemoOptions,
+    excluded_globs: GlobSet,
+    next_github_id: i64,
+    summary: DemoSummary,
+}
+
+/// Fills an empty database with demo data. Databases that already hold scrapes are left alone,
+/// so real data is never mixed with made-up data.
+pub async fn generate_demo(db_pool: &SqlitePool, options: &DemoOptions) -> Result<DemoSummary> {
+    if options.orgs == 0 || options.repos_per_org == 0 || options.contributors == 0 || options.weeks == 0 {
+        return Err(anyhow!("Demo data needs at least one org, repo, contributor and week"));
+    }
+
+    let mut db_conn = db_pool.acquire().await?;
+    if Scrape::get_latest(&mut db_conn).await?.is_some() {
+        return Err(anyhow!("The database already has scrapes; demo data is only written to a fresh database"));
+    }
+
+    let mut generator = DemoGenerator {
+        rng: StdRng::seed_from_u64(options.seed),
+        options: options.clone(),
+        excluded_globs: excluded_line_globs(&AppConfig::default())?,
+        next_github_id: 1,
+        summary: DemoSummary::default(),
+    };
+
+    // Thousands of small inserts are far quicker in one transaction, and a failure leaves nothing behind
+    let mut tx = db_conn.begin().await?;
+    generator.fill(&mut tx).await?;
+    tx.commit().await?;
+    Ok(generator.summary)
+}
+
+impl DemoGenerator {
+    async fn fill(&mut self, db_conn: &mut SqliteConnection) -> Result<()> {
+        let orgs = self.create_orgs(db_conn).await?;
+        let people = self.create_people(db_conn).await?;
+        let mut bots = Vec::new();
+        for name in BOT_NAMES {
+            bots.push(Contributor::create(db_conn, name.to_string()).await?);
+        }
+        self.create_teams(db_conn, &orgs, &people).await?;
+        let mut repos = self.create_repos(db_conn, &orgs, &people).await?;
+
+        for week in 0..self.options.weeks {
+            for repo in repos.iter_mut() {
+                self.create_week(db_conn, repo, &people, &bots, week).await?;
+            }
+        }
+
+        // Counters are derived from the stored activity, exactly as a scrape or webhook rebuild does
+        let bot_regex = Regex::new(BOT_PATTERN)?;
+        for week in 0..self.options.weeks {
+            let start_dt = self.week_start(week);
+            let scrape = Scrape::create(db_conn, start_dt, start_dt + Duration::days(7), None, ScrapeStatus::Complete).await?;
+            for repo in Repo::list_active(db_conn, scrape.start_dt, scrape.end_dt).await? {
+                record_repo_scrape(db_conn, &scrape, &repo, &bot_regex).await?;
+            }
+            self.summary.scrapes += 1;
+        }
+        Ok(())
+    }
+
+    fn week_start(&self, week: usize) -> DateTime<Utc> {
+        self.options.end - Duration::days(7 * (self.options.weeks - week) as i64)
+    }
+
+    fn github_id(&mut self) -> i64 {
+        self.next_github_id += 1;
+        self.next_github_id
+    }
+
+    fn sha(&mut self) -> String {
+        hex::encode(self.rng.r#gen::<[u8; 20]>())
+    }
+
+    /// A moment in the week, mostly during working hours on weekdays
+    fn time_in_week(&mut self, week: usize) -> DateTime<Utc> {
+        let day = if self.rng.gen_bool(0.9) { self.rng.gen_range(0..5) } else { self.rng.gen_range(5..7) };
+        let hour = if self.rng.gen_bool(0.85) { self.rng.gen_range(9..18) } else { self.rng.gen_range(0..24) };
+        // Weeks start on whatever weekday `end` falls on, so count days from the first Monday
+        let week_start = self.week_start(week);
+        let monday_offset = (7 - week_start.weekday().num_days_from_monday() as i64) % 7;
+        let day = (day + monday_offset) % 7;
+        week_start + Duration::days(day) + Duration::hours(hour) + Duration::seconds(self.rng.gen_range(1..3600))
+    }
+
+    /// Lines changed, heavy tailed so most changes are small and a few are huge
+    fn change_size(&mut self) -> (i64, i64) {
+        let additions = (self.rng.r#gen::<f64>().powi(3) * 300.0) as i64 + 1;
+        let deletions = (additions as f64 * self.rng.gen_range(0.0..0.8)) as i64;
+        (additions, deletions)
+    }
+
+    fn message(&mut self, scope: &str) -> String {
+        let subject = *SUBJECTS.choose(&mut self.rng).expect("subjects aren't empty");
+        if self.rng.gen_bool(0.15) {
+            let mut chars = subject.chars();
+            return chars.next().map(|c| c.to_uppercase().chain(chars).collect()).unwrap_or_default();
+        }
+        let types = WeightedIndex::new(CHANGE_TYPES.iter().map(|(_, weight)| weight)).expect("weights are positive");
+        format!("{}({scope}): {subject}", CHANGE_TYPES[types.sample(&mut self.rng)].0)
+    }
+
+    /// Picks an active member of the org, weighted by their share of the work
+    fn pick_person<'a>(&mut self, people: &'a [DemoPerson], org: usize, week: usize, except: Option<i64>) -> Option<&'a DemoPerson> {
+        let candidates: Vec<&DemoPerson> = people.iter()
+            .filter(|p| p.is_active(org, week) && Some(p.contributor.id) != except)
+            .collect();
+        let weights = WeightedIndex::new(candidates.iter().map(|p| p.weight)).ok()?;
+        Some(candidates[weights.sample(&mut self.rng)])
+    }
+
+    async fn create_orgs(&mut self, db_conn: &mut SqliteConnection) -> Result<Vec<Org>> {
+        let mut orgs = Vec::new();
+        for index in 0..self.options.orgs {
+            orgs.push(Org::create(db_conn, numbered_name(&ORG_NAMES, index)).await?);
+        }
+        self.summary.orgs = orgs.len();
+        Ok(orgs)
+    }
+
+    async fn create_people(&mut self, db_conn: &mut SqliteConnection) -> Result<Vec<DemoPerson>> {
+        let weeks = self.options.weeks;
+        let mut people = Vec::new();
+        for index in 0..self.options.contributors {
+            let first = FIRST_NAMES[index % FIRST_NAMES.len()];
+            let last = LAST_NAMES[(index / FIRST_NAMES.len() + index) % LAST_NAMES.len()];
+            let username = match index / (FIRST_NAMES.len() * LAST_NAMES.len()) {
+                0 => format!("{first}-{last}"),
+                round => format!("{first}-{last}{}", round + 1),
+            };
+
+            let mut orgs = vec![self.rng.gen_range(0..self.options.orgs)];
+            if self.options.orgs > 1 && self.rng.gen_bool(0.15) {
+                let other = (orgs[0] + self.rng.gen_range(1..self.options.orgs)) % self.options.orgs;
+                orgs.push(other);
+            }
+            // Some people join partway through and some leave, so the contributor list churns
+            let joined_week = if weeks > 1 && self.rng.gen_bool(0.15) { self.rng.gen_range(1..weeks) } else { 0 };
+            let left_week = if weeks > joined_week + 1 && self.rng.gen_bool(0.15) { self.rng.gen_range(joined_week + 1..weeks) } else { weeks };
+
+            people.push(DemoPerson {
+                contributor: Contributor::create(db_conn, username).await?,
+                weight: 1.0 / ((index + 1) as f64).powf(1.1),
+                orgs,
+                joined_week,
+                left_week,
+            });
+        }
+        self.summary.contributors = people.len();
+        Ok(people)
+    }
+
+    async fn create_teams(&mut self, db_conn: &mut SqliteConnection, orgs: &[Org], people: &[DemoPerson]) -> Result<()> {
+        for (org_index, org) in orgs.iter().enumerate() {
+            let members: Vec<&DemoPerson> = people.iter().filter(|p| p.orgs.contains(&org_index)).collect();
+            let team_count = TEAM_NAMES.len().min(members.len().div_ceil(4)).max(1);
+            for (team_index, (slug, name)) in TEAM_NAMES.iter().take(team_count).enumerate() {
+                let mut team = Team::create(db_conn, org.clone(), slug.to_string(), name.to_string()).await?;
+                team.members = members.iter()
+                    .enumerate()
+                    .filter(|(member_index, _)| member_index % team_count == team_index)
+                    .map(|(_, person)| person.contributor.clone())
+                    .collect();
+                team.save(db_conn).await?;
+            }
+        }
+        Ok(())
+    }
+
+    async fn create_repos(&mut self, db_conn: &mut SqliteConnection, orgs: &[Org], people: &[DemoPerson]) -> Result<Vec<DemoRepo>> {
+        let mut repos = Vec::new();
+        for (org_index, org) in orgs.iter().enumerate() {
+            let members: Vec<&DemoPerson> = people.iter().filter(|p| p.orgs.contains(&org_index)).collect();
+            let team_count = TEAM_NAMES.len().min(members.len().div_ceil(4)).max(1);
+            for index in 0..self.options.repos_per_org {
+                let (language, extension, lockfile) = *LANGUAGES.choose(&mut self.rng).expect("languages aren't empty");
+                let private = self.rng.gen_bool(0.7);
+                let mut repo = Repo::create(db_conn, numbered_name(&REPO_NAMES, index), org.clone()).await?;
+                repo.metadata = RepoMetadata {
+                    language: Some(language.to_string()),
+                    topics: vec![language.to_lowercase(), "demo".to_string()],
+                    private,
+                    visibility: Some(if private { "private" } else { "public" }.to_string()),
+                    default_branch: Some("main".to_string()),
+                    size: self.rng.gen_range(100..50_000),
+                    created_at: Some(self.options.end - Duration::days(self.rng.gen_range(200..2000))),
+                    pushed_at: Some(self.options.end - Duration::hours(self.rng.gen_range(1..48))),
+                    ..Default::default()
+                };
+                repo.save(db_conn).await?;
+
+                // The repo's team owns everything, and one person owns the docs
+                let team_slug = TEAM_NAMES[index % team_count].0;
+                let mut codeowners = format!("* @{}/{team_slug}\n", org.name);
+                if let Some(docs_owner) = members.choose(&mut self.rng) {
+                    codeowners.push_str(&format!("/docs/ @{}\n", docs_owner.contributor.username));
+                }
+                repo.save_codeowners(db_conn, &CodeOwners::parse(&codeowners)).await?;
+
+                let mut modules = MODULES.to_vec();
+                modules.shuffle(&mut self.rng);
+                let mut paths: Vec<String> = modules.iter()
+                    .take(3)
+                    .flat_map(|module| FILES.iter().map(move |file| format!("src/{module}/{file}.{extension}")))
+                    .collect();
+                // People touch the lockfile when they add dependencies, so their line totals have exclusions too
+                paths.extend(["docs/guide.md".to_string(), "README.md".to_string(), format!("tests/integration.{extension}"), lockfile.to_string()]);
+
+                repos.push(DemoRepo {
+                    repo,
+                    org: org_index,
+                    weight: 1.0 / ((index + 1) as f64).powf(1.3),
+                    paths,
+                    lockfile: lockfile.to_string(),
+                    next_pr_number: 1,
+                    next_minor_version: 0,
+                });
+            }
+        }
+        self.summary.repos = repos.len();
+        Ok(repos)
+    }
+
+    /// Stores one week of commits, PRs, reviews, releases, deployments and workflow runs for a repo.
+    async fn create_week(&mut self, db_conn: &mut SqliteConnection, repo: &mut DemoRepo, people: &[DemoPerson], bots: &[Contributor], week: usize) -> Result<()> {
+        let activity = repo.weight * self.rng.gen_range(0.6..1.4);
+        let commits = (BUSIEST_REPO_COMMITS_PER_WEEK * activity).round() as usize;
+        let mut shas = Vec::new();
+
+        for _ in 0..commits {
+            let Some(author) = self.pick_person(people, repo.org, week, None) else {
+                break;
+            };
+            let path_count = self.rng.gen_range(1..=3);
+            let paths: Vec<String> = repo.paths.choose_multiple(&mut self.rng, path_count).cloned().collect();
+            let scope = paths[0].split('/').nth(1).filter(|_| paths[0].starts_with("src/")).unwrap_or("repo").to_string();
+            let message = self.message(&scope);
+            let time = self.time_in_week(week);
+            shas.push(self.create_commit(db_conn, repo, &author.contributor, time, message, &paths).await?);
+        }
+
+        // Bots bump dependencies, which only touches the lockfile
+        if !shas.is_empty() {
+            let lockfile = [repo.lockfile.clone()];
+            for _ in 0..self.rng.gen_range(0..=2) {
+                let bot = bots.choose(&mut self.rng).expect("bots aren't empty").clone();
+                let dependency = *DEPENDENCIES.choose(&mut self.rng).expect("dependencies aren't empty");
+                let message = format!("chore(deps): bump {dependency} from 1.{week}.0 to 1.{}.0", week + 1);
+                let time = self.time_in_week(week);
+                let sha = self.create_commit(db_conn, repo, &bot, time, message.clone(), &lockfile).await?;
+                shas.push(sha);
+                self.create_pull_request(db_conn, repo, people, &bot, week, message, &lockfile).await?;
+            }
+        }
+
+        for _ in 0..commits.div_ceil(3) {
+            let Some(author) = self.pick_person(people, repo.org, week, None) else {
+                break;
+            };
+            let path_count = self.rng.gen_range(1..=4);
+            let paths: Vec<String> = repo.paths.choose_multiple(&mut self.rng, path_count).cloned().collect();
+            let title = self.message("repo");
+            let author = author.contributor.clone();
+            self.create_pull_request(db_conn, repo, people, &author, week, title, &paths).await?;
+        }
+
+        if shas.is_empty() {
+            return Ok(());
+        }
+
+        if self.rng.gen_bool((repo.weight * 1.5).min(1.0)) {
+            repo.next_minor_version += 1;
+            let tag_name = format!("v1.{}.0", repo.next_minor_version);
+            let published_at = self.time_in_week(week);
+            Release::create(db_conn, repo.repo.id, tag_name.clone(), tag_name, "main".to_string(), false, false, Some(published_at), Some(published_at)).await?;
+        }
+
+        // Only the busier repos deploy continuously; the rest ship through releases
+        if repo.weight > 0.3 {
+            for _ in 0..(activity * 5.0).round() as usize {
+                let github_id = self.github_id();
+                let sha = shas.choose(&mut self.rng).expect("shas aren't empty").clone();
+                let created_at = self.time_in_week(week);
+                Deployment::create(db_conn, repo.repo.id, github_id, "production".to_string(), sha, "main".to_string(), Some(created_at)).await?;
+            }
+        }
+
+        for sha in &shas {
+            let github_id = self.github_id();
+            let (status, conclusion) = match self.rng.gen_range(0..100) {
+                0..=84 => ("completed", Some("success")),
+                85..=94 => ("completed", Some("failure")),
+                95..=97 => ("completed", Some("cancelled")),
+                _ => ("in_progress", None),
+            };
+            let run_attempt = if self.rng.gen_bool(0.05) { 2 } else { 1 };
+            let started_at = self.time_in_week(week);
+            let finished_at = started_at + Duration::seconds(self.rng.gen_range(120..900));
+            WorkflowRun::create(
+                db_conn,
+                repo.repo.id,
+                github_id,
+                1,
+                "CI".to_string(),
+                "main".to_string(),
+                sha.clone(),
+                "push".to_string(),
+                status.to_string(),
+                conclusion.map(|c| c.to_string()),
+                run_attempt,
+                Some(started_at),
+                Some(started_at),
+                Some(finished_at),
+            ).await?;
+        }
+        Ok(())
+    }
+
+    async fn create_commit(&mut self, db_conn: &mut SqliteConnection, repo: &DemoRepo, author: &Contributor, time: DateTime<Utc>, message: String, paths: &[String]) -> Result<String> {
+        let sha = self.sha();
+        let sizes: Vec<(i64, i64)> = paths.iter().map(|_| self.change_size()).collect();
+        let commit_type = change_type(&message);
+        let commit = Commit::create(
+            db_conn,
+            repo.repo.clone(),
+            sha.clone(),
+            author.clone(),
+            Some(author.clone()),
+            Some(time),
+            Some(time),
+            sizes.iter().map(|(additions, _)| additions).sum(),
+            sizes.iter().map(|(_, deletions)| deletions).sum(),
+            message,
+            commit_type,
+        ).await?;
+        for (path, (additions, deletions)) in paths.iter().zip(sizes) {
+            let directory = path.rsplit_once('/').map(|(directory, _)| directory.to_string()).unwrap_or_default();
+            CommitFile::create(db_conn, commit.id, path.clone(), directory, additions, deletions, self.excluded_globs.is_match(path)).await?;
+        }
+        self.summary.commits += 1;
+        Ok(sha)
+    }
+
+    /// Stores a PR, merged within the week unless it's one of the few still open, with one or
+    /// two reviews from other members of the org.
+    #[allow(clippy::too_many_arguments)]
+    async fn create_pull_request(&mut self, db_conn: &mut SqliteConnection, repo: &mut DemoRepo, people: &[DemoPerson], author: &Contributor, week: usize, title: String, paths: &[String]) -> Result<()> {
+        let number = repo.next_pr_number;
+        repo.next_pr_number += 1;
+        let merged_at = self.time_in_week(week);
+        let created_at = merged_at - Duration::hours(self.rng.gen_range(2..96));
+        let merged = self.rng.gen_bool(0.9);
+        let sizes: Vec<(i64, i64)> = paths.iter().map(|_| self.change_size()).collect();
+        let pr_type = change_type(&title);
+
+        let pr = PullRequest::create(
+            db_conn,
+            repo.repo.clone(),
+            number,
+            title,
+            author.clone(),
+            if merged { "closed" } else { "open" }.to_string(),
+            Some(created_at),
+            Some(merged_at),
+            merged.then_some(merged_at),
+            merged.then_some(merged_at),
+            sizes.iter().map(|(additions, _)| additions).sum(),
+            sizes.iter().map(|(_, deletions)| deletions).sum(),
+            paths.len() as i64,
+            "main".to_string(),
+            pr_type,
+        ).await?;
+        for (path, (additions, deletions)) in paths.iter().zip(sizes) {
+            PullRequestFile::create(db_conn, pr.id, path.clone(), additions, deletions, self.excluded_globs.is_match(path)).await?;
+        }
+
+        let mut reviewed_by = vec![author.id];
+        for _ in 0..self.rng.gen_range(1..=2) {
+            let Some(reviewer) = self.pick_person(people, repo.org, week, Some(author.id)) else {
+                break;
+            };
+            if reviewed_by.contains(&reviewer.contributor.id) {
+                continue;
+            }
+            reviewed_by.push(reviewer.contributor.id);
+            let github_id = self.github_id();
+            let submitted_at = created_at + (merged_at - created_at) / 2;
+            let state = if self.rng.gen_bool(0.8) { "approved" } else { "commented" };
+            PullRequestReview::create(db_conn, pr.id, github_id, reviewer.contributor.clone(), state.to_string(), Some(submitted_at)).await?;
+        }
+
+        self.summary.prs += 1;
+        Ok(())
+    }
+}
+
+/// The `index`th name from a list, numbered once the list runs out, e.g. `acme`, then `acme-2`
+fn numbered_name(names: &[&str], index: usize) -> String {
+    match index / names.len() {
+        0 => names[index].to_string(),
+        round => format!("{}-{}", names[index % names.len()], round + 1),
+    }
+}
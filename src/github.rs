@@ -1,8 +1,80 @@
 use chrono::{DateTime, Utc};
-use octocrab::{models::{orgs::MembershipInvitation, pulls::PullRequest, repos::RepoCommit, Repository}, FromResponse, Octocrab, Page};
-use anyhow::Result;
+use octocrab::{models::{orgs::MembershipInvitation, Rate, pulls::{PullRequest, Review}, repos::{DiffEntry, Release, RepoCommit, Tag}, teams::RequestedTeam, Author, Repository}, FromResponse, Octocrab, Page};
+use serde::Deserialize;
 
-const COMMITS_PER_PAGE: u8 = 200;
+use std::env;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use http::header::USER_AGENT;
+use http::{HeaderValue, Uri};
+use http_body_util::BodyExt;
+use hyper_rustls::HttpsConnectorBuilder;
+use octocrab::service::middleware::{auth_header::AuthHeaderLayer, base_uri::BaseUriLayer, extra_headers::ExtraHeadersLayer, retry::RetryConfig};
+use octocrab::{AuthState, OctocrabBuilder};
+use sqlx::SqlitePool;
+use tower::retry::RetryLayer;
+use tower::{BoxError, ServiceBuilder, ServiceExt};
+use tower_http::follow_redirect::FollowRedirectLayer;
+
+use crate::codeowners::CODEOWNERS_PATHS;
+use crate::config::{AppConfig, HttpCacheMode, RecordingMode};
+use crate::http_cache::HttpCacheLayer;
+use crate::recording::RecordingLayer;
+use anyhow::{anyhow, Result};
+
+/// GitHub's maximum page size for commit and pull request listings
+pub const COMMITS_PER_PAGE: u8 = 100;
+const WORKFLOW_RUNS_PER_PAGE: usize = 100;
+pub const ORG_REPOS_PER_PAGE: u8 = 50;
+const GITHUB_BASE_URI: &str = "https://api.github.com";
+const GITHUB_UPLOAD_URI: &str = "https://uploads.github.com";
+
+/// A deployment as returned by GitHub's REST API, which octocrab has no model for
+#[derive(Deserialize, Debug, Clone)]
+pub struct Deployment {
+    pub id: u64,
+    pub sha: String,
+    #[serde(rename = "ref")]
+    pub git_ref: String,
+    pub environment: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A GitHub Actions workflow run. octocrab's model lacks `run_attempt` and
+/// `run_started_at`, which flaky rerun and duration stats need.
+#[derive(Deserialize, Debug, Clone)]
+pub struct WorkflowRun {
+    pub id: u64,
+    pub workflow_id: u64,
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub head_branch: Option<String>,
+    pub head_sha: String,
+    pub event: String,
+    #[serde(default)]
+    pub status: Option<String>,
+    #[serde(default)]
+    pub conclusion: Option<String>,
+    #[serde(default = "first_attempt")]
+    pub run_attempt: u32,
+    #[serde(default)]
+    pub run_started_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+fn first_attempt() -> u32 {
+    1
+}
+
+/// One page of `GET /repos/{owner}/{repo}/actions/runs`
+#[derive(Deserialize, Debug, Clone)]
+pub struct WorkflowRunPage {
+    pub total_count: u64,
+    pub workflow_runs: Vec<WorkflowRun>,
+}
 
 pub struct Github {
     client: Octocrab
@@ -15,36 +87,107 @@ impl Github {
         }
     }
 
-    pub async fn get_repo_commits(&self, org: &str, repo: &str, since: DateTime<Utc>) -> Result<Page<RepoCommit>> {
-        let repo_details = self.client.repos(org, repo);
-        return match repo_details
+    /// The client scrapes use, authenticated with `GITHUB_TOKEN`, caching responses per `http_cache`
+    /// and recording or replaying them per `api_recording`. Offline and replayed scrapes never reach
+    /// GitHub, so they don't need a token.
+    pub fn from_env(cfg: &AppConfig, pool: &SqlitePool) -> Result<Self> {
+        let token = env::var("GITHUB_TOKEN").ok();
+        if token.is_none() && cfg.http_cache != HttpCacheMode::Offline && cfg.api_recording != RecordingMode::Replay {
+            return Err(anyhow!("GITHUB_TOKEN isn't set"));
+        }
+        Github::with_layers(
+            token.as_deref(),
+            HttpCacheLayer::new(pool.clone(), cfg.http_cache),
+            RecordingLayer::new(cfg.api_recording, &cfg.api_recording_dir),
+        )
+    }
+
+    /// Serves every request from a recording made with `api_recording = "record"`.
+    pub fn replay(dir: impl Into<PathBuf>, pool: &SqlitePool) -> Result<Self> {
+        Github::with_layers(None, HttpCacheLayer::new(pool.clone(), HttpCacheMode::Off), RecordingLayer::new(RecordingMode::Replay, dir))
+    }
+
+    /// Builds a client with recording and the HTTP cache in its HTTP stack, below pagination, so
+    /// they see every request. The stack otherwise matches octocrab's default client: it retries
+    /// server errors and follows redirects.
+    fn with_layers(auth_token: Option<&str>, cache: HttpCacheLayer, recording: RecordingLayer) -> Result<Self> {
+        let connector = HttpsConnectorBuilder::new()
+            .with_native_roots()?
+            .https_or_http()
+            .enable_http1()
+            .build();
+        let client = hyper_util::client::legacy::Client::builder(hyper_util::rt::TokioExecutor::new())
+            .build(connector);
+        let transport = ServiceBuilder::new()
+            .layer(FollowRedirectLayer::new())
+            .layer(RetryLayer::new(RetryConfig::Simple(3)))
+            .service(client)
+            .map_response(|response| response.map(|body| body.map_err(BoxError::from).boxed()))
+            .map_err(BoxError::from);
+
+        let base_uri = Uri::from_static(GITHUB_BASE_URI);
+        let auth_header = auth_token
+            .map(|token| HeaderValue::from_str(&format!("Bearer {token}")))
+            .transpose()?;
+        let client = OctocrabBuilder::new_empty()
+            .with_service(transport)
+            .with_layer(&cache)
+            .with_layer(&recording)
+            .with_layer(&ExtraHeadersLayer::new(Arc::new(vec![(USER_AGENT, HeaderValue::from_static("octocrab"))])))
+            .with_layer(&BaseUriLayer::new(base_uri.clone()))
+            .with_layer(&AuthHeaderLayer::new(auth_header, base_uri, Uri::from_static(GITHUB_UPLOAD_URI)))
+            .with_auth(AuthState::None)
+            .build()
+            .map_err(|e| anyhow!("Failed to build Octocrab: {e}"))?;
+        Ok(Github { client })
+    }
+
+    /// Lists the commits on `main`, or `master` for repos without one, within a window, following
+    /// every page of it. Empty repos and repos with neither branch have no commits to list.
+    pub async fn get_repo_commits(&self, org: &str, repo: &str, since: DateTime<Utc>, until: DateTime<Utc>) -> Result<Vec<RepoCommit>> {
+        let mut first_page = None;
+        for branch in ["main", "master"] {
+            match self.get_repo_commits_page(org, repo, branch, since, until).await {
+                Ok(page) => {
+                    first_page = Some(page);
+                    break;
+                }
+                Err(octocrab::Error::GitHub { source, .. }) if source.status_code.as_str() == "404" => continue,
+                Err(octocrab::Error::GitHub { source, .. }) if source.status_code.as_str() == "409" => return Ok(vec![]),
+                Err(e) => return Err(anyhow!("Failed to list commits for {org}/{repo}: {e}")),
+            }
+        }
+        match first_page {
+            Some(page) => Ok(self.client.all_pages(page).await?),
+            None => Ok(vec![]),
+        }
+    }
+
+    async fn get_repo_commits_page(&self, org: &str, repo: &str, branch: &str, since: DateTime<Utc>, until: DateTime<Utc>) -> octocrab::Result<Page<RepoCommit>> {
+        self.client.repos(org, repo)
             .list_commits()
             .since(since)
-            .branch("main")
+            .until(until)
+            .branch(branch)
             .per_page(COMMITS_PER_PAGE)
             .send()
-            .await {
-                Ok(val) => Ok(val),
-                Err(octocrab::Error::GitHub { source, .. }) if source.status_code.as_str() == "404" => {
-                        // If main doesn't exist
-                        repo_details
-                            .list_commits()
-                            .since(since)
-                            .branch("master")
-                            .per_page(COMMITS_PER_PAGE)
-                            .send()
-                            .await
-                            .map_err(|e| e.into())
-                },
-                Err(e) => {
-                    println!("Unknown error for {:?}: {:?}", repo, &e);
-                    Err(e.into())
-                },
-            };
+            .await
     }
 
-    pub async fn get_repo_prs (&self, org: &str, repo: &str, since: DateTime<Utc>) -> Result<Vec<PullRequest>> {
-        let repo_details = self.client.pulls(org, repo)
+    /// Fetches a single commit, which unlike the list endpoint includes its
+    /// line stats and changed files.
+    pub async fn get_commit(&self, org: &str, repo: &str, sha: &str) -> Result<RepoCommit> {
+        self.client.commits(org, repo)
+            .get(sha)
+            .await
+            .map_err(|e| e.into())
+    }
+
+    /// Lists PRs merged into main between `since` and `until`, with their full details. PRs are read
+    /// most recently updated first, up to the first one last updated before the window, since a PR
+    /// can't be updated before it's merged.
+    pub async fn get_repo_prs (&self, org: &str, repo: &str, since: DateTime<Utc>, until: DateTime<Utc>) -> Result<Vec<PullRequest>> {
+        let mut page = self.client.pulls(org, repo)
             .list()
             .state(octocrab::params::State::All)
             .sort(octocrab::params::pulls::Sort::Updated)
@@ -55,20 +198,132 @@ impl Github {
             .await?;
         let mut res = vec![];
 
-        for detail in repo_details {
-            match detail.merged_at {
-                Some(dt) => {
-                    if dt < since {
-                        return Ok(res)
-                    }
-                    let full_res: PullRequest = FromResponse::from_response(self.client._get(&detail.url).await?).await?;
-                    res.push(full_res);
+        loop {
+            for detail in page.items {
+                if detail.updated_at.is_some_and(|dt| dt < since) {
+                    return Ok(res)
+                }
+                match detail.merged_at {
+                    Some(dt) => {
+                        if dt < since || dt >= until {
+                            continue
+                        }
+                        let full_res: PullRequest = FromResponse::from_response(self.client._get(&detail.url).await?).await?;
+                        res.push(full_res);
+                    },
+                    None => continue
+                }
+            }
+            page = match self.client.get_page::<PullRequest>(&page.next).await? {
+                Some(next) => next,
+                None => return Ok(res),
+            };
+        }
+    }
+
+    /// Lists every file changed by a pull request along with its line stats.
+    pub async fn get_pr_files(&self, org: &str, repo: &str, number: u64) -> Result<Vec<DiffEntry>> {
+        let page = self.client.pulls(org, repo)
+            .list_files(number)
+            .await?;
+        self.client.all_pages(page).await.map_err(|e| e.into())
+    }
+
+    /// Lists every review submitted on a pull request.
+    pub async fn get_pr_reviews(&self, org: &str, repo: &str, number: u64) -> Result<Vec<Review>> {
+        let page = self.client.pulls(org, repo)
+            .list_reviews(number)
+            .per_page(100u8)
+            .send()
+            .await?;
+        self.client.all_pages(page).await.map_err(|e| e.into())
+    }
+
+    /// Fetches the repo's CODEOWNERS file from the first location GitHub would use it from.
+    pub async fn get_codeowners(&self, org: &str, repo: &str) -> Result<Option<String>> {
+        for path in CODEOWNERS_PATHS {
+            match self.client.repos(org, repo).get_content().path(path).send().await {
+                Ok(mut content) => {
+                    return Ok(content.take_items().first().and_then(|item| item.decoded_content()))
                 },
-                None => continue
+                Err(octocrab::Error::GitHub { source, .. }) if source.status_code.as_str() == "404" => continue,
+                Err(e) => return Err(e.into()),
             }
         }
+        Ok(None)
+    }
+
+    /// Lists the most recent releases of a repo, newest first.
+    pub async fn get_releases(&self, org: &str, repo: &str) -> Result<Vec<Release>> {
+        let page = self.client.repos(org, repo)
+            .releases()
+            .list()
+            .per_page(100)
+            .send()
+            .await?;
+        Ok(page.items)
+    }
 
-        Ok(res)
+    /// Lists the most recent tags of a repo. Tags carry no date, so the tagged commit
+    /// has to be fetched separately.
+    pub async fn get_tags(&self, org: &str, repo: &str) -> Result<Vec<Tag>> {
+        let page = self.client.repos(org, repo)
+            .list_tags()
+            .per_page(100)
+            .send()
+            .await?;
+        Ok(page.items)
+    }
+
+    /// Lists the most recent deployments of a repo to an environment, newest first.
+    pub async fn get_deployments(&self, org: &str, repo: &str, environment: &str) -> Result<Vec<Deployment>> {
+        let params = [("environment", environment), ("per_page", "100")];
+        self.client
+            .get(format!("/repos/{org}/{repo}/deployments"), Some(&params))
+            .await
+            .map_err(|e| e.into())
+    }
+
+    /// Lists every workflow run created between `since` and `until`, newest first.
+    pub async fn get_workflow_runs(&self, org: &str, repo: &str, since: DateTime<Utc>, until: DateTime<Utc>) -> Result<Vec<WorkflowRun>> {
+        let created = format!("{}..{}", since.format("%Y-%m-%dT%H:%M:%SZ"), until.format("%Y-%m-%dT%H:%M:%SZ"));
+        let per_page = WORKFLOW_RUNS_PER_PAGE.to_string();
+        let mut runs = vec![];
+        let mut page: u32 = 1;
+
+        loop {
+            let page_param = page.to_string();
+            let params = [("created", created.as_str()), ("per_page", per_page.as_str()), ("page", page_param.as_str())];
+            let run_page: WorkflowRunPage = self.client
+                .get(format!("/repos/{org}/{repo}/actions/runs"), Some(&params))
+                .await?;
+            let page_len = run_page.workflow_runs.len();
+            runs.extend(run_page.workflow_runs);
+            if page_len < WORKFLOW_RUNS_PER_PAGE || runs.len() as u64 >= run_page.total_count {
+                return Ok(runs);
+            }
+            page += 1;
+        }
+    }
+
+    /// Lists every team in an org. Requires a token with `read:org`.
+    pub async fn get_org_teams(&self, org: &str) -> Result<Vec<RequestedTeam>> {
+        let page = self.client.teams(org)
+            .list()
+            .per_page(100u8)
+            .send()
+            .await?;
+        self.client.all_pages(page).await.map_err(|e| e.into())
+    }
+
+    /// Lists the members of a team, including those of its child teams.
+    pub async fn get_team_members(&self, org: &str, team_slug: &str) -> Result<Vec<Author>> {
+        let page = self.client.teams(org)
+            .members(team_slug)
+            .per_page(100u8)
+            .send()
+            .await?;
+        self.client.all_pages(page).await.map_err(|e| e.into())
     }
 
     pub async fn get_org_repos_by_page(&self, org: &str, per_page: &u8, page: &u32) -> Result<Page<Repository>> {
@@ -81,6 +336,27 @@ impl Github {
                     .send().await.map_err(|e| e.into())
     }
 
+    /// Lists every repo in an org, most recently updated first.
+    pub async fn get_org_repos(&self, org: &str) -> Result<Vec<Repository>> {
+        let mut repos = vec![];
+        let mut page: u32 = 1;
+        loop {
+            let repo_page = self.get_org_repos_by_page(org, &ORG_REPOS_PER_PAGE, &page).await?;
+            let page_len = repo_page.items.len();
+            repos.extend(repo_page.items);
+            if page_len < ORG_REPOS_PER_PAGE as usize {
+                return Ok(repos);
+            }
+            page += 1;
+        }
+    }
+
+    /// The core API quota. Checking it doesn't count against the quota.
+    pub async fn get_rate_limit(&self) -> Result<Rate> {
+        let limits = self.client.ratelimit().get().await?;
+        Ok(limits.resources.core)
+    }
+
     pub async fn get_orgs(&self) -> Result<Page<MembershipInvitation>> {
         return self.client.current()
             .list_org_memberships_for_authenticated_user()
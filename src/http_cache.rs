@@ -0,0 +1,180 @@
+//! Caches GitHub API responses in the database and revalidates them with conditional requests.
+//!
+//! GitHub doesn't count `304 Not Modified` responses against the rate limit, so re-scraping repos
+//! whose commits, PRs and listings haven't changed costs little quota. The cache sits in octocrab's
+//! HTTP stack, below pagination, so every page of a list and every detail fetch is covered. URLs
+//! include query parameters, so lists filtered by a scrape window are only reused for the same window.
+
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use bytes::Bytes;
+use chrono::Utc;
+use http::header::{ACCEPT, CONTENT_TYPE, ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED, LINK};
+use http::{HeaderValue, Method, Request, Response, StatusCode, Uri};
+use http_body_util::{combinators::BoxBody, BodyExt, Full};
+use sqlx::SqlitePool;
+use tower::{BoxError, Layer, Service};
+
+use crate::config::HttpCacheMode;
+use crate::db::HttpCacheEntry;
+
+/// The response body type every layer of the GitHub client's HTTP stack passes along
+pub type ResponseBody = BoxBody<Bytes, BoxError>;
+
+/// Response headers stored with a cached body. `Link` carries pagination.
+pub(crate) const REPLAYED_HEADERS: [http::HeaderName; 4] = [CONTENT_TYPE, LINK, ETAG, LAST_MODIFIED];
+/// Paths that are never cached because they change on every call
+const UNCACHED_PATHS: [&str; 1] = ["/rate_limit"];
+
+#[derive(Clone)]
+pub struct HttpCacheLayer {
+    pool: SqlitePool,
+    mode: HttpCacheMode,
+}
+
+impl HttpCacheLayer {
+    pub fn new(pool: SqlitePool, mode: HttpCacheMode) -> HttpCacheLayer {
+        HttpCacheLayer { pool, mode }
+    }
+}
+
+impl<S> Layer<S> for HttpCacheLayer {
+    type Service = HttpCache<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        HttpCache { inner, pool: self.pool.clone(), mode: self.mode }
+    }
+}
+
+#[derive(Clone)]
+pub struct HttpCache<S> {
+    inner: S,
+    pool: SqlitePool,
+    mode: HttpCacheMode,
+}
+
+impl<S, B> Service<Request<B>> for HttpCache<S>
+where
+    S: Service<Request<B>, Response = Response<ResponseBody>, Error = BoxError> + Clone + Send + 'static,
+    S::Future: Send,
+    B: Send + 'static,
+{
+    type Response = Response<ResponseBody>;
+    type Error = BoxError;
+    type Future = Pin<Box<dyn Future<Output = Result<Response<ResponseBody>, BoxError>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), BoxError>> {
+        match self.mode {
+            HttpCacheMode::Offline => Poll::Ready(Ok(())),
+            _ => self.inner.poll_ready(cx),
+        }
+    }
+
+    fn call(&mut self, mut request: Request<B>) -> Self::Future {
+        // Take the service that was polled ready, leaving a fresh clone for the next call
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        let pool = self.pool.clone();
+        let mode = self.mode;
+
+        Box::pin(async move {
+            let cacheable = mode != HttpCacheMode::Off
+                && request.method() == Method::GET
+                && !UNCACHED_PATHS.contains(&request.uri().path());
+            if !cacheable {
+                if mode == HttpCacheMode::Offline {
+                    return Ok(not_cached(request.uri()));
+                }
+                return inner.call(request).await;
+            }
+
+            let url = request.uri().to_string();
+            let accept = request.headers().get(ACCEPT)
+                .and_then(|value| value.to_str().ok())
+                .unwrap_or_default()
+                .to_string();
+            let mut db_conn = pool.acquire().await?;
+            let cached = HttpCacheEntry::get(&mut db_conn, &url, &accept).await?;
+
+            if mode == HttpCacheMode::Offline {
+                return Ok(match cached {
+                    Some(entry) => replay(&entry),
+                    None => not_cached(request.uri()),
+                });
+            }
+
+            if let Some(entry) = &cached {
+                if let Some(etag) = &entry.etag {
+                    request.headers_mut().insert(IF_NONE_MATCH, HeaderValue::from_str(etag)?);
+                }
+                if let Some(last_modified) = &entry.last_modified {
+                    request.headers_mut().insert(IF_MODIFIED_SINCE, HeaderValue::from_str(last_modified)?);
+                }
+            }
+            let response = inner.call(request).await?;
+
+            if response.status() == StatusCode::NOT_MODIFIED
+                && let Some(entry) = &cached
+            {
+                return Ok(replay(entry));
+            }
+            let header = |name: &http::HeaderName| response.headers().get(name)
+                .and_then(|value| value.to_str().ok())
+                .map(|value| value.to_string());
+            let etag = header(&ETAG);
+            let last_modified = header(&LAST_MODIFIED);
+            if response.status() != StatusCode::OK || (etag.is_none() && last_modified.is_none()) {
+                return Ok(response);
+            }
+
+            let (parts, body) = response.into_parts();
+            let body = body.collect().await?.to_bytes();
+            let entry = HttpCacheEntry {
+                url,
+                accept,
+                etag,
+                last_modified,
+                headers: REPLAYED_HEADERS.iter()
+                    .filter_map(|name| Some((name.to_string(), parts.headers.get(name)?.to_str().ok()?.to_string())))
+                    .collect(),
+                body: body.to_vec(),
+                fetched_at: Utc::now(),
+            };
+            entry.save(&mut db_conn).await?;
+
+            Ok(Response::from_parts(parts, full_body(body)))
+        })
+    }
+}
+
+/// Rebuilds a `200 OK` response from a cache entry.
+fn replay(entry: &HttpCacheEntry) -> Response<ResponseBody> {
+    let mut response = Response::builder().status(StatusCode::OK);
+    for (name, value) in &entry.headers {
+        response = response.header(name, value);
+    }
+    response.body(full_body(Bytes::from(entry.body.clone())))
+        .expect("cached headers were valid when stored")
+}
+
+/// The error offline mode answers with for requests it has no cached response for
+fn not_cached(uri: &Uri) -> Response<ResponseBody> {
+    error_response(format!("{uri} isn't in the HTTP cache and http_cache is offline"))
+}
+
+/// A `504` standing in for a response that can't be served without the network. The body is
+/// shaped like a GitHub error so octocrab reports the message.
+pub(crate) fn error_response(message: String) -> Response<ResponseBody> {
+    let body = serde_json::json!({ "message": message });
+    Response::builder()
+        .status(StatusCode::GATEWAY_TIMEOUT)
+        .header(CONTENT_TYPE, "application/json")
+        .body(full_body(Bytes::from(body.to_string())))
+        .expect("static response is valid")
+}
+
+pub(crate) fn full_body(bytes: Bytes) -> ResponseBody {
+    Full::new(bytes).map_err(|never| match never {}).boxed()
+}
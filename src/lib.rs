@@ -1,7 +1,21 @@
 pub mod app;
+pub mod cli;
+pub mod codeowners;
 pub mod config;
+pub mod conventional;
+pub mod daemon;
+pub mod date_range;
 pub mod db;
+pub mod demo;
 pub mod github;
+pub mod http_cache;
+pub mod lock;
+pub mod paths;
+pub mod plan;
+pub mod recording;
+pub mod schedule;
 pub mod scrape;
 pub mod scraper;
-pub mod stats;
\ No newline at end of file
+pub mod stats;
+pub mod timing;
+pub mod webhook;
@@ -0,0 +1,73 @@
+//! Lock files that keep scrapes from overlapping and stop two daemons or webhook receivers
+//! sharing a database.
+//!
+//! A lock is an OS file lock on a file next to the database, which holds the owner's pid for
+//! error messages. The OS releases it when the lock is dropped or the owning process exits,
+//! however it exits, so a crashed process never leaves a lock behind.
+
+use std::{fs::{self, File, TryLockError}, io::{Seek, Write}, path::{Path, PathBuf}};
+
+use anyhow::{anyhow, Result};
+
+use crate::paths::db_path;
+
+/// Held by `org-pulse daemon` for as long as it runs
+pub fn daemon_lock_path() -> PathBuf {
+    lock_path("daemon")
+}
+
+/// Held by `org-pulse serve` for as long as it runs, since receivers share one live scrape
+pub fn serve_lock_path() -> PathBuf {
+    lock_path("serve")
+}
+
+/// Held by `run_scrape` for the duration of a scrape
+pub fn scrape_lock_path() -> PathBuf {
+    lock_path("scrape")
+}
+
+/// `<database>.<name>.lock`, so each database has its own locks
+fn lock_path(name: &str) -> PathBuf {
+    let mut path = db_path().into_os_string();
+    path.push(format!(".{name}.lock"));
+    PathBuf::from(path)
+}
+
+#[derive(Debug)]
+pub struct LockFile {
+    path: PathBuf,
+    /// Holds the lock; closing it releases the lock
+    _file: File,
+}
+
+impl LockFile {
+    /// Takes the lock at `path`, failing if another process, or another handle in this one, holds it.
+    pub fn acquire(path: impl Into<PathBuf>) -> Result<LockFile> {
+        let path = path.into();
+        // The file is left in place between holders, so it's only truncated once the lock is ours
+        let mut file = fs::OpenOptions::new().read(true).write(true).create(true).truncate(false).open(&path)?;
+        match file.try_lock() {
+            Ok(()) => {}
+            Err(TryLockError::WouldBlock) => {
+                return Err(match lock_holder(&path) {
+                    Some(pid) => anyhow!("{} is held by process {pid}", path.display()),
+                    None => anyhow!("{} is held by another process", path.display()),
+                });
+            }
+            Err(TryLockError::Error(e)) => return Err(e.into()),
+        }
+
+        file.set_len(0)?;
+        file.rewind()?;
+        writeln!(file, "{}", std::process::id())?;
+        Ok(LockFile { path, _file: file })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+fn lock_holder(path: &Path) -> Option<u32> {
+    fs::read_to_string(path).ok()?.trim().parse().ok()
+}
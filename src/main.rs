@@ -11,9 +11,60 @@ use ratatui::{
 use std::io;
 
 use org_pulse::app::{events::handle_events, state::App, ui::ui};
+use org_pulse::cli::{Cli, Command, USAGE};
+use org_pulse::config::get_config;
+use org_pulse::date_range::DateRange;
+use org_pulse::db::new_pool;
+use org_pulse::paths::{set_config_path, set_db_path};
 
 #[tokio::main]
 async fn main() -> Result<()> {
+    let cli = match Cli::parse(std::env::args().skip(1)) {
+        Ok(cli) => cli,
+        Err(e) => {
+            eprintln!("{e}\n\n{USAGE}");
+            std::process::exit(2);
+        }
+    };
+    if let Some(config) = &cli.config {
+        set_config_path(config);
+    }
+    if let Some(db) = &cli.db {
+        set_db_path(get_config()?.database_path(db));
+    }
+    match cli.command {
+        Command::Tui => {}
+        Command::Scrape { range, profile } => {
+            let cfg = get_config()?;
+            let profile = profile.map(|name| cfg.profile(&name)).transpose()?;
+            let range = range.unwrap_or_else(|| DateRange::for_profile(&cfg, profile));
+            org_pulse::scraper::run_scrape(&new_pool().await?, range, profile).await?;
+            println!("Scraped {} to {}", range.start.to_rfc3339(), range.end.to_rfc3339());
+            return Ok(());
+        }
+        Command::Plan { range, profile } => {
+            let cfg = get_config()?;
+            let profile = profile.map(|name| cfg.profile(&name)).transpose()?;
+            let range = range.unwrap_or_else(|| DateRange::for_profile(&cfg, profile));
+            print!("{}", org_pulse::plan::plan_scrape(&new_pool().await?, range, profile).await?);
+            return Ok(());
+        }
+        Command::Demo(options) => {
+            let db_pool = new_pool().await?;
+            println!("{}", org_pulse::demo::generate_demo(&db_pool, &options).await?);
+            return Ok(());
+        }
+        Command::Daemon => return org_pulse::daemon::run_daemon(&new_pool().await?).await,
+        Command::Serve => return org_pulse::webhook::run_server(new_pool().await?).await,
+        Command::Help => {
+            println!("{USAGE}");
+            return Ok(());
+        }
+    }
+
+    // One pool serves the whole session, including scrapes started from the TUI
+    let db_pool = new_pool().await?;
+
     // Setup terminal
     enable_raw_mode()?;
     let mut stdout = io::stdout();
@@ -22,7 +73,7 @@ async fn main() -> Result<()> {
     let mut terminal = Terminal::new(backend)?;
 
     // Create app state and load data
-    let mut app = match App::new_with_data().await {
+    let mut app = match App::new_with_data(db_pool).await {
         Ok(app) => app,
         Err(e) => {
             // Restore terminal before showing error
@@ -73,6 +124,11 @@ async fn run_tui<B: Backend>(terminal: &mut Terminal<B>, app: &mut App) -> Resul
             app.set_error(format!("Navigation error: {e}"));
         }
 
+        // Handle database switches - errors should be captured and displayed
+        if let Err(e) = app.handle_database_switch().await {
+            app.set_error(format!("Database switch error: {e}"));
+        }
+
         // Handle pending view switches - errors should be captured and displayed
         if let Err(e) = app.handle_pending_view_switch().await {
             app.set_error(format!("View switch error: {e}"));
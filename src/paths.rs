@@ -0,0 +1,70 @@
+//! Where the database and config file live.
+//!
+//! Each is the first of: a path given with `--db`/`--config` (or picked in the TUI), the
+//! `ORG_PULSE_DB`/`ORG_PULSE_CONFIG` environment variable, the file in the working directory
+//! when it already exists, and otherwise the XDG data or config directory. Existing setups that
+//! keep `org-pulse.db` and `config.toml` next to where they run keep working, while new ones get
+//! the same database and config wherever they're run from.
+
+use std::env;
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+
+pub const DB_FILE: &str = "org-pulse.db";
+pub const CONFIG_FILE: &str = "config.toml";
+pub const DB_ENV: &str = "ORG_PULSE_DB";
+pub const CONFIG_ENV: &str = "ORG_PULSE_CONFIG";
+const APP_DIR: &str = "org-pulse";
+
+static DB_PATH: RwLock<Option<PathBuf>> = RwLock::new(None);
+static CONFIG_PATH: RwLock<Option<PathBuf>> = RwLock::new(None);
+
+/// The database every pool connects to
+pub fn db_path() -> PathBuf {
+    let set = DB_PATH.read().expect("path lock isn't poisoned").clone();
+    locate(set, env::var_os(DB_ENV), Path::new(DB_FILE), xdg_dir("XDG_DATA_HOME", ".local/share"))
+}
+
+/// Points new pools, and the scrape and daemon locks, at another database.
+pub fn set_db_path(path: impl Into<PathBuf>) {
+    *DB_PATH.write().expect("path lock isn't poisoned") = Some(path.into());
+}
+
+/// The config file `get_config` reads, created with defaults when it's missing
+pub fn config_path() -> PathBuf {
+    let set = CONFIG_PATH.read().expect("path lock isn't poisoned").clone();
+    locate(set, env::var_os(CONFIG_ENV), Path::new(CONFIG_FILE), xdg_dir("XDG_CONFIG_HOME", ".config"))
+}
+
+pub fn set_config_path(path: impl Into<PathBuf>) {
+    *CONFIG_PATH.write().expect("path lock isn't poisoned") = Some(path.into());
+}
+
+/// Picks a path in priority order: one that was set explicitly, the environment variable, an
+/// existing `local` file, then `local`'s file name in `app_dir`. Without a home directory to put
+/// `app_dir` in, the local file is used.
+pub fn locate(set: Option<PathBuf>, env_value: Option<OsString>, local: &Path, app_dir: Option<PathBuf>) -> PathBuf {
+    if let Some(path) = set {
+        return path;
+    }
+    if let Some(path) = env_value.filter(|value| !value.is_empty()) {
+        return PathBuf::from(path);
+    }
+    if local.exists() {
+        return local.to_path_buf();
+    }
+    match (app_dir, local.file_name()) {
+        (Some(dir), Some(file_name)) => dir.join(file_name),
+        _ => local.to_path_buf(),
+    }
+}
+
+/// `$XDG_..._HOME/org-pulse`, falling back to `~/<default>/org-pulse` when the variable isn't set
+fn xdg_dir(variable: &str, default: &str) -> Option<PathBuf> {
+    let base = env::var_os(variable)
+        .filter(|value| !value.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(default)))?;
+    Some(base.join(APP_DIR))
+}
@@ -0,0 +1,269 @@
+//! Dry-run scrape plans: which repos a scrape would visit and roughly how many API calls it
+//! would take, checked against the remaining quota. Planning lists orgs, teams and repos like a
+//! scrape does, but fetches nothing per repo and never writes a scrape.
+
+use std::collections::HashMap;
+use std::fmt;
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use octocrab::models::Rate;
+use sqlx::SqlitePool;
+
+use crate::codeowners::CODEOWNERS_PATHS;
+use crate::config::{get_config, ScrapeProfile};
+use crate::date_range::DateRange;
+use crate::db::get_repo_activity;
+use crate::github::{Github, COMMITS_PER_PAGE, ORG_REPOS_PER_PAGE};
+use crate::scraper::ScrapeFilter;
+use crate::stats::median;
+
+/// Rates assumed for repos that haven't been scraped before, when there's no history at all
+const TYPICAL_COMMITS_PER_DAY: f64 = 10.0 / 7.0;
+const TYPICAL_PRS_PER_DAY: f64 = 2.0 / 7.0;
+/// Detail, files and reviews for each merged PR
+const CALLS_PER_PR: f64 = 3.0;
+
+/// A repo's average activity across past scrapes
+#[derive(Debug, Clone, PartialEq)]
+pub struct RepoActivity {
+    pub org_name: String,
+    pub repo_name: String,
+    pub commits_per_day: f64,
+    pub prs_per_day: f64,
+}
+
+/// Where a repo's activity estimate came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EstimateBasis {
+    /// Nothing was pushed since the window started, so only the commit list is fetched
+    NoPushes,
+    /// The repo's own past scrapes
+    History,
+    /// The median of other repos' history, or a typical repo when there's none
+    Typical,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RepoPlan {
+    pub org_name: String,
+    pub repo_name: String,
+    pub commits: f64,
+    pub prs: f64,
+    pub basis: EstimateBasis,
+    pub api_calls: u64,
+}
+
+/// Turns past activity into per-repo call estimates
+#[derive(Debug, Clone)]
+pub struct ActivityEstimator {
+    history: HashMap<(String, String), (f64, f64)>,
+    typical: (f64, f64),
+}
+
+impl ActivityEstimator {
+    pub fn new(history: Vec<RepoActivity>) -> ActivityEstimator {
+        let typical = (
+            median(history.iter().map(|a| a.commits_per_day).collect()).unwrap_or(TYPICAL_COMMITS_PER_DAY),
+            median(history.iter().map(|a| a.prs_per_day).collect()).unwrap_or(TYPICAL_PRS_PER_DAY),
+        );
+        let history = history.into_iter()
+            .map(|a| ((a.org_name, a.repo_name), (a.commits_per_day, a.prs_per_day)))
+            .collect();
+        ActivityEstimator { history, typical }
+    }
+
+    /// Estimates the calls a scrape makes for one repo, mirroring the order `run_scrape` fetches in.
+    pub fn estimate(&self, org_name: &str, repo_name: &str, pushed_at: Option<DateTime<Utc>>, range: &DateRange, environments: usize, max_commit_details: usize) -> RepoPlan {
+        let mut plan = RepoPlan {
+            org_name: org_name.to_string(),
+            repo_name: repo_name.to_string(),
+            commits: 0.0,
+            prs: 0.0,
+            basis: EstimateBasis::NoPushes,
+            api_calls: 1,
+        };
+        if pushed_at.is_some_and(|pushed_at| pushed_at < range.start) {
+            return plan;
+        }
+
+        let days = (range.end - range.start).num_seconds() as f64 / 86400.0;
+        let ((commits_per_day, prs_per_day), basis) = match self.history.get(&(org_name.to_string(), repo_name.to_string())) {
+            Some(rates) => (*rates, EstimateBasis::History),
+            None => (self.typical, EstimateBasis::Typical),
+        };
+        plan.basis = basis;
+        plan.commits = commits_per_day * days;
+        plan.prs = prs_per_day * days;
+        // Repos without commits in the window stop after the commit list
+        if plan.commits.round() < 1.0 {
+            return plan;
+        }
+
+        let commits = plan.commits.round() as u64;
+        let commit_calls = (commits - 1) / COMMITS_PER_PAGE as u64 // further commit list pages
+            + commits.min(max_commit_details as u64); // commit details, up to the per-repo limit
+        let per_repo = CODEOWNERS_PATHS.len() // CODEOWNERS lookups, at worst
+            + 1 // PR list
+            + 2 // releases, and tags for repos without them
+            + environments // deployments
+            + 1; // workflow runs
+        plan.api_calls += commit_calls + (plan.prs * CALLS_PER_PR).round() as u64 + per_repo as u64;
+        plan
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct OrgPlan {
+    pub name: String,
+    pub listed_repos: usize,
+    pub excluded_repos: usize,
+    /// `None` when the token can't list teams
+    pub teams: Option<usize>,
+}
+
+impl OrgPlan {
+    /// Repo pages, then the team list and each team's members
+    pub fn listing_calls(&self) -> u64 {
+        let repo_pages = self.listed_repos / ORG_REPOS_PER_PAGE as usize + 1;
+        (repo_pages + 1 + self.teams.unwrap_or(0)) as u64
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ScrapePlan {
+    pub range: DateRange,
+    pub profile: Option<String>,
+    pub orgs: Vec<OrgPlan>,
+    /// Orgs left out by `ignored_org_pattern` or the profile
+    pub skipped_orgs: Vec<String>,
+    pub repos: Vec<RepoPlan>,
+    /// `None` when the quota couldn't be checked
+    pub rate_limit: Option<Rate>,
+}
+
+impl ScrapePlan {
+    /// The org membership list, then each org's listings
+    pub fn listing_calls(&self) -> u64 {
+        1 + self.orgs.iter().map(OrgPlan::listing_calls).sum::<u64>()
+    }
+
+    pub fn api_calls(&self) -> u64 {
+        self.listing_calls() + self.repos.iter().map(|r| r.api_calls).sum::<u64>()
+    }
+
+    /// Whether the estimate fits in the remaining quota, if it's known
+    pub fn fits_quota(&self) -> Option<bool> {
+        self.rate_limit.as_ref().map(|rate| self.api_calls() <= rate.remaining as u64)
+    }
+}
+
+impl fmt::Display for ScrapePlan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let format = "%Y-%m-%d %H:%M UTC";
+        write!(f, "Scrape plan for {} .. {}", self.range.start.format(format), self.range.end.format(format))?;
+        if let Some(profile) = &self.profile {
+            write!(f, " (profile {profile})")?;
+        }
+        writeln!(f)?;
+        writeln!(f)?;
+
+        for org in &self.orgs {
+            let teams = match org.teams {
+                Some(teams) => format!("{teams} teams"),
+                None => "teams unavailable".to_string(),
+            };
+            writeln!(
+                f,
+                "  {:<30} {:>5} repos listed, {:>5} to scrape, {:>5} excluded, {teams}",
+                org.name,
+                org.listed_repos,
+                org.listed_repos - org.excluded_repos,
+                org.excluded_repos,
+            )?;
+        }
+        if !self.skipped_orgs.is_empty() {
+            writeln!(f, "  Skipped orgs: {}", self.skipped_orgs.join(", "))?;
+        }
+        writeln!(f)?;
+
+        let pushed = self.repos.iter().filter(|r| r.basis != EstimateBasis::NoPushes).count();
+        let from_history = self.repos.iter().filter(|r| r.basis == EstimateBasis::History).count();
+        writeln!(f, "Repos to scrape:     {} ({pushed} pushed to in the window, {from_history} estimated from past scrapes)", self.repos.len())?;
+        writeln!(f, "Estimated API calls: ~{} ({} listing, {} per repo)", self.api_calls(), self.listing_calls(), self.api_calls() - self.listing_calls())?;
+        match &self.rate_limit {
+            Some(rate) => {
+                let reset = DateTime::from_timestamp(rate.reset as i64, 0)
+                    .map(|reset| reset.format(format).to_string())
+                    .unwrap_or_default();
+                writeln!(f, "Rate limit:          {} of {} remaining, resets {reset}", rate.remaining, rate.limit)?;
+                if self.api_calls() <= rate.remaining as u64 {
+                    writeln!(f, "Fits in the remaining quota")?;
+                } else {
+                    writeln!(f, "Exceeds the remaining quota by ~{} calls", self.api_calls() - rate.remaining as u64)?;
+                }
+            }
+            None => writeln!(f, "Rate limit:          unavailable")?,
+        }
+
+        let mut busiest: Vec<&RepoPlan> = self.repos.iter().filter(|r| r.basis != EstimateBasis::NoPushes).collect();
+        busiest.sort_by_key(|repo| std::cmp::Reverse(repo.api_calls));
+        if !busiest.is_empty() {
+            writeln!(f)?;
+            writeln!(f, "Busiest repos:")?;
+            for repo in busiest.iter().take(10) {
+                writeln!(
+                    f,
+                    "  {:<40} ~{:>5} calls (~{:.0} commits, ~{:.0} PRs{})",
+                    format!("{}/{}", repo.org_name, repo.repo_name),
+                    repo.api_calls,
+                    repo.commits,
+                    repo.prs,
+                    if repo.basis == EstimateBasis::Typical { ", typical" } else { "" },
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Lists and filters orgs and repos the way a scrape over `range` would, and estimates its API calls.
+pub async fn plan_scrape(db_pool: &SqlitePool, range: DateRange, profile: Option<&ScrapeProfile>) -> Result<ScrapePlan> {
+    let cfg = get_config()?;
+    let gh = Github::from_env(&cfg, db_pool)?;
+    let filter = ScrapeFilter::new(&cfg, profile)?;
+
+    let mut db_conn = db_pool.acquire().await?;
+    let estimator = ActivityEstimator::new(get_repo_activity(&mut db_conn).await?);
+
+    let mut plan = ScrapePlan {
+        range,
+        profile: profile.map(|p| p.name.clone()),
+        orgs: vec![],
+        skipped_orgs: vec![],
+        repos: vec![],
+        rate_limit: None,
+    };
+    for org in gh.get_orgs().await? {
+        let login = org.organization.login;
+        if !filter.includes_org(&login) {
+            plan.skipped_orgs.push(login);
+            continue;
+        }
+
+        let teams = gh.get_org_teams(&login).await.ok().map(|teams| teams.len());
+        let repos = gh.get_org_repos(&login).await?;
+        let mut excluded_repos = 0;
+        for repo in &repos {
+            if !filter.includes_repo(&cfg, &login, repo) {
+                excluded_repos += 1;
+                continue;
+            }
+            plan.repos.push(estimator.estimate(&login, &repo.name, repo.pushed_at, &range, cfg.deployment_environments.len(), cfg.max_commit_details));
+        }
+        plan.orgs.push(OrgPlan { name: login, listed_repos: repos.len(), excluded_repos, teams });
+    }
+    plan.rate_limit = gh.get_rate_limit().await.ok();
+
+    Ok(plan)
+}
@@ -0,0 +1,171 @@
+//! Records GitHub API traffic to a fixture directory and replays it without the network.
+//!
+//! Each request is stored as one JSON file holding the response's status, headers and body. Files
+//! are named after the request path plus a hash of the method, URL and `Accept` header, so they can
+//! be found and edited by hand. Request headers aren't recorded, so fixtures never contain tokens.
+//! URLs include query parameters such as a scrape's window, so replaying a scrape needs the window
+//! it was recorded with.
+
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use anyhow::Result;
+use bytes::Bytes;
+use http::header::ACCEPT;
+use http::{Request, Response, StatusCode};
+use http_body_util::BodyExt;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tower::{BoxError, Layer, Service};
+
+use crate::config::RecordingMode;
+use crate::http_cache::{error_response, full_body, ResponseBody, REPLAYED_HEADERS};
+
+/// One recorded request and its response
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct RecordedExchange {
+    pub method: String,
+    pub url: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub accept: String,
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    /// The body as JSON when it parses, so fixtures stay readable, otherwise as a string
+    pub body: serde_json::Value,
+    /// Set when `body` holds a response that wasn't JSON
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub text: bool,
+}
+
+impl RecordedExchange {
+    fn body_bytes(&self) -> Result<Bytes> {
+        Ok(match (&self.body, self.text) {
+            (serde_json::Value::String(text), true) => Bytes::from(text.clone()),
+            (body, _) => Bytes::from(serde_json::to_vec(body)?),
+        })
+    }
+}
+
+/// The file a request is recorded in, e.g. `get-repos-acme-api-commits-3f9a0c2b17de.json`
+pub fn fixture_path(dir: &Path, method: &str, url: &str, accept: &str) -> PathBuf {
+    let hash = hex::encode(Sha256::digest(format!("{method} {url} {accept}")));
+    let path = url.split_once("://").map_or(url, |(_, rest)| rest.split_once('/').map_or("", |(_, path)| path));
+    let path = path.split('?').next().unwrap_or_default();
+    let slug: String = path.chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_lowercase() } else { '-' })
+        .take(80)
+        .collect();
+    dir.join(format!("{}-{}-{}.json", method.to_lowercase(), slug.trim_matches('-'), &hash[..12]))
+}
+
+#[derive(Clone)]
+pub struct RecordingLayer {
+    mode: RecordingMode,
+    dir: Arc<PathBuf>,
+}
+
+impl RecordingLayer {
+    pub fn new(mode: RecordingMode, dir: impl Into<PathBuf>) -> RecordingLayer {
+        RecordingLayer { mode, dir: Arc::new(dir.into()) }
+    }
+}
+
+impl<S> Layer<S> for RecordingLayer {
+    type Service = Recording<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        Recording { inner, mode: self.mode, dir: self.dir.clone() }
+    }
+}
+
+#[derive(Clone)]
+pub struct Recording<S> {
+    inner: S,
+    mode: RecordingMode,
+    dir: Arc<PathBuf>,
+}
+
+impl<S, B> Service<Request<B>> for Recording<S>
+where
+    S: Service<Request<B>, Response = Response<ResponseBody>, Error = BoxError> + Clone + Send + 'static,
+    S::Future: Send,
+    B: Send + 'static,
+{
+    type Response = Response<ResponseBody>;
+    type Error = BoxError;
+    type Future = Pin<Box<dyn Future<Output = Result<Response<ResponseBody>, BoxError>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), BoxError>> {
+        match self.mode {
+            RecordingMode::Replay => Poll::Ready(Ok(())),
+            _ => self.inner.poll_ready(cx),
+        }
+    }
+
+    fn call(&mut self, request: Request<B>) -> Self::Future {
+        // Take the service that was polled ready, leaving a fresh clone for the next call
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        let mode = self.mode;
+        let dir = self.dir.clone();
+
+        Box::pin(async move {
+            if mode == RecordingMode::Off {
+                return inner.call(request).await;
+            }
+
+            let method = request.method().to_string();
+            let url = request.uri().to_string();
+            let accept = request.headers().get(ACCEPT)
+                .and_then(|value| value.to_str().ok())
+                .unwrap_or_default()
+                .to_string();
+            let path = fixture_path(&dir, &method, &url, &accept);
+
+            if mode == RecordingMode::Replay {
+                let recorded = match tokio::fs::read(&path).await {
+                    Ok(recorded) => recorded,
+                    Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                        return Ok(error_response(format!("No recorded response for {method} {url} in {}", dir.display())));
+                    }
+                    Err(e) => return Err(e.into()),
+                };
+                let exchange: RecordedExchange = serde_json::from_slice(&recorded)?;
+                let mut response = Response::builder().status(exchange.status);
+                for (name, value) in &exchange.headers {
+                    response = response.header(name, value);
+                }
+                return Ok(response.body(full_body(exchange.body_bytes()?))?);
+            }
+
+            let response = inner.call(request).await?;
+            let (parts, body) = response.into_parts();
+            let body = body.collect().await?.to_bytes();
+            let (body_json, text) = match serde_json::from_slice(&body) {
+                Ok(json) => (json, false),
+                Err(_) => (serde_json::Value::String(String::from_utf8_lossy(&body).into_owned()), true),
+            };
+            let exchange = RecordedExchange {
+                method,
+                url,
+                accept,
+                status: parts.status.as_u16(),
+                headers: REPLAYED_HEADERS.iter()
+                    .filter_map(|name| Some((name.to_string(), parts.headers.get(name)?.to_str().ok()?.to_string())))
+                    .collect(),
+                body: body_json,
+                text,
+            };
+            // A 304 can't be replayed without the cached body it refers to
+            if parts.status != StatusCode::NOT_MODIFIED {
+                tokio::fs::create_dir_all(dir.as_path()).await?;
+                tokio::fs::write(&path, serde_json::to_vec_pretty(&exchange)?).await?;
+            }
+
+            Ok(Response::from_parts(parts, full_body(body)))
+        })
+    }
+}
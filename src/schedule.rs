@@ -0,0 +1,129 @@
+//! Cron-style schedules for `org-pulse daemon`.
+//!
+//! Schedules use the standard five fields, `minute hour day-of-month month day-of-week`,
+//! evaluated in UTC. Each field accepts `*`, numbers, ranges (`1-5`), lists (`1,15`) and
+//! steps (`*/15`, `0-30/10`). Day-of-week runs from 0 (Sunday) to 6, with 7 also meaning Sunday.
+//! As in cron, when both day fields are restricted a day matching either one runs.
+
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Datelike, Duration, DurationRound, Timelike, Utc};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Schedule {
+    minutes: Vec<bool>,
+    hours: Vec<bool>,
+    days_of_month: Vec<bool>,
+    months: Vec<bool>,
+    days_of_week: Vec<bool>,
+    /// Whether the day-of-month or day-of-week field is `*`
+    any_day_of_month: bool,
+    any_day_of_week: bool,
+}
+
+impl Schedule {
+    /// Parses a five-field cron expression such as `0 6 * * 1` (Mondays at 06:00 UTC).
+    pub fn parse(expression: &str) -> Result<Schedule> {
+        let fields: Vec<&str> = expression.split_whitespace().collect();
+        let [minute, hour, day_of_month, month, day_of_week] = fields[..] else {
+            return Err(anyhow!("Schedule `{expression}` should have 5 fields: minute hour day-of-month month day-of-week"));
+        };
+
+        let mut days_of_week = parse_field(day_of_week, 0, 7)
+            .map_err(|e| anyhow!("Invalid day-of-week in schedule `{expression}`: {e}"))?;
+        if days_of_week[7] {
+            days_of_week[0] = true;
+        }
+        days_of_week.truncate(7);
+
+        Ok(Schedule {
+            minutes: parse_field(minute, 0, 59).map_err(|e| anyhow!("Invalid minute in schedule `{expression}`: {e}"))?,
+            hours: parse_field(hour, 0, 23).map_err(|e| anyhow!("Invalid hour in schedule `{expression}`: {e}"))?,
+            days_of_month: parse_field(day_of_month, 1, 31).map_err(|e| anyhow!("Invalid day-of-month in schedule `{expression}`: {e}"))?,
+            months: parse_field(month, 1, 12).map_err(|e| anyhow!("Invalid month in schedule `{expression}`: {e}"))?,
+            days_of_week,
+            any_day_of_month: day_of_month == "*",
+            any_day_of_week: day_of_week == "*",
+        })
+    }
+
+    fn matches_day(&self, time: DateTime<Utc>) -> bool {
+        let day_of_month = self.days_of_month[time.day() as usize];
+        let day_of_week = self.days_of_week[time.weekday().num_days_from_sunday() as usize];
+        match (self.any_day_of_month, self.any_day_of_week) {
+            (false, false) => day_of_month || day_of_week,
+            _ => day_of_month && day_of_week,
+        }
+    }
+
+    /// The first scheduled minute strictly after `after`, or `None` if the schedule never
+    /// fires, e.g. `0 0 31 2 *`.
+    pub fn next_after(&self, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let mut time = after.duration_trunc(Duration::minutes(1)).ok()? + Duration::minutes(1);
+        // Every combination of month, day and weekday repeats within 28 years
+        let give_up = after + Duration::days(366 * 28);
+
+        while time < give_up {
+            if !self.months[time.month() as usize] {
+                let (year, month) = if time.month() == 12 { (time.year() + 1, 1) } else { (time.year(), time.month() + 1) };
+                time = time.with_day(1)?.with_hour(0)?.with_minute(0)?.with_month(month)?.with_year(year)?;
+                continue;
+            }
+            if !self.matches_day(time) {
+                time = (time + Duration::days(1)).with_hour(0)?.with_minute(0)?;
+                continue;
+            }
+            if !self.hours[time.hour() as usize] {
+                time = (time + Duration::hours(1)).with_minute(0)?;
+                continue;
+            }
+            if !self.minutes[time.minute() as usize] {
+                time += Duration::minutes(1);
+                continue;
+            }
+            return Some(time);
+        }
+        None
+    }
+}
+
+/// Parses one cron field into a lookup table indexed by value, for values in `min..=max`.
+fn parse_field(field: &str, min: u32, max: u32) -> Result<Vec<bool>> {
+    let mut allowed = vec![false; max as usize + 1];
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => (range, step.parse::<u32>().map_err(|_| anyhow!("bad step `{step}`"))?),
+            None => (part, 1),
+        };
+        if step == 0 {
+            return Err(anyhow!("step can't be 0"));
+        }
+
+        let (start, end) = match range {
+            "*" => (min, max),
+            _ => match range.split_once('-') {
+                Some((start, end)) => (parse_value(start, min, max)?, parse_value(end, min, max)?),
+                // `5/10` means every 10 starting at 5
+                None if part.contains('/') => (parse_value(range, min, max)?, max),
+                None => {
+                    let value = parse_value(range, min, max)?;
+                    (value, value)
+                }
+            },
+        };
+        if start > end {
+            return Err(anyhow!("range `{range}` runs backwards"));
+        }
+        for value in (start..=end).step_by(step as usize) {
+            allowed[value as usize] = true;
+        }
+    }
+    Ok(allowed)
+}
+
+fn parse_value(value: &str, min: u32, max: u32) -> Result<u32> {
+    let parsed = value.parse::<u32>().map_err(|_| anyhow!("`{value}` isn't a number"))?;
+    if !(min..=max).contains(&parsed) {
+        return Err(anyhow!("{parsed} is outside {min}-{max}"));
+    }
+    Ok(parsed)
+}
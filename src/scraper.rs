@@ -1,11 +1,15 @@
-use std::env;
-use chrono::{Duration, Utc};
-use anyhow::Result;
+use chrono::{DateTime, Utc};
+use anyhow::{anyhow, Result};
+use globset::{Glob, GlobBuilder, GlobSet, GlobSetBuilder};
 use regex::Regex;
-use sqlx::migrate;
+use sqlx::{Connection, SqliteConnection, SqlitePool};
 use std::collections::HashMap;
 
-use crate::{config::get_config, db::{new_pool, Org, Repo, Scrape, RepoScrape, Contributor, ContributorScrapes}, github::Github};
+use crate::{codeowners::CodeOwners, conventional::change_type, config::{get_config, AppConfig, ScrapeProfile}, date_range::DateRange, db::{Org, Repo, RepoMetadata, Scrape, ScrapeStatus, RepoScrape, Contributor, ContributorScrapes, Commit, CommitFile, PullRequest, PullRequestFile, PullRequestReview, Team, Release, Tag, Deployment, WorkflowRun, sync_config_groups}, github::{Deployment as GhDeployment, Github, WorkflowRun as GhWorkflowRun}, lock::{scrape_lock_path, LockFile}};
+use octocrab::models::{pulls::{PullRequest as GhPullRequest, Review, ReviewState}, repos::{DiffEntry, Release as GhRelease, RepoCommit, Tag as GhTag}, IssueState, Repository};
+
+/// Requests left unspent by commit detail fetches, for the PR, release and run listings that follow
+const DETAIL_QUOTA_RESERVE: usize = 500;
 
 // Temporary data structure to collect scrape data before saving to DB
 #[derive(Debug)]
@@ -14,6 +18,7 @@ struct TempRepoScrape {
     total_commits: i64,
     total_prs: i64,
     total_lines: i64,
+    total_excluded_lines: i64,
 }
 
 #[derive(Debug)]
@@ -21,6 +26,7 @@ struct TempContributorData {
     username: String,
     commits: i64,
     lines: i64,
+    excluded_lines: i64,
 }
 
 impl TempRepoScrape {
@@ -30,151 +36,646 @@ impl TempRepoScrape {
             total_commits: 0,
             total_prs: 0,
             total_lines: 0,
+            total_excluded_lines: 0,
         }
     }
 
-    fn process_commit(&mut self, commit: &octocrab::models::repos::RepoCommit, user_ignore_regex: &Regex) -> Result<()> {
-        let commit_author = match &commit.author {
-            Some(author) => author.login.clone(),
-            None => "anonymous".to_string()
-        };
+    /// Fills commit counters from the per-author counts stored in the `commits` table.
+    fn process_commit_counts(&mut self, counts: Vec<(String, i64)>, user_ignore_regex: &Regex) {
+        for (username, commits) in counts {
+            if user_ignore_regex.find(&username).is_some() {
+                continue;
+            }
+
+            self.total_commits += commits;
+
+            let contributor = self.contributors
+                .entry(username.clone())
+                .or_insert_with(|| TempContributorData {
+                    username,
+                    commits: 0,
+                    lines: 0,
+                    excluded_lines: 0,
+                });
+            contributor.commits += commits;
+        }
+    }
+
+    /// Fills PR and line counters from the per-author totals stored in the `pull_requests` table.
+    fn process_pr_totals(&mut self, totals: Vec<(String, i64, i64, i64)>, user_ignore_regex: &Regex) {
+        for (username, prs, lines, excluded_lines) in totals {
+            if user_ignore_regex.find(&username).is_some() {
+                continue;
+            }
+
+            let contributor = self.contributors
+                .entry(username.clone())
+                .or_insert_with(|| TempContributorData {
+                    username,
+                    commits: 0,
+                    lines: 0,
+                    excluded_lines: 0,
+                });
+            contributor.lines += lines;
+            contributor.excluded_lines += excluded_lines;
+
+            self.total_prs += prs;
+            self.total_lines += lines;
+            self.total_excluded_lines += excluded_lines;
+        }
+    }
+}
+
+/// Checks the fork/archived switches and topic allow/deny lists from config.
+/// Runs on the org listing so excluded repos cost no further API calls.
+fn is_repo_excluded(cfg: &AppConfig, metadata: &RepoMetadata) -> bool {
+    if cfg.skip_forks && metadata.fork {
+        return true;
+    }
+    if cfg.skip_archived && metadata.archived {
+        return true;
+    }
+
+    let topics = &metadata.topics;
+    if !cfg.allowed_topics.is_empty() && !topics.iter().any(|t| cfg.allowed_topics.contains(t)) {
+        return true;
+    }
+    topics.iter().any(|t| cfg.denied_topics.contains(t))
+}
+
+/// Maps the fields we keep from GitHub's repository listing.
+fn repo_metadata(repo: &Repository) -> RepoMetadata {
+    RepoMetadata {
+        language: repo.language.as_ref().and_then(|l| l.as_str()).map(|l| l.to_string()),
+        topics: repo.topics.clone().unwrap_or_default(),
+        fork: repo.fork.unwrap_or_default(),
+        archived: repo.archived.unwrap_or_default(),
+        private: repo.private.unwrap_or_default(),
+        visibility: repo.visibility.clone(),
+        default_branch: repo.default_branch.clone(),
+        size: repo.size.unwrap_or_default() as i64,
+        created_at: repo.created_at,
+        pushed_at: repo.pushed_at,
+    }
+}
+
+/// Compiles the configured excluded line patterns, skipping any that are not valid globs.
+pub(crate) fn excluded_line_globs(cfg: &AppConfig) -> Result<GlobSet> {
+    let mut builder = GlobSetBuilder::new();
+    for pattern in &cfg.excluded_line_patterns {
+        if let Ok(glob) = Glob::new(pattern) {
+            builder.add(glob);
+        }
+    }
+    Ok(builder.build()?)
+}
+
+/// Truncates a file path to its leading `depth` directories, the unit churn hotspots roll up to.
+/// Files at the repository root map to an empty directory.
+pub fn churn_directory(path: &str, depth: usize) -> String {
+    let components: Vec<&str> = path.split('/').collect();
+    let dir_components = components.len().saturating_sub(1).min(depth);
+    components[..dir_components].join("/")
+}
+
+/// Persists a raw commit record so it can be re-analyzed without re-scraping.
+pub(crate) async fn save_commit(db_conn: &mut SqliteConnection, repo: &Repo, commit: &RepoCommit, churn_path_depth: usize, excluded_globs: &GlobSet) -> Result<Commit> {
+    let author_login = match &commit.author {
+        Some(author) => author.login.clone(),
+        None => "anonymous".to_string()
+    };
+    let author = Contributor::create(db_conn, author_login).await?;
+    let committer = match &commit.committer {
+        Some(committer) => Some(Contributor::create(db_conn, committer.login.clone()).await?),
+        None => None
+    };
+
+    let (additions, deletions) = match &commit.stats {
+        Some(stats) => (stats.additions.unwrap_or_default() as i64, stats.deletions.unwrap_or_default() as i64),
+        None => (0, 0)
+    };
+    let message_summary = commit.commit.message.lines().next().unwrap_or_default().to_string();
+    let commit_type = change_type(&message_summary);
+
+    let mut db_commit = Commit::create(
+        db_conn,
+        repo.clone(),
+        commit.sha.clone(),
+        author,
+        committer,
+        commit.commit.author.as_ref().and_then(|a| a.date),
+        commit.commit.committer.as_ref().and_then(|c| c.date),
+        additions,
+        deletions,
+        message_summary,
+        commit_type,
+    ).await?;
+
+    // Only the commit detail endpoint includes files
+    for file in commit.files.iter().flatten() {
+        let db_file = CommitFile::create(
+            db_conn,
+            db_commit.id,
+            file.filename.clone(),
+            churn_directory(&file.filename, churn_path_depth),
+            file.additions as i64,
+            file.deletions as i64,
+            excluded_globs.is_match(&file.filename),
+        ).await?;
+        db_commit.files.push(db_file);
+    }
 
-        if user_ignore_regex.find(&commit_author).is_some() {
-            return Ok(());
+    Ok(db_commit)
+}
+
+/// Persists a pull request so it can be drilled into and re-analyzed later.
+pub(crate) async fn save_pull_request(db_conn: &mut SqliteConnection, repo: &Repo, pr: &GhPullRequest, files: &[DiffEntry], reviews: &[Review], excluded_globs: &GlobSet) -> Result<PullRequest> {
+    let author_login = match pr.user.clone() {
+        Some(auth) => auth.login,
+        None => "anonymous".to_string()
+    };
+    let author = Contributor::create(db_conn, author_login).await?;
+    let state = match pr.state {
+        Some(IssueState::Closed) => "closed",
+        _ => "open"
+    };
+
+    let mut db_pr = PullRequest::create(
+        db_conn,
+        repo.clone(),
+        pr.number as i64,
+        pr.title.clone().unwrap_or_default(),
+        author,
+        state.to_string(),
+        pr.created_at,
+        pr.updated_at,
+        pr.closed_at,
+        pr.merged_at,
+        pr.additions.unwrap_or_default() as i64,
+        pr.deletions.unwrap_or_default() as i64,
+        pr.changed_files.unwrap_or_default() as i64,
+        pr.base.ref_field.clone(),
+        pr.title.as_deref().and_then(change_type),
+    ).await?;
+
+    for file in files {
+        let db_file = PullRequestFile::create(
+            db_conn,
+            db_pr.id,
+            file.filename.clone(),
+            file.additions as i64,
+            file.deletions as i64,
+            excluded_globs.is_match(&file.filename),
+        ).await?;
+        db_pr.files.push(db_file);
+    }
+
+    for review in reviews {
+        let db_review = save_review(db_conn, db_pr.id, review).await?;
+        db_pr.reviews.push(db_review);
+    }
+
+    Ok(db_pr)
+}
+
+/// Persists a review submitted on a stored pull request.
+pub(crate) async fn save_review(db_conn: &mut SqliteConnection, pull_request_id: i64, review: &Review) -> Result<PullRequestReview> {
+    let reviewer_login = match &review.user {
+        Some(user) => user.login.clone(),
+        None => "anonymous".to_string()
+    };
+    let reviewer = Contributor::create(db_conn, reviewer_login).await?;
+    let state = match review.state {
+        Some(ReviewState::Approved) => "approved",
+        Some(ReviewState::ChangesRequested) => "changes_requested",
+        Some(ReviewState::Commented) => "commented",
+        Some(ReviewState::Dismissed) => "dismissed",
+        Some(ReviewState::Pending) => "pending",
+        _ => "open"
+    };
+    PullRequestReview::create(
+        db_conn,
+        pull_request_id,
+        review.id.0 as i64,
+        reviewer,
+        state.to_string(),
+        review.submitted_at,
+    ).await
+}
+
+/// A repo's releases, tags and deployments, fetched before any of them are stored
+struct DeliveryEvents {
+    releases: Vec<GhRelease>,
+    /// New or retagged tags with their commit date, only fetched for repos without releases
+    tags: Vec<(GhTag, Option<DateTime<Utc>>)>,
+    deployments: Vec<GhDeployment>,
+}
+
+/// Fetches a repo's releases and configured deployments for delivery metrics. Repos that
+/// don't publish releases fall back to their tags, dated by the tagged commit.
+async fn fetch_delivery_events(db_conn: &mut SqliteConnection, gh: &Github, org: &str, repo: &str, environments: &[String]) -> Result<DeliveryEvents> {
+    let releases = gh.get_releases(org, repo).await.unwrap_or_default();
+
+    let mut tags = vec![];
+    if releases.is_empty() {
+        let repo_id = Repo::find_id(db_conn, org, repo).await?;
+        for tag in gh.get_tags(org, repo).await.unwrap_or_default() {
+            // Tags don't move often, so only look up the commit date for new or retagged ones
+            let known = match repo_id {
+                Some(repo_id) => Tag::get_by_name(db_conn, repo_id, &tag.name).await?,
+                None => None,
+            };
+            if let Some(known) = &known
+                && known.sha == tag.commit.sha
+                && known.committed_at.is_some()
+            {
+                continue;
+            }
+            let committed_at = match gh.get_commit(org, repo, &tag.commit.sha).await {
+                Ok(commit) => commit.commit.committer.and_then(|c| c.date),
+                Err(_e) => None
+            };
+            tags.push((tag, committed_at));
         }
+    }
+
+    let mut deployments = vec![];
+    for environment in environments {
+        deployments.extend(gh.get_deployments(org, repo, environment).await.unwrap_or_default());
+    }
+
+    Ok(DeliveryEvents { releases, tags, deployments })
+}
+
+async fn save_delivery_events(db_conn: &mut SqliteConnection, repo: &Repo, events: &DeliveryEvents) -> Result<()> {
+    for release in &events.releases {
+        Release::create(
+            db_conn,
+            repo.id,
+            release.tag_name.clone(),
+            release.name.clone().unwrap_or_default(),
+            release.target_commitish.clone(),
+            release.draft,
+            release.prerelease,
+            release.created_at,
+            release.published_at,
+        ).await?;
+    }
+
+    for (tag, committed_at) in &events.tags {
+        Tag::create(db_conn, repo.id, tag.name.clone(), tag.commit.sha.clone(), *committed_at).await?;
+    }
+
+    for deployment in &events.deployments {
+        Deployment::create(
+            db_conn,
+            repo.id,
+            deployment.id as i64,
+            deployment.environment.clone(),
+            deployment.sha.clone(),
+            deployment.git_ref.clone(),
+            Some(deployment.created_at),
+        ).await?;
+    }
+
+    Ok(())
+}
+
+/// Stores the repo's Actions runs since the start of the scrape window.
+async fn save_workflow_runs(db_conn: &mut SqliteConnection, repo: &Repo, runs: &[GhWorkflowRun]) -> Result<()> {
+    for run in runs {
+        WorkflowRun::create(
+            db_conn,
+            repo.id,
+            run.id as i64,
+            run.workflow_id as i64,
+            run.name.clone().unwrap_or_default(),
+            run.head_branch.clone().unwrap_or_default(),
+            run.head_sha.clone(),
+            run.event.clone(),
+            run.status.clone().unwrap_or_default(),
+            run.conclusion.clone(),
+            run.run_attempt as i64,
+            run.run_started_at,
+            Some(run.created_at),
+            Some(run.updated_at),
+        ).await?;
+    }
+    Ok(())
+}
 
-        self.total_commits += 1;
-        
-        let contributor = self.contributors
-            .entry(commit_author.clone())
-            .or_insert_with(|| TempContributorData {
-                username: commit_author,
-                commits: 0,
-                lines: 0,
+/// Everything a scrape stores for one repo, fetched from GitHub before any of it is written
+struct RepoResults {
+    commits: Vec<RepoCommit>,
+    codeowners: Option<String>,
+    pull_requests: Vec<(GhPullRequest, Vec<DiffEntry>, Vec<Review>)>,
+    delivery: DeliveryEvents,
+    workflow_runs: Vec<GhWorkflowRun>,
+}
+
+/// How many of a repo's commits get line stats: at most `max_commit_details`, and fewer once the
+/// quota left is down to `DETAIL_QUOTA_RESERVE`, which is kept for the rest of the scrape.
+async fn commit_detail_budget(cfg: &AppConfig, gh: &Github) -> usize {
+    if cfg.max_commit_details == 0 {
+        return 0;
+    }
+    match gh.get_rate_limit().await {
+        Ok(rate) => cfg.max_commit_details.min(rate.remaining.saturating_sub(DETAIL_QUOTA_RESERVE)),
+        Err(_e) => cfg.max_commit_details,
+    }
+}
+
+/// Fetches everything a scrape stores for a repo, or `None` when it has no commits in the window.
+async fn fetch_repo_results(db_conn: &mut SqliteConnection, cfg: &AppConfig, gh: &Github, org: &str, repo: &str, range: DateRange) -> Result<Option<RepoResults>> {
+    // A failed listing fails the scrape, rather than recording the repo as having no commits
+    let commits_this_week = gh.get_repo_commits(org, repo, range.start, range.end).await?;
+
+    if commits_this_week.is_empty() {
+        return Ok(None);
+    }
+
+    // Line stats come from the detail endpoint, one request per commit. Commits past the budget
+    // are stored from the listing without them, unless an earlier scrape already stored them.
+    let detail_budget = commit_detail_budget(cfg, gh).await;
+    let repo_id = Repo::find_id(db_conn, org, repo).await?;
+    let mut commits = vec![];
+    for (i, commit) in commits_this_week.into_iter().enumerate() {
+        if i < detail_budget {
+            commits.push(match gh.get_commit(org, repo, &commit.sha).await {
+                Ok(detail) => detail,
+                Err(_e) => commit
             });
-        contributor.commits += 1;
+            continue;
+        }
+        let stored = match repo_id {
+            Some(repo_id) => Commit::find_id(db_conn, repo_id, &commit.sha).await?.is_some(),
+            None => false,
+        };
+        if !stored {
+            commits.push(commit);
+        }
+    }
 
-        Ok(())
+    let codeowners = gh.get_codeowners(org, repo).await.ok().flatten();
+
+    let mut pull_requests = vec![];
+    for pr in gh.get_repo_prs(org, repo, range.start, range.end).await? {
+        let pr_files = gh.get_pr_files(org, repo, pr.number)
+            .await
+            .unwrap_or_default();
+        let pr_reviews = gh.get_pr_reviews(org, repo, pr.number)
+            .await
+            .unwrap_or_default();
+        pull_requests.push((pr, pr_files, pr_reviews));
+    }
+
+    Ok(Some(RepoResults {
+        commits,
+        codeowners,
+        pull_requests,
+        delivery: fetch_delivery_events(db_conn, gh, org, repo, &cfg.deployment_environments).await?,
+        workflow_runs: gh.get_workflow_runs(org, repo, range.start, range.end).await.unwrap_or_default(),
+    }))
+}
+
+/// Writes a repo's fetched results and its counters under the scrape. Callers run this in a
+/// transaction, so a repo is either fully recorded or not at all.
+async fn save_repo_results(
+    db_conn: &mut SqliteConnection,
+    cfg: &AppConfig,
+    scrape: &Scrape,
+    repo: &Repo,
+    results: &RepoResults,
+    excluded_globs: &GlobSet,
+    user_ignore_regex: &Regex,
+) -> Result<()> {
+    for commit in &results.commits {
+        save_commit(db_conn, repo, commit, cfg.churn_path_depth, excluded_globs).await?;
+    }
+    if let Some(content) = &results.codeowners {
+        repo.save_codeowners(db_conn, &CodeOwners::parse(content)).await?;
+    }
+    for (pr, files, reviews) in &results.pull_requests {
+        save_pull_request(db_conn, repo, pr, files, reviews, excluded_globs).await?;
     }
+    save_delivery_events(db_conn, repo, &results.delivery).await?;
+    save_workflow_runs(db_conn, repo, &results.workflow_runs).await?;
+
+    record_repo_scrape(db_conn, scrape, repo, user_ignore_regex).await?;
+    Ok(())
+}
+
+/// Derives a repo's commit, PR and line counters for a window from the stored commits and
+/// pull requests, and records them under the given scrape.
+pub(crate) async fn record_repo_scrape(
+    db_conn: &mut SqliteConnection,
+    scrape: &Scrape,
+    repo: &Repo,
+    user_ignore_regex: &Regex,
+) -> Result<RepoScrape> {
+    let mut temp_repo_scrape = TempRepoScrape::new(&repo.org.name, &repo.name);
+
+    let commit_counts = Commit::count_by_author(db_conn, repo.id, scrape.start_dt, scrape.end_dt).await?;
+    temp_repo_scrape.process_commit_counts(commit_counts, user_ignore_regex);
+
+    let pr_totals = PullRequest::totals_by_author(db_conn, repo.id, scrape.start_dt, scrape.end_dt).await?;
+    temp_repo_scrape.process_pr_totals(pr_totals, user_ignore_regex);
+
+    let repo_scrape = RepoScrape::create(
+        db_conn,
+        scrape.id,
+        repo.org.clone(),
+        repo.clone(),
+        temp_repo_scrape.total_commits,
+        temp_repo_scrape.total_prs,
+        temp_repo_scrape.total_lines,
+        temp_repo_scrape.total_excluded_lines,
+    ).await?;
+
+    for (_username, temp_contributor) in temp_repo_scrape.contributors {
+        let db_contributor = Contributor::create(db_conn, temp_contributor.username).await?;
+        let _contributor_scrape = ContributorScrapes::create(
+            db_conn,
+            repo_scrape.id,
+            db_contributor,
+            temp_contributor.commits,
+            temp_contributor.lines,
+            temp_contributor.excluded_lines,
+        ).await?;
+    }
+
+    Ok(repo_scrape)
+}
+
+/// Decides which orgs and repos a scrape covers, from config and an optional profile.
+/// Dry-run plans use the same rules, so they count exactly the repos a scrape would visit.
+pub(crate) struct ScrapeFilter {
+    org_ignore_regex: Regex,
+    profile_orgs: Vec<String>,
+    /// Profile `org/repo` globs; `None` when every repo is scraped
+    profile_repos: Option<GlobSet>,
+}
 
-    fn process_pr(&mut self, pr: &octocrab::models::pulls::PullRequest, user_ignore_regex: &Regex) -> Result<()> {
-        let author = match pr.user.clone() {
-            Some(auth) => auth.login,
-            None => "anonymous".to_string()
+impl ScrapeFilter {
+    pub(crate) fn new(cfg: &AppConfig, profile: Option<&ScrapeProfile>) -> Result<ScrapeFilter> {
+        let profile_repos = match profile.filter(|p| !p.repos.is_empty()) {
+            Some(profile) => {
+                let mut builder = GlobSetBuilder::new();
+                for pattern in &profile.repos {
+                    builder.add(GlobBuilder::new(pattern).literal_separator(true).build()?);
+                }
+                Some(builder.build()?)
+            }
+            None => None,
         };
+        Ok(ScrapeFilter {
+            org_ignore_regex: Regex::new(&cfg.ignored_org_pattern)?,
+            profile_orgs: profile.map(|p| p.organizations.clone()).unwrap_or_default(),
+            profile_repos,
+        })
+    }
 
-        if user_ignore_regex.find(&author).is_some() {
-            return Ok(());
+    pub(crate) fn includes_org(&self, org: &str) -> bool {
+        if self.org_ignore_regex.find(org).is_some() {
+            return false;
         }
+        self.profile_orgs.is_empty() || self.profile_orgs.iter().any(|o| o == org)
+    }
 
-        let contributor = self.contributors
-            .entry(author.clone())
-            .or_insert_with(|| TempContributorData {
-                username: author,
-                commits: 0,
-                lines: 0,
-            });
+    pub(crate) fn includes_repo(&self, cfg: &AppConfig, org: &str, repo: &Repository) -> bool {
+        self.includes(cfg, org, &repo.name, &repo_metadata(repo))
+    }
 
-        let line_count = pr.additions.unwrap_or_default() + pr.deletions.unwrap_or_default();
-        contributor.lines += line_count as i64;
+    /// Same as `includes_repo`, for a repo already in the database
+    pub(crate) fn includes_stored_repo(&self, cfg: &AppConfig, repo: &Repo) -> bool {
+        self.includes_org(&repo.org.name) && self.includes(cfg, &repo.org.name, &repo.name, &repo.metadata)
+    }
 
-        self.total_prs += 1;
-        self.total_lines += line_count as i64;
-        Ok(())
+    fn includes(&self, cfg: &AppConfig, org: &str, name: &str, metadata: &RepoMetadata) -> bool {
+        if is_repo_excluded(cfg, metadata) {
+            return false;
+        }
+        match &self.profile_repos {
+            Some(globs) => globs.is_match(format!("{org}/{name}")),
+            None => true,
+        }
     }
 }
 
-/// Runs a complete scrape of GitHub organizations and repositories.
+/// Runs a complete scrape of GitHub organizations and repositories over the given window,
+/// limited to a profile's orgs and repos when one is given, returning the new scrape's id.
 /// This function runs silently to avoid interfering with TUI display.
-pub async fn run_scrape() -> Result<()> {
-    let db_pool = new_pool().await?;
-    migrate!().run(&db_pool).await?;
-    let cfg = get_config().unwrap();
-    let github_token: String = env::var("GITHUB_TOKEN")?;
-    let gh = Github::new(&github_token);
+pub async fn run_scrape(db_pool: &SqlitePool, range: DateRange, profile: Option<&ScrapeProfile>) -> Result<i64> {
+    // Held until the scrape finishes, so a scheduled scrape and one started from the TUI can't overlap
+    let _scrape_lock = LockFile::acquire(scrape_lock_path())
+        .map_err(|e| anyhow!("A scrape is already running: {e}"))?;
+    // With the lock held, any scrape still marked running was cut short
+    Scrape::fail_interrupted(&mut *db_pool.acquire().await?).await?;
+    let cfg = get_config()?;
+    let gh = Github::from_env(&cfg, db_pool)?;
+
+    Ok(scrape_into(db_pool, &cfg, &gh, range, profile).await?.id)
+}
 
+/// Marks scrapes cut short by a stopped or crashed process as failed, returning how many there
+/// were. Does nothing while another process is scraping, since its scrape is still being written.
+pub async fn fail_interrupted_scrapes(db_pool: &SqlitePool) -> Result<u64> {
+    let Ok(_scrape_lock) = LockFile::acquire(scrape_lock_path()) else {
+        return Ok(0);
+    };
+    Scrape::fail_interrupted(&mut *db_pool.acquire().await?).await
+}
+
+/// Scrapes into the given database with the given client, returning the new scrape. `run_scrape`
+/// wraps this with the scrape lock, and regression tests run it against replayed API traffic.
+///
+/// Each repo's results are written in their own transaction once they've all been fetched, and
+/// the scrape is only marked complete, and so shown to viewers, after every repo is recorded.
+pub async fn scrape_into(db_pool: &SqlitePool, cfg: &AppConfig, gh: &Github, range: DateRange, profile: Option<&ScrapeProfile>) -> Result<Scrape> {
     let orgs = gh.get_orgs().await?;
-    let org_ignore_regex = Regex::new(&cfg.ignored_org_pattern.to_string())?;
-    let user_ignore_regex = Regex::new(&cfg.ignored_user_patterns.to_string())?;
+    let filter = ScrapeFilter::new(cfg, profile)?;
+    let user_patterns = profile
+        .and_then(|p| p.ignored_user_patterns.clone())
+        .unwrap_or_else(|| cfg.ignored_user_patterns.clone());
+    let user_ignore_regex = Regex::new(&user_patterns)?;
+    let excluded_globs = excluded_line_globs(cfg)?;
+    let teams = cfg.configured_teams()?;
     
     // Create a new scrape session
-    let start_time = Utc::now() - Duration::days(7);
-    let end_time = Utc::now();
+    let start_time = range.start;
+    let end_time = range.end;
     
     let mut db_conn = db_pool.acquire().await?;
-    let scrape = Scrape::create(&mut db_conn, start_time, end_time).await?;
+    let mut scrape = Scrape::create(&mut db_conn, start_time, end_time, profile.map(|p| p.name.clone()), ScrapeStatus::Running).await?;
     
-    for org in orgs {
-        if org_ignore_regex.find(&org.organization.login).is_some() {
-            continue;
-        }
+    let result: Result<()> = async {
+        for org in orgs {
+            let login = &org.organization.login;
+            if !filter.includes_org(login) {
+                continue;
+            }
 
-        // Create or get org from database
-        let db_org = Org::create(&mut db_conn, org.organization.login.clone()).await?;
-
-        let mut results_count = 50;
-        let mut page: u32 = 1;
-        
-        while results_count == 50 {
-            let repos = gh.get_org_repos_by_page(&org.organization.login, &results_count, &page).await?;
-            results_count = 0;
-            
-            for repo in repos {
-                results_count += 1;
-                
-                // Create or get repo from database
-                let db_repo = Repo::create(&mut db_conn, repo.name.clone(), db_org.clone()).await?;
-                let mut temp_repo_scrape = TempRepoScrape::new(&org.organization.login, &repo.name);
-
-                let commits_this_week = match gh.get_repo_commits(&org.organization.login, &repo.name, start_time).await {
-                    Ok(val) => val,
-                    Err(_e) => continue
-                };
-
-                // Process each commit for the week in the repo
-                let mut commit_counter = 0;
-                for commit in commits_this_week {
-                    commit_counter += 1;
-                    temp_repo_scrape.process_commit(&commit, &user_ignore_regex)?;
+            // Team listing needs `read:org`; scrape repos regardless when it's unavailable
+            let mut teams = vec![];
+            if let Ok(org_teams) = gh.get_org_teams(login).await {
+                for team in org_teams {
+                    let members = gh.get_team_members(login, &team.slug)
+                        .await
+                        .unwrap_or_default();
+                    teams.push((team, members));
                 }
-                if commit_counter == 0 {
-                    continue;
+            }
+
+            let mut tx = db_conn.begin_with("BEGIN IMMEDIATE").await?;
+            let db_org = Org::create(&mut tx, login.clone()).await?;
+            for (team, members) in teams {
+                let mut db_team = Team::create(&mut tx, db_org.clone(), team.slug, team.name).await?;
+                for member in members {
+                    db_team.members.push(Contributor::create(&mut tx, member.login).await?);
                 }
+                db_team.save(&mut tx).await?;
+            }
+            tx.commit().await?;
 
-                let repo_prs = gh.get_repo_prs(&org.organization.login, &repo.name, start_time).await?;
-                for pr in repo_prs {
-                    temp_repo_scrape.process_pr(&pr, &user_ignore_regex)?;
+            for repo in gh.get_org_repos(login).await? {
+                if !filter.includes_repo(cfg, login, &repo) {
+                    continue;
                 }
 
-                // Save repo scrape to database
-                let repo_scrape = RepoScrape::create(
-                    &mut db_conn,
-                    scrape.id,
-                    db_org.clone(),
-                    db_repo,
-                    temp_repo_scrape.total_commits,
-                    temp_repo_scrape.total_prs,
-                    temp_repo_scrape.total_lines,
-                ).await?;
-
-                // Save contributor data
-                for (_username, temp_contributor) in temp_repo_scrape.contributors {
-                    let db_contributor = Contributor::create(&mut db_conn, temp_contributor.username).await?;
-                    let _contributor_scrape = ContributorScrapes::create(
-                        &mut db_conn,
-                        repo_scrape.id,
-                        db_contributor,
-                        temp_contributor.commits,
-                        temp_contributor.lines,
-                    ).await?;
+                // Repos without commits in the window only have their metadata refreshed
+                let results = fetch_repo_results(&mut db_conn, cfg, gh, login, &repo.name, range).await?;
+
+                // Taking the write lock up front keeps the transaction short and avoids lock
+                // upgrade failures when the webhook receiver or another process writes at the same time.
+                // The transaction rolls back if it's dropped, e.g. when the daemon stops mid-scrape.
+                let mut tx = db_conn.begin_with("BEGIN IMMEDIATE").await?;
+                let mut db_repo = Repo::create(&mut tx, repo.name.clone(), db_org.clone()).await?;
+                db_repo.metadata = repo_metadata(&repo);
+                db_repo.save(&mut tx).await?;
+                if let Some(results) = &results {
+                    save_repo_results(&mut tx, cfg, &scrape, &db_repo, results, &excluded_globs, &user_ignore_regex).await?;
                 }
+                tx.commit().await?;
 
                 // Repo processing completed silently to avoid TUI interference
             }
-            page += 1;
         }
-    }
-    
+
+        // Repo groups pick up repos this scrape saw for the first time
+        sync_config_groups(&mut db_conn, &teams, &cfg.repo_groups).await?;
+        Ok(())
+    }.await;
+
     // Scrape completed silently to avoid TUI interference
-    Ok(())
-}
\ No newline at end of file
+    match result {
+        Ok(()) => {
+            scrape.set_status(&mut db_conn, ScrapeStatus::Complete).await?;
+            Ok(scrape)
+        }
+        Err(e) => {
+            // Running scrapes are hidden too, so the scrape stays out of view even if this fails
+            let _ = scrape.set_status(&mut db_conn, ScrapeStatus::Failed).await;
+            Err(e)
+        }
+    }
+}
@@ -1,3 +1,5 @@
+use std::collections::BTreeMap;
+
 use chrono::{DateTime, Utc};
 
 #[derive(Debug, Clone)]
@@ -7,6 +9,9 @@ pub struct OrgStats {
     pub total_lines: i64,
     pub repo_count: i64,
     pub contributor_count: i64,
+    pub excluded_lines: i64,
+    pub delivery: DeliveryMetrics,
+    pub change_mix: ChangeMix,
 }
 
 #[derive(Debug, Clone)]
@@ -17,6 +22,133 @@ pub struct RepoStats {
     pub lines: i64,
     pub prs: i64,
     pub contributor_count: i64,
+    pub language: Option<String>,
+    pub topics: Vec<String>,
+    pub fork: bool,
+    pub archived: bool,
+    pub excluded_lines: i64,
+    pub delivery: DeliveryMetrics,
+    pub ci: CiHealth,
+    pub change_mix: ChangeMix,
+}
+
+/// Commits in a scrape window by Conventional Commit type
+#[derive(Debug, Clone, Default)]
+pub struct ChangeMix {
+    pub counts: BTreeMap<String, i64>,
+    /// Commits whose summary doesn't follow the convention
+    pub unclassified: i64,
+}
+
+impl ChangeMix {
+    pub fn add(&mut self, change_type: Option<&str>, commits: i64) {
+        match change_type {
+            Some(change_type) => *self.counts.entry(change_type.to_string()).or_default() += commits,
+            None => self.unclassified += commits,
+        }
+    }
+
+    pub fn total(&self) -> i64 {
+        self.counts.values().sum::<i64>() + self.unclassified
+    }
+
+    /// Share of all commits, unclassified ones included, that have the given type
+    pub fn share(&self, change_type: &str) -> Option<f64> {
+        let total = self.total();
+        if total == 0 {
+            return None;
+        }
+        Some(self.counts.get(change_type).copied().unwrap_or_default() as f64 / total as f64)
+    }
+}
+
+/// GitHub Actions health for a scrape window
+#[derive(Debug, Clone, Default)]
+pub struct CiHealth {
+    pub runs: i64,
+    /// Completed runs that passed or failed; cancelled, skipped and neutral runs don't count
+    pub decided_runs: i64,
+    pub successful_runs: i64,
+    /// Runs that only passed after being re-run
+    pub flaky_reruns: i64,
+    pub median_duration_secs: Option<f64>,
+}
+
+impl CiHealth {
+    /// Builds health stats from `(conclusion, run_attempt, duration_secs)` per run.
+    pub fn from_runs<'a>(runs: impl IntoIterator<Item = (Option<&'a str>, i64, Option<f64>)>) -> CiHealth {
+        let mut health = CiHealth::default();
+        let mut durations = vec![];
+
+        for (conclusion, run_attempt, duration_secs) in runs {
+            health.runs += 1;
+            match conclusion {
+                Some("success") => {
+                    health.decided_runs += 1;
+                    health.successful_runs += 1;
+                    if run_attempt > 1 {
+                        health.flaky_reruns += 1;
+                    }
+                }
+                Some("failure" | "timed_out" | "startup_failure") => health.decided_runs += 1,
+                _ => {}
+            }
+            if conclusion.is_some()
+                && let Some(duration_secs) = duration_secs
+            {
+                durations.push(duration_secs);
+            }
+        }
+
+        health.median_duration_secs = median(durations);
+        health
+    }
+
+    pub fn success_rate(&self) -> Option<f64> {
+        if self.decided_runs == 0 {
+            return None;
+        }
+        Some(self.successful_runs as f64 / self.decided_runs as f64)
+    }
+}
+
+pub(crate) fn median(mut values: Vec<f64>) -> Option<f64> {
+    values.sort_by(|a, b| a.total_cmp(b));
+    match values.len() {
+        0 => None,
+        n if n % 2 == 1 => Some(values[n / 2]),
+        n => Some((values[n / 2 - 1] + values[n / 2]) / 2.0),
+    }
+}
+
+/// Repos that record deployments are measured by them; the rest fall back to releases.
+pub fn deploy_events(releases: i64, deployments: i64) -> i64 {
+    if deployments > 0 { deployments } else { releases }
+}
+
+/// DORA-style delivery metrics for a scrape window
+#[derive(Debug, Clone, Default)]
+pub struct DeliveryMetrics {
+    /// Published releases, plus tags without a release
+    pub releases: i64,
+    pub deployments: i64,
+    /// Deployments per week, falling back to releases when no deployments were collected
+    pub deploys_per_week: f64,
+    /// Median hours from a PR merging to the first release that follows it
+    pub lead_time_hours: Option<f64>,
+}
+
+impl DeliveryMetrics {
+    /// `deploy_events` is what counts as a deploy, i.e. deployments for repos that record
+    /// them and releases for those that don't.
+    pub fn new(releases: i64, deployments: i64, deploy_events: i64, weeks: f64, lead_times: Vec<f64>) -> DeliveryMetrics {
+        DeliveryMetrics {
+            releases,
+            deployments,
+            deploys_per_week: deploy_events as f64 / weeks,
+            lead_time_hours: median(lead_times),
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -25,7 +157,9 @@ pub struct ContributorStats {
     pub total_commits: i64,
     pub total_lines: i64,
     pub repo_count: i64,
+    pub excluded_lines: i64,
     pub orgs: Vec<String>,
+    pub change_mix: ChangeMix,
 }
 
 #[derive(Debug, Clone)]
@@ -33,6 +167,7 @@ pub struct ScrapeInfo {
     pub id: i64,
     pub start_dt: DateTime<Utc>,
     pub end_dt: DateTime<Utc>,
+    pub profile: Option<String>,
     pub repo_count: i64,
 }
 
@@ -48,6 +183,32 @@ pub struct RepoDetail {
     pub org_name: String,
     pub repo_name: String,
     pub contributors: Vec<RepoContributor>,
+    pub ownership: OwnershipCoverage,
+}
+
+/// CODEOWNERS coverage of a repo's churn within a scrape window
+#[derive(Debug, Clone, Default)]
+pub struct OwnershipCoverage {
+    pub has_codeowners: bool,
+    pub total_lines: i64,
+    /// Lines changed in paths with at least one owner
+    pub owned_lines: i64,
+    /// Owned lines whose commit author is one of the path's owners
+    pub owner_authored_lines: i64,
+    /// Merged PRs touching owned paths
+    pub owned_prs: i64,
+    /// Owned PRs reviewed by at least one of their owners
+    pub owner_reviewed_prs: i64,
+    pub owners: Vec<OwnerCoverage>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct OwnerCoverage {
+    pub owner: String,
+    pub owned_lines: i64,
+    pub authored_lines: i64,
+    pub owned_prs: i64,
+    pub reviewed_prs: i64,
 }
 
 #[derive(Debug, Clone)]
@@ -62,6 +223,21 @@ pub struct RepoContributor {
 pub struct ContributorDetail {
     pub username: String,
     pub contributions: Vec<ContributorRepo>,
+    /// Only loaded when `activity_timing` is enabled in config
+    pub timing: Option<ActivityTiming>,
+}
+
+/// When a contributor's commits in a scrape window were authored, in their local time
+#[derive(Debug, Clone, Default)]
+pub struct ActivityTiming {
+    /// UTC offset the hours are in, e.g. `+02:00`
+    pub timezone: String,
+    pub by_hour: [i64; 24],
+    /// Monday first
+    pub by_weekday: [i64; 7],
+    /// Commits on weekends or outside working hours
+    pub outside_hours: i64,
+    pub total: i64,
 }
 
 #[derive(Debug, Clone)]
@@ -73,6 +249,66 @@ pub struct ContributorRepo {
     pub prs: i64,
 }
 
+#[derive(Debug, Clone)]
+pub struct RepoHotspots {
+    pub org_name: String,
+    pub repo_name: String,
+    pub paths: Vec<PathChurn>,
+}
+
+#[derive(Debug, Clone)]
+pub struct PathChurn {
+    pub path: String,
+    pub is_directory: bool,
+    pub additions: i64,
+    pub deletions: i64,
+    pub commits: i64,
+    pub top_contributors: Vec<String>,
+}
+
+/// Totals for a GitHub team, summed over its current members
+#[derive(Debug, Clone, Default)]
+pub struct TeamStats {
+    pub org_name: String,
+    pub team_slug: String,
+    pub team_name: String,
+    pub member_count: i64,
+    pub commits: i64,
+    pub lines: i64,
+    pub prs: i64,
+    pub reviews: i64,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct TeamDetail {
+    pub org_name: String,
+    pub team_slug: String,
+    pub team_name: String,
+    pub members: Vec<TeamMember>,
+}
+
+#[derive(Debug, Clone)]
+pub struct TeamMember {
+    pub username: String,
+    pub commits: i64,
+    pub lines: i64,
+    pub prs: i64,
+    pub reviews: i64,
+}
+
+/// Totals for a team or repo group defined in config
+#[derive(Debug, Clone)]
+pub struct GroupStats {
+    /// `team` or `repos`
+    pub kind: String,
+    pub name: String,
+    /// Members for teams, repositories for repo groups
+    pub size: i64,
+    pub commits: i64,
+    pub lines: i64,
+    pub prs: i64,
+}
+
 #[derive(Debug, Clone)]
 pub enum ViewData {
     Orgs(Vec<OrgStats>),
@@ -81,6 +317,10 @@ pub enum ViewData {
     OrgDetail(OrgDetail),
     RepoDetail(RepoDetail),
     ContributorDetail(ContributorDetail),
+    RepoHotspots(RepoHotspots),
+    Teams(Vec<TeamStats>),
+    TeamDetail(TeamDetail),
+    Groups(Vec<GroupStats>),
     Loading,
     Error(String),
 }
\ No newline at end of file
@@ -0,0 +1,65 @@
+//! Hour-of-day and weekday breakdowns of when contributors author commits.
+//!
+//! GitHub reports commit dates in UTC, so each contributor's local time comes from a
+//! configured UTC offset. Offsets are fixed, which means daylight saving shifts aren't followed.
+
+use chrono::{DateTime, Datelike, FixedOffset, Timelike, Utc};
+
+use crate::config::AppConfig;
+use crate::stats::ActivityTiming;
+
+/// Parses `UTC`, `Z` or an offset such as `+02:00`, `-0530` or `+9`.
+pub fn parse_utc_offset(value: &str) -> Option<FixedOffset> {
+    let value = value.trim();
+    if value.eq_ignore_ascii_case("utc") || value.eq_ignore_ascii_case("z") {
+        return FixedOffset::east_opt(0);
+    }
+
+    let (sign, rest) = match value.split_at_checked(1)? {
+        ("+", rest) => (1, rest),
+        ("-", rest) => (-1, rest),
+        _ => return None,
+    };
+    let (hours, minutes) = match rest.split_once(':') {
+        Some((hours, minutes)) => (hours, minutes),
+        None if rest.len() == 4 => rest.split_at(2),
+        None => (rest, "0"),
+    };
+    let hours: i32 = hours.parse().ok()?;
+    let minutes: i32 = minutes.parse().ok()?;
+    if !(0..=14).contains(&hours) || !(0..60).contains(&minutes) {
+        return None;
+    }
+    FixedOffset::east_opt(sign * (hours * 3600 + minutes * 60))
+}
+
+/// The configured UTC offset for a contributor, falling back to `default_timezone` and then UTC.
+pub fn contributor_offset(cfg: &AppConfig, username: &str) -> FixedOffset {
+    cfg.contributor_timezones.get(username)
+        .and_then(|tz| parse_utc_offset(tz))
+        .or_else(|| parse_utc_offset(&cfg.default_timezone))
+        .unwrap_or(FixedOffset::east_opt(0).expect("zero offset is valid"))
+}
+
+/// Buckets commit times by local hour and weekday. Commits on weekends or outside
+/// `[work_start, work_end)` local hours count as outside working hours.
+pub fn activity_timing(times: &[DateTime<Utc>], offset: FixedOffset, work_start: u32, work_end: u32) -> ActivityTiming {
+    let mut timing = ActivityTiming {
+        timezone: offset.to_string(),
+        ..Default::default()
+    };
+
+    for time in times {
+        let local = time.with_timezone(&offset);
+        let hour = local.hour();
+        let weekday = local.weekday().num_days_from_monday();
+        timing.by_hour[hour as usize] += 1;
+        timing.by_weekday[weekday as usize] += 1;
+        timing.total += 1;
+        if weekday >= 5 || hour < work_start || hour >= work_end {
+            timing.outside_hours += 1;
+        }
+    }
+
+    timing
+}
@@ -0,0 +1,475 @@
+//! Webhook receiver that writes GitHub `push`, `pull_request` and `pull_request_review`
+//! deliveries straight into the database, so current-week stats stay fresh between scrapes.
+//!
+//! Every delivery must carry a valid `X-Hub-Signature-256` HMAC of its body. The receiver
+//! keeps a live scrape covering the last week under the `webhook` profile, and rebuilds the
+//! touched repo's counters from stored data after each event.
+
+use std::{env, sync::Arc, time::Duration as StdDuration};
+
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Duration, Utc};
+use globset::GlobSet;
+use hmac::{Hmac, Mac};
+use octocrab::models::{webhook_events::{WebhookEvent, WebhookEventPayload}, Repository};
+use regex::Regex;
+use sha2::Sha256;
+use sqlx::{Connection, SqliteConnection, SqlitePool};
+use tokio::{io::{AsyncReadExt, AsyncWriteExt}, net::{TcpListener, TcpStream}, sync::Mutex, time::{timeout_at, Instant}};
+
+use crate::{
+    config::{get_config, AppConfig},
+    conventional::change_type,
+    db::{Commit, Contributor, Org, PullRequest, Repo, Scrape, ScrapeStatus},
+    github::Github,
+    lock::{serve_lock_path, LockFile},
+    scraper::{excluded_line_globs, record_repo_scrape, save_commit, save_pull_request, save_review, ScrapeFilter},
+};
+
+pub const EVENT_HEADER: &str = "x-github-event";
+pub const SIGNATURE_HEADER: &str = "x-hub-signature-256";
+
+const MAX_HEADER_BYTES: usize = 64 * 1024;
+/// GitHub caps webhook payloads at 25 MB
+const MAX_BODY_BYTES: usize = 25 * 1024 * 1024;
+const LIVE_WINDOW_DAYS: i64 = 7;
+/// Repos without deliveries keep counters for a window up to this old before they're rebuilt
+const FULL_REBUILD_MINUTES: i64 = 60;
+/// A client gets this long to send its whole request
+const READ_TIMEOUT_SECS: u64 = 30;
+
+/// Profile name the live scrape is stored under, so each `org-pulse serve` picks up the same scrape
+pub const LIVE_PROFILE: &str = "webhook";
+
+/// Checks a `sha256=<hex>` signature header against the HMAC of the body, in constant time.
+pub fn verify_signature(secret: &[u8], body: &[u8], signature: &str) -> bool {
+    let Some(expected) = signature.strip_prefix("sha256=").and_then(|digest| hex::decode(digest).ok()) else {
+        return false;
+    };
+    let Ok(mut mac) = Hmac::<Sha256>::new_from_slice(secret) else {
+        return false;
+    };
+    mac.update(body);
+    mac.verify_slice(&expected).is_ok()
+}
+
+/// Signs a body the way GitHub does, for replaying saved payloads.
+pub fn sign(secret: &[u8], body: &[u8]) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret).expect("HMAC accepts keys of any length");
+    mac.update(body);
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+/// What a delivery changed in the database
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Ingested {
+    Pong,
+    Commits(usize),
+    PullRequest(i64),
+    Review(i64),
+    Ignored(String),
+}
+
+pub struct WebhookReceiver {
+    pool: SqlitePool,
+    secret: Vec<u8>,
+    /// Used to fetch line stats and files the payloads leave out. Without it, only payload fields are stored.
+    gh: Option<Github>,
+    cfg: AppConfig,
+    /// Deliveries for orgs and repos a scrape would skip are ignored
+    filter: ScrapeFilter,
+    excluded_globs: GlobSet,
+    churn_path_depth: usize,
+    user_ignore_regex: Regex,
+    /// Held while writing, so deliveries are stored one at a time
+    live_scrape: Mutex<LiveScrape>,
+}
+
+/// The scrape deliveries are recorded under, and when every repo's counters were last rebuilt
+struct LiveScrape {
+    scrape: Scrape,
+    rebuilt_at: DateTime<Utc>,
+}
+
+/// The repo a delivery belongs to, taken from its payload
+struct EventRepo {
+    org: String,
+    name: String,
+    default_branch: String,
+}
+
+impl WebhookReceiver {
+    /// Picks up the live scrape from earlier runs, or creates it, and rebuilds its counters from
+    /// already stored data. It stays hidden as running until the rebuild is done.
+    pub async fn new(pool: SqlitePool, cfg: &AppConfig, secret: &str, gh: Option<Github>) -> Result<WebhookReceiver> {
+        if secret.is_empty() {
+            return Err(anyhow!("A webhook secret is required to verify deliveries"));
+        }
+
+        let mut db_conn = pool.acquire().await?;
+        let scrape = match Scrape::find_by_profile(&mut db_conn, LIVE_PROFILE).await? {
+            Some(mut scrape) => {
+                scrape.set_status(&mut db_conn, ScrapeStatus::Running).await?;
+                scrape
+            }
+            None => {
+                let now = Utc::now();
+                Scrape::create(&mut db_conn, now - Duration::days(LIVE_WINDOW_DAYS), now, Some(LIVE_PROFILE.to_string()), ScrapeStatus::Running).await?
+            }
+        };
+
+        let receiver = WebhookReceiver {
+            pool: pool.clone(),
+            secret: secret.as_bytes().to_vec(),
+            gh,
+            cfg: cfg.clone(),
+            filter: ScrapeFilter::new(cfg, None)?,
+            excluded_globs: excluded_line_globs(cfg)?,
+            churn_path_depth: cfg.churn_path_depth,
+            user_ignore_regex: Regex::new(&cfg.ignored_user_patterns)?,
+            live_scrape: Mutex::new(LiveScrape { scrape, rebuilt_at: Utc::now() }),
+        };
+        {
+            let mut live = receiver.live_scrape.lock().await;
+            let mut tx = db_conn.begin_with("BEGIN IMMEDIATE").await?;
+            receiver.rebuild_live_scrape(&mut tx, &mut live).await?;
+            tx.commit().await?;
+        }
+
+        Ok(receiver)
+    }
+
+    /// Id of the scrape deliveries are recorded under
+    pub async fn live_scrape_id(&self) -> i64 {
+        self.live_scrape.lock().await.scrape.id
+    }
+
+    /// Verifies a delivery's signature, then stores the event it carries.
+    pub async fn receive(&self, event_name: &str, signature: Option<&str>, body: &[u8]) -> Result<Ingested, WebhookError> {
+        match signature {
+            Some(signature) if verify_signature(&self.secret, body, signature) => {}
+            _ => return Err(WebhookError::Unauthorized),
+        }
+        let event = WebhookEvent::try_from_header_and_body(event_name, body)
+            .map_err(|e| WebhookError::BadRequest(format!("Invalid {event_name} payload: {e}")))?;
+        self.ingest(event_name, event).await.map_err(WebhookError::Internal)
+    }
+
+    /// Fetches whatever the payload leaves out from GitHub first, then writes the event and the
+    /// touched repo's counters in one transaction.
+    async fn ingest(&self, event_name: &str, event: WebhookEvent) -> Result<Ingested> {
+        match event.specific {
+            WebhookEventPayload::Ping(_) => Ok(Ingested::Pong),
+            WebhookEventPayload::Push(push) => {
+                let event_repo = match self.event_repo(&event.repository) {
+                    Ok(event_repo) => event_repo,
+                    Err(reason) => return Ok(Ingested::Ignored(format!("push {reason}"))),
+                };
+                // Scrapes only count commits on the default branch
+                if push.r#ref != format!("refs/heads/{}", event_repo.default_branch) {
+                    return Ok(Ingested::Ignored(format!("push to {}", push.r#ref)));
+                }
+
+                let mut details = vec![];
+                for commit in &push.commits {
+                    details.push(match &self.gh {
+                        Some(gh) => gh.get_commit(&event_repo.org, &event_repo.name, &commit.id).await.ok(),
+                        None => None,
+                    });
+                }
+
+                let mut live = self.live_scrape.lock().await;
+                let mut db_conn = self.pool.acquire().await?;
+                let mut tx = db_conn.begin_with("BEGIN IMMEDIATE").await?;
+                let repo = store_repo(&mut tx, &event_repo).await?;
+                for (commit, detail) in push.commits.iter().zip(details) {
+                    if let Some(detail) = detail {
+                        save_commit(&mut tx, &repo, &detail, self.churn_path_depth, &self.excluded_globs).await?;
+                        continue;
+                    }
+
+                    // Payloads have no line stats, so don't overwrite a commit a scrape already stored
+                    if Commit::find_id(&mut tx, repo.id, &commit.id).await?.is_some() {
+                        continue;
+                    }
+                    let author_login = commit.author.username.clone().unwrap_or_else(|| "anonymous".to_string());
+                    let author = Contributor::create(&mut tx, author_login).await?;
+                    let committer = match &commit.committer.username {
+                        Some(username) => Some(Contributor::create(&mut tx, username.clone()).await?),
+                        None => None,
+                    };
+                    let message_summary = commit.message.lines().next().unwrap_or_default().to_string();
+                    let commit_type = change_type(&message_summary);
+                    Commit::create(
+                        &mut tx,
+                        repo.clone(),
+                        commit.id.clone(),
+                        author,
+                        committer,
+                        Some(commit.timestamp),
+                        Some(commit.timestamp),
+                        0,
+                        0,
+                        message_summary,
+                        commit_type,
+                    ).await?;
+                }
+                self.refresh_live_scrape(&mut tx, &mut live, &repo).await?;
+                tx.commit().await?;
+                Ok(Ingested::Commits(push.commits.len()))
+            }
+            WebhookEventPayload::PullRequest(payload) => {
+                let event_repo = match self.event_repo(&event.repository) {
+                    Ok(event_repo) => event_repo,
+                    Err(reason) => return Ok(Ingested::Ignored(format!("pull_request {reason}"))),
+                };
+                let pr = &payload.pull_request;
+                if pr.base.ref_field != event_repo.default_branch {
+                    return Ok(Ingested::Ignored(format!("pull request into {}", pr.base.ref_field)));
+                }
+
+                let files = match (&self.gh, pr.merged_at) {
+                    (Some(gh), Some(_)) => gh.get_pr_files(&event_repo.org, &event_repo.name, pr.number).await.unwrap_or_default(),
+                    _ => vec![],
+                };
+
+                let mut live = self.live_scrape.lock().await;
+                let mut db_conn = self.pool.acquire().await?;
+                let mut tx = db_conn.begin_with("BEGIN IMMEDIATE").await?;
+                let repo = store_repo(&mut tx, &event_repo).await?;
+                let db_pr = save_pull_request(&mut tx, &repo, pr, &files, &[], &self.excluded_globs).await?;
+                self.refresh_live_scrape(&mut tx, &mut live, &repo).await?;
+                tx.commit().await?;
+                Ok(Ingested::PullRequest(db_pr.id))
+            }
+            WebhookEventPayload::PullRequestReview(payload) => {
+                let event_repo = match self.event_repo(&event.repository) {
+                    Ok(event_repo) => event_repo,
+                    Err(reason) => return Ok(Ingested::Ignored(format!("pull_request_review {reason}"))),
+                };
+
+                let mut live = self.live_scrape.lock().await;
+                let mut db_conn = self.pool.acquire().await?;
+                let mut tx = db_conn.begin_with("BEGIN IMMEDIATE").await?;
+                let repo = store_repo(&mut tx, &event_repo).await?;
+                // Review payloads carry a trimmed pull request without line stats, so keep a stored one as is
+                let pull_request_id = match PullRequest::find_id(&mut tx, repo.id, payload.pull_request.number as i64).await? {
+                    Some(id) => id,
+                    None => save_pull_request(&mut tx, &repo, &payload.pull_request, &[], &[], &self.excluded_globs).await?.id,
+                };
+                let review = save_review(&mut tx, pull_request_id, &payload.review).await?;
+                self.refresh_live_scrape(&mut tx, &mut live, &repo).await?;
+                tx.commit().await?;
+                Ok(Ingested::Review(review.id))
+            }
+            _ => Ok(Ingested::Ignored(format!("{event_name} events aren't tracked"))),
+        }
+    }
+
+    /// Slides the live scrape's window up to now and rebuilds the counters of the repo a delivery
+    /// touched. Other repos are rebuilt with the window once it has moved on by `FULL_REBUILD_MINUTES`.
+    async fn refresh_live_scrape(&self, db_conn: &mut SqliteConnection, live: &mut LiveScrape, repo: &Repo) -> Result<()> {
+        let now = Utc::now();
+        if now - live.rebuilt_at >= Duration::minutes(FULL_REBUILD_MINUTES) {
+            return self.rebuild_live_scrape(db_conn, live).await;
+        }
+
+        live.scrape.end_dt = now;
+        live.scrape.start_dt = now - Duration::days(LIVE_WINDOW_DAYS);
+        live.scrape.save(db_conn).await?;
+        live.scrape.clear_repo_scrape(db_conn, repo.id).await?;
+        record_repo_scrape(db_conn, &live.scrape, repo, &self.user_ignore_regex).await?;
+        Ok(())
+    }
+
+    /// Slides the live scrape's window up to now, rebuilds every included repo's counters from
+    /// stored data and marks the scrape complete. Callers run this in a transaction, so the TUI
+    /// never reads the counters half cleared.
+    async fn rebuild_live_scrape(&self, db_conn: &mut SqliteConnection, live: &mut LiveScrape) -> Result<()> {
+        let now = Utc::now();
+        live.scrape.end_dt = now;
+        live.scrape.start_dt = now - Duration::days(LIVE_WINDOW_DAYS);
+        live.scrape.status = ScrapeStatus::Complete;
+        live.scrape.save(db_conn).await?;
+        live.scrape.clear_repo_scrapes(db_conn).await?;
+
+        for repo in Repo::list_active(db_conn, live.scrape.start_dt, live.scrape.end_dt).await? {
+            if self.filter.includes_stored_repo(&self.cfg, &repo) {
+                record_repo_scrape(db_conn, &live.scrape, &repo, &self.user_ignore_regex).await?;
+            }
+        }
+        live.rebuilt_at = now;
+        Ok(())
+    }
+
+    /// The repo an event belongs to, or why the event is ignored.
+    fn event_repo(&self, repository: &Option<Repository>) -> Result<EventRepo, String> {
+        let Some(repository) = repository else {
+            return Err("without a repository".to_string());
+        };
+        let Some(owner) = &repository.owner else {
+            return Err("without a repository owner".to_string());
+        };
+        if !self.filter.includes_org(&owner.login) || !self.filter.includes_repo(&self.cfg, &owner.login, repository) {
+            return Err(format!("for {}/{}, which scrapes skip", owner.login, repository.name));
+        }
+
+        Ok(EventRepo {
+            org: owner.login.clone(),
+            name: repository.name.clone(),
+            default_branch: repository.default_branch.clone().unwrap_or_else(|| "main".to_string()),
+        })
+    }
+
+    /// Accepts deliveries until the listener fails.
+    pub async fn serve(self: Arc<Self>, listener: TcpListener) -> Result<()> {
+        loop {
+            let (stream, _addr) = listener.accept().await?;
+            let receiver = Arc::clone(&self);
+            tokio::spawn(async move {
+                if let Err(e) = receiver.handle_connection(stream).await {
+                    eprintln!("Webhook connection error: {e}");
+                }
+            });
+        }
+    }
+
+    /// Reads a single HTTP/1.1 request and answers it, closing the connection afterwards.
+    async fn handle_connection(&self, mut stream: TcpStream) -> Result<()> {
+        // Clients that stall mid-request are dropped instead of holding the task open
+        let deadline = Instant::now() + StdDuration::from_secs(READ_TIMEOUT_SECS);
+        let mut buf = Vec::new();
+        let mut chunk = [0u8; 8192];
+        let (header_len, method, event_name, signature, content_length) = loop {
+            let read = timeout_at(deadline, stream.read(&mut chunk)).await
+                .map_err(|_| anyhow!("Timed out reading request headers"))??;
+            if read == 0 {
+                return Ok(());
+            }
+            buf.extend_from_slice(&chunk[..read]);
+
+            let mut headers = [httparse::EMPTY_HEADER; 64];
+            let mut request = httparse::Request::new(&mut headers);
+            match request.parse(&buf)? {
+                httparse::Status::Complete(header_len) => {
+                    let header = |name: &str| request.headers.iter()
+                        .find(|h| h.name.eq_ignore_ascii_case(name))
+                        .and_then(|h| std::str::from_utf8(h.value).ok())
+                        .map(|v| v.to_string());
+                    let content_length = header("content-length")
+                        .and_then(|len| len.parse::<usize>().ok())
+                        .unwrap_or_default();
+                    break (
+                        header_len,
+                        request.method.unwrap_or_default().to_string(),
+                        header(EVENT_HEADER),
+                        header(SIGNATURE_HEADER),
+                        content_length,
+                    );
+                }
+                httparse::Status::Partial if buf.len() > MAX_HEADER_BYTES => {
+                    return respond(&mut stream, 431, "Request headers too large").await;
+                }
+                httparse::Status::Partial => continue,
+            }
+        };
+
+        if method != "POST" {
+            return respond(&mut stream, 405, "Only POST is supported").await;
+        }
+        if content_length > MAX_BODY_BYTES {
+            return respond(&mut stream, 413, "Payload too large").await;
+        }
+        let Some(event_name) = event_name else {
+            return respond(&mut stream, 400, "Missing X-GitHub-Event header").await;
+        };
+
+        let mut body = buf.split_off(header_len);
+        while body.len() < content_length {
+            let read = timeout_at(deadline, stream.read(&mut chunk)).await
+                .map_err(|_| anyhow!("Timed out reading request body"))??;
+            if read == 0 {
+                return respond(&mut stream, 400, "Body shorter than Content-Length").await;
+            }
+            body.extend_from_slice(&chunk[..read]);
+        }
+        body.truncate(content_length);
+
+        match self.receive(&event_name, signature.as_deref(), &body).await {
+            Ok(Ingested::Pong) => respond(&mut stream, 200, "pong").await,
+            Ok(Ingested::Ignored(reason)) => respond(&mut stream, 202, &format!("Ignored: {reason}")).await,
+            Ok(ingested) => respond(&mut stream, 200, &format!("{ingested:?}")).await,
+            Err(WebhookError::Unauthorized) => respond(&mut stream, 401, "Invalid signature").await,
+            Err(WebhookError::BadRequest(message)) => respond(&mut stream, 400, &message).await,
+            Err(WebhookError::Internal(e)) => {
+                eprintln!("Failed to ingest {event_name} delivery: {e}");
+                respond(&mut stream, 500, "Failed to store event").await
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum WebhookError {
+    Unauthorized,
+    BadRequest(String),
+    Internal(anyhow::Error),
+}
+
+impl std::fmt::Display for WebhookError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WebhookError::Unauthorized => write!(f, "invalid signature"),
+            WebhookError::BadRequest(message) => write!(f, "{message}"),
+            WebhookError::Internal(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl std::error::Error for WebhookError {}
+
+/// Finds or creates the repo a delivery belongs to.
+async fn store_repo(db_conn: &mut SqliteConnection, event_repo: &EventRepo) -> Result<Repo> {
+    let org = Org::create(db_conn, event_repo.org.clone()).await?;
+    Repo::create(db_conn, event_repo.name.clone(), org).await
+}
+
+async fn respond(stream: &mut TcpStream, status: u16, body: &str) -> Result<()> {
+    let reason = match status {
+        200 => "OK",
+        202 => "Accepted",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        405 => "Method Not Allowed",
+        413 => "Payload Too Large",
+        431 => "Request Header Fields Too Large",
+        _ => "Internal Server Error",
+    };
+    let response = format!(
+        "HTTP/1.1 {status} {reason}\r\nContent-Type: text/plain\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    );
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await?;
+    Ok(())
+}
+
+/// Runs `org-pulse serve`: listens for webhook deliveries on `webhook_listen_addr`.
+/// The secret comes from `GITHUB_WEBHOOK_SECRET`; `GITHUB_TOKEN` is optional but
+/// lets pushes and merged PRs be stored with their line stats.
+pub async fn run_server(db_pool: SqlitePool) -> Result<()> {
+    let cfg = get_config()?;
+    let _instance_lock = LockFile::acquire(serve_lock_path())
+        .map_err(|e| anyhow!("Another webhook receiver is already using the database: {e}"))?;
+    let secret = env::var("GITHUB_WEBHOOK_SECRET")
+        .map_err(|_| anyhow!("GITHUB_WEBHOOK_SECRET must be set to verify webhook deliveries"))?;
+    let gh = match env::var("GITHUB_TOKEN") {
+        Ok(_) => Some(Github::from_env(&cfg, &db_pool)?),
+        Err(_) => None,
+    };
+
+    let receiver = Arc::new(WebhookReceiver::new(db_pool, &cfg, &secret, gh).await?);
+    let listener = TcpListener::bind(&cfg.webhook_listen_addr).await?;
+    println!("Listening for GitHub webhooks on {}", listener.local_addr()?);
+    receiver.serve(listener).await
+}
@@ -0,0 +1,63 @@
+use org_pulse::codeowners::{is_user_owner, CodeOwners};
+
+const CODEOWNERS: &str = "\
+# Default owners for everything
+*               @acme/everyone
+*.rs            @alice
+/docs/          @acme/docs  # only the top-level docs directory
+src/generated/
+!src/legacy/    @bob
+apps/*          @carol
+";
+
+fn owners(codeowners: &CodeOwners, path: &str) -> Option<Vec<String>> {
+    codeowners.owners_for(path).map(|owners| owners.to_vec())
+}
+
+fn list(owners: &[&str]) -> Option<Vec<String>> {
+    Some(owners.iter().map(|owner| owner.to_string()).collect())
+}
+
+#[test]
+fn last_matching_rule_wins() {
+    let codeowners = CodeOwners::parse(CODEOWNERS);
+
+    assert_eq!(owners(&codeowners, "README.md"), list(&["@acme/everyone"]));
+    assert_eq!(owners(&codeowners, "src/main.rs"), list(&["@alice"]));
+    // A later rule without owners leaves its paths unowned
+    assert_eq!(owners(&codeowners, "src/generated/api.rs"), None);
+}
+
+#[test]
+fn anchored_patterns_only_match_from_the_root() {
+    let codeowners = CodeOwners::parse(CODEOWNERS);
+
+    assert_eq!(owners(&codeowners, "docs/guide.md"), list(&["@acme/docs"]));
+    assert_eq!(owners(&codeowners, "docs/api/index.md"), list(&["@acme/docs"]));
+    assert_eq!(owners(&codeowners, "src/docs/guide.md"), list(&["@acme/everyone"]));
+    // A trailing `*` covers direct children only
+    assert_eq!(owners(&codeowners, "apps/README.md"), list(&["@carol"]));
+    assert_eq!(owners(&codeowners, "apps/web/index.ts"), list(&["@acme/everyone"]));
+}
+
+#[test]
+fn negated_patterns_are_not_supported() {
+    // GitHub doesn't support `!` in CODEOWNERS, so the rule never matches and ownership is unchanged
+    let codeowners = CodeOwners::parse(CODEOWNERS);
+    assert_eq!(owners(&codeowners, "src/legacy/old.rs"), list(&["@alice"]));
+}
+
+#[test]
+fn files_without_rules_have_no_owners() {
+    let codeowners = CodeOwners::parse("/src/ @alice\n");
+    assert_eq!(owners(&codeowners, "README.md"), None);
+    assert!(CodeOwners::parse("# only a comment\n\n").rules.is_empty());
+}
+
+#[test]
+fn only_direct_user_owners_match() {
+    let owners = ["@Alice".to_string(), "@acme/platform".to_string(), "bob@example.com".to_string()];
+    assert!(is_user_owner(&owners, "alice"));
+    assert!(!is_user_owner(&owners, "platform"));
+    assert!(!is_user_owner(&owners, "bob"));
+}
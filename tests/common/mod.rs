@@ -0,0 +1,31 @@
+//! Helpers shared by the integration tests. Each test crate uses a subset of them.
+#![allow(dead_code)]
+
+use chrono::{DateTime, Utc};
+use org_pulse::config::AppConfig;
+use sqlx::{sqlite::SqlitePoolOptions, SqlitePool};
+
+/// A migrated in-memory database. A single connection keeps every query on the same database.
+pub async fn memory_db() -> SqlitePool {
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect("sqlite::memory:")
+        .await
+        .expect("in-memory database should open");
+    sqlx::migrate!().run(&pool).await.expect("migrations should apply");
+    pool
+}
+
+/// Ignores `sandbox-` orgs and bot accounts, like a typical config
+pub fn test_config() -> AppConfig {
+    AppConfig {
+        ignored_org_pattern: "^sandbox-".to_string(),
+        ignored_user_patterns: "\\[bot\\]$".to_string(),
+        ..Default::default()
+    }
+}
+
+/// Parses an RFC 3339 timestamp
+pub fn at(time: &str) -> DateTime<Utc> {
+    DateTime::parse_from_rfc3339(time).unwrap().with_timezone(&Utc)
+}
@@ -0,0 +1,28 @@
+use org_pulse::conventional::change_type;
+
+#[test]
+fn reads_type_scope_and_breaking_marker() {
+    assert_eq!(change_type("feat: add team rollups").as_deref(), Some("feat"));
+    assert_eq!(change_type("feat!: drop the v1 API").as_deref(), Some("feat"));
+    assert_eq!(change_type("fix(scope): handle empty pages").as_deref(), Some("fix"));
+    assert_eq!(change_type("fix(auth)!: reject expired tokens").as_deref(), Some("fix"));
+    assert_eq!(change_type("docs:typo").as_deref(), Some("docs"));
+}
+
+#[test]
+fn normalises_case_and_common_aliases() {
+    assert_eq!(change_type("Feature(ui): dark mode").as_deref(), Some("feat"));
+    assert_eq!(change_type("HOTFIX: restore login").as_deref(), Some("fix"));
+    assert_eq!(change_type("tests: cover pagination").as_deref(), Some("test"));
+    assert_eq!(change_type("Revert \"feat: add team rollups\"").as_deref(), Some("revert"));
+}
+
+#[test]
+fn leaves_non_conventional_messages_unclassified() {
+    assert_eq!(change_type("Merge pull request #12 from acme/fix-login").as_deref(), None);
+    assert_eq!(change_type("Update README.md").as_deref(), None);
+    assert_eq!(change_type("wip: try another approach").as_deref(), None);
+    assert_eq!(change_type("fix: ").as_deref(), None);
+    assert_eq!(change_type("fix(auth: unclosed scope").as_deref(), None);
+    assert_eq!(change_type("").as_deref(), None);
+}
@@ -0,0 +1,57 @@
+mod common;
+
+use chrono::{DateTime, Utc};
+use org_pulse::lock::LockFile;
+use org_pulse::schedule::Schedule;
+
+use common::at;
+
+fn next(expression: &str, after: &str) -> Option<DateTime<Utc>> {
+    Schedule::parse(expression).unwrap().next_after(at(after))
+}
+
+#[test]
+fn weekly_schedule_fires_on_the_next_monday() {
+    // 2025-06-04 is a Wednesday
+    assert_eq!(next("0 6 * * 1", "2025-06-04T12:00:00Z"), Some(at("2025-06-09T06:00:00Z")));
+    // Runs strictly after the given time, even when it's exactly on schedule
+    assert_eq!(next("0 6 * * 1", "2025-06-09T06:00:00Z"), Some(at("2025-06-16T06:00:00Z")));
+    assert_eq!(next("0 6 * * 1", "2025-06-09T05:59:30Z"), Some(at("2025-06-09T06:00:00Z")));
+}
+
+#[test]
+fn supports_steps_ranges_and_lists() {
+    assert_eq!(next("*/15 * * * *", "2025-06-04T12:07:00Z"), Some(at("2025-06-04T12:15:00Z")));
+    assert_eq!(next("30 9-17/4 * * 1-5", "2025-06-06T18:00:00Z"), Some(at("2025-06-09T09:30:00Z")));
+    assert_eq!(next("0 0 1,15 * *", "2025-06-02T00:00:00Z"), Some(at("2025-06-15T00:00:00Z")));
+    // 7 is also Sunday
+    assert_eq!(next("0 0 * * 7", "2025-06-04T00:00:00Z"), Some(at("2025-06-08T00:00:00Z")));
+    assert_eq!(next("0 0 1 1 *", "2025-06-04T00:00:00Z"), Some(at("2026-01-01T00:00:00Z")));
+}
+
+#[test]
+fn restricted_day_fields_match_either() {
+    // The 13th, or any Friday: 2025-06-06 is a Friday, before the 13th
+    assert_eq!(next("0 0 13 * 5", "2025-06-04T00:00:00Z"), Some(at("2025-06-06T00:00:00Z")));
+}
+
+#[test]
+fn rejects_malformed_schedules() {
+    assert!(Schedule::parse("0 6 * *").is_err());
+    assert!(Schedule::parse("60 * * * *").is_err());
+    assert!(Schedule::parse("* 5-2 * * *").is_err());
+    assert!(Schedule::parse("*/0 * * * *").is_err());
+    assert!(Schedule::parse("0 0 0 * *").is_err());
+    assert_eq!(Schedule::parse("0 0 31 2 *").unwrap().next_after(at("2025-01-01T00:00:00Z")), None);
+}
+
+#[test]
+fn lock_is_exclusive_until_dropped() {
+    let path = std::env::temp_dir().join(format!("org-pulse-test-{}.lock", std::process::id()));
+    let lock = LockFile::acquire(&path).expect("first acquire succeeds");
+    let err = LockFile::acquire(&path).expect_err("second acquire fails");
+    assert!(err.to_string().contains(&std::process::id().to_string()));
+
+    drop(lock);
+    let _lock = LockFile::acquire(&path).expect("lock can be taken again once released");
+}
@@ -0,0 +1,66 @@
+mod common;
+
+use chrono::{Duration, Utc};
+use org_pulse::cli::Command;
+use org_pulse::date_range::DateRange;
+
+use common::at;
+
+fn args(args: &[&str]) -> Vec<String> {
+    args.iter().map(|arg| arg.to_string()).collect()
+}
+
+#[test]
+fn plain_dates_cover_whole_days() {
+    let q3 = DateRange::parse_input("2025-07-01..2025-09-30").unwrap();
+    assert_eq!(q3.start, at("2025-07-01T00:00:00Z"));
+    assert_eq!(q3.end, at("2025-10-01T00:00:00Z"));
+
+    let incident_week = DateRange::parse_input("2025-03-10T14:30:00+01:00 2025-03-17").unwrap();
+    assert_eq!(incident_week.start, at("2025-03-10T13:30:00Z"));
+    assert_eq!(incident_week.end, at("2025-03-18T00:00:00Z"));
+}
+
+#[test]
+fn missing_start_defaults_to_a_week_and_future_ends_are_clamped() {
+    let range = DateRange::parse(None, Some("2025-06-30")).unwrap();
+    assert_eq!(range.start, at("2025-06-24T00:00:00Z"));
+
+    let open_ended = DateRange::parse_input("2025-06-01..").unwrap();
+    assert!(open_ended.end <= Utc::now());
+
+    let future = DateRange::parse(Some("2025-06-01"), Some("2999-01-01")).unwrap();
+    assert!(future.end <= Utc::now() && future.end > Utc::now() - Duration::minutes(1));
+}
+
+#[test]
+fn rejects_backwards_and_malformed_ranges() {
+    assert!(DateRange::parse_input("2025-09-30..2025-07-01").is_err());
+    assert!(DateRange::parse_input("2025-07-01").is_err());
+    assert!(DateRange::parse_input("last sprint..2025-07-01").is_err());
+    assert!(DateRange::parse_input("2025-02-30..2025-03-01").is_err());
+}
+
+#[test]
+fn parses_scrape_command() {
+    assert_eq!(Command::parse(args(&[])).unwrap(), Command::Tui);
+    assert_eq!(Command::parse(args(&["daemon"])).unwrap(), Command::Daemon);
+
+    assert_eq!(
+        Command::parse(args(&["scrape", "--since", "2025-07-01", "--until=2025-09-30"])).unwrap(),
+        Command::Scrape {
+            range: Some(DateRange { start: at("2025-07-01T00:00:00Z"), end: at("2025-10-01T00:00:00Z") }),
+            profile: None,
+        }
+    );
+    // Without bounds the profile's own window applies
+    assert_eq!(
+        Command::parse(args(&["scrape", "--profile", "platform"])).unwrap(),
+        Command::Scrape { range: None, profile: Some("platform".to_string()) }
+    );
+
+    assert!(Command::parse(args(&["scrape", "--since"])).is_err());
+    assert!(Command::parse(args(&["scrape", "--window", "7"])).is_err());
+    assert!(Command::parse(args(&["daemon", "extra"])).is_err());
+    assert!(Command::parse(args(&["frobnicate"])).is_err());
+}
@@ -0,0 +1,26 @@
+use org_pulse::stats::{deploy_events, DeliveryMetrics};
+
+#[test]
+fn deployments_count_as_deploys_when_recorded() {
+    assert_eq!(deploy_events(3, 5), 5);
+    // Repos without deployments fall back to releases
+    assert_eq!(deploy_events(3, 0), 3);
+    assert_eq!(deploy_events(0, 0), 0);
+}
+
+#[test]
+fn delivery_metrics_take_the_median_lead_time() {
+    let metrics = DeliveryMetrics::new(4, 6, 6, 2.0, vec![30.0, 2.0, 10.0, 50.0]);
+    assert_eq!((metrics.releases, metrics.deployments), (4, 6));
+    assert_eq!(metrics.deploys_per_week, 3.0);
+    // An even count averages the middle two
+    assert_eq!(metrics.lead_time_hours, Some(20.0));
+
+    let metrics = DeliveryMetrics::new(3, 0, deploy_events(3, 0), 0.5, vec![5.0, 1.0, 3.0]);
+    assert_eq!(metrics.deploys_per_week, 6.0);
+    assert_eq!(metrics.lead_time_hours, Some(3.0));
+
+    let metrics = DeliveryMetrics::new(0, 0, 0, 1.0, vec![]);
+    assert_eq!(metrics.deploys_per_week, 0.0);
+    assert_eq!(metrics.lead_time_hours, None);
+}
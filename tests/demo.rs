@@ -0,0 +1,150 @@
+mod common;
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use chrono::{TimeZone, Utc};
+use org_pulse::cli::Command;
+use org_pulse::db::{self, RepoScrape, Scrape};
+use org_pulse::demo::{generate_demo, DemoOptions};
+use sqlx::SqlitePool;
+
+use common::memory_db;
+
+fn options(seed: u64) -> DemoOptions {
+    DemoOptions {
+        seed,
+        orgs: 2,
+        repos_per_org: 4,
+        contributors: 30,
+        weeks: 3,
+        end: Utc.with_ymd_and_hms(2025, 7, 7, 0, 0, 0).unwrap(),
+    }
+}
+
+/// `(username, commits)` for every contributor in the latest scrape
+async fn latest_commits(pool: &SqlitePool) -> Vec<(String, i64)> {
+    let mut conn = pool.acquire().await.unwrap();
+    let latest = Scrape::get_latest(&mut conn).await.unwrap().expect("demo data has scrapes");
+    let mut commits: Vec<(String, i64)> = db::get_contributor_stats(&mut conn, latest.id).await.unwrap()
+        .into_iter()
+        .map(|c| (c.username, c.total_commits))
+        .collect();
+    commits.sort();
+    commits
+}
+
+#[tokio::test]
+async fn the_same_seed_generates_the_same_data() {
+    let (first, second, other) = (memory_db().await, memory_db().await, memory_db().await);
+    let summary = generate_demo(&first, &options(7)).await.unwrap();
+    assert_eq!(generate_demo(&second, &options(7)).await.unwrap(), summary);
+    generate_demo(&other, &options(8)).await.unwrap();
+
+    assert_eq!(latest_commits(&first).await, latest_commits(&second).await);
+    assert_ne!(latest_commits(&first).await, latest_commits(&other).await);
+}
+
+#[tokio::test]
+async fn demo_data_fills_every_view() {
+    let pool = memory_db().await;
+    let summary = generate_demo(&pool, &options(42)).await.unwrap();
+    assert_eq!((summary.orgs, summary.repos, summary.scrapes), (2, 8, 3));
+
+    let mut conn = pool.acquire().await.unwrap();
+    let scrapes = Scrape::list_all(&mut conn).await.unwrap();
+    assert_eq!(scrapes.len(), 3);
+    let latest = Scrape::get_latest(&mut conn).await.unwrap().unwrap();
+    assert_eq!(latest.end_dt, options(42).end);
+
+    let repos = db::get_repo_stats(&mut conn, latest.id).await.unwrap();
+    assert!(!repos.is_empty());
+    assert!(repos.iter().any(|r| r.delivery.releases > 0 || r.delivery.deployments > 0));
+    assert!(repos.iter().all(|r| r.ci.runs > 0));
+    assert!(repos.iter().any(|r| r.excluded_lines > 0), "lockfile changes are excluded");
+
+    // Bots are in the raw data but not the contributor counters, and a few people do most of the work
+    let contributors = db::get_contributor_stats(&mut conn, latest.id).await.unwrap();
+    assert!(contributors.iter().all(|c| !c.username.ends_with("[bot]")));
+    let mut commits: Vec<i64> = contributors.iter().map(|c| c.total_commits).collect();
+    commits.sort_unstable_by(|a, b| b.cmp(a));
+    assert!(commits[0] >= 3 * commits[commits.len() / 2]);
+
+    let teams = db::get_team_stats(&mut conn, latest.id).await.unwrap();
+    assert!(teams.iter().any(|t| t.commits > 0 && t.reviews > 0));
+
+    let busiest = repos.iter().max_by_key(|r| r.commits).unwrap();
+    let ownership = db::get_repo_ownership(&mut conn, latest.id, &busiest.org_name, &busiest.repo_name).await.unwrap();
+    assert!(ownership.has_codeowners && ownership.owned_prs > 0);
+    let hotspots = db::get_repo_hotspots(&mut conn, latest.id, &busiest.org_name, &busiest.repo_name).await.unwrap();
+    assert!(!hotspots.paths.is_empty());
+}
+
+#[tokio::test]
+async fn a_loaded_scrape_agrees_with_its_stats() {
+    let pool = memory_db().await;
+    generate_demo(&pool, &options(3)).await.unwrap();
+    let mut conn = pool.acquire().await.unwrap();
+    let latest = Scrape::get_latest(&mut conn).await.unwrap().unwrap();
+    let scrape = Scrape::get(&mut conn, &latest.id).await.unwrap();
+    assert_eq!(scrape.repo_scrapes.len() as i64, latest.repo_count);
+
+    // Rebuild the contributor view from the loaded scrape
+    let mut loaded: BTreeMap<String, (i64, BTreeSet<String>)> = BTreeMap::new();
+    for repo_scrape in &scrape.repo_scrapes {
+        assert_eq!(repo_scrape.org.id, repo_scrape.repo.org.id);
+        for cs in &repo_scrape.contributor_scrapes {
+            let entry = loaded.entry(cs.contributor.username.clone()).or_default();
+            entry.0 += cs.commits;
+            entry.1.insert(repo_scrape.org.name.clone());
+        }
+    }
+    let stats: BTreeMap<String, (i64, BTreeSet<String>)> = db::get_contributor_stats(&mut conn, latest.id).await.unwrap()
+        .into_iter()
+        .map(|c| (c.username, (c.total_commits, c.orgs.into_iter().collect())))
+        .collect();
+    assert_eq!(loaded, stats);
+
+    // Org totals count each repo once, however many contributors it has
+    let mut org_totals: BTreeMap<String, (i64, i64, i64, i64, i64)> = BTreeMap::new();
+    for repo_scrape in &scrape.repo_scrapes {
+        let entry = org_totals.entry(repo_scrape.org.name.clone()).or_default();
+        *entry = (entry.0 + repo_scrape.commits, entry.1 + repo_scrape.lines, entry.2 + repo_scrape.excluded_lines, entry.3 + 1, 0);
+    }
+    for (org, totals) in org_totals.iter_mut() {
+        totals.4 = loaded.values().filter(|(_, orgs)| orgs.contains(org)).count() as i64;
+    }
+    let org_stats: BTreeMap<String, (i64, i64, i64, i64, i64)> = db::get_org_stats(&mut conn, latest.id).await.unwrap()
+        .into_iter()
+        .map(|o| (o.name, (o.total_commits, o.total_lines, o.excluded_lines, o.repo_count, o.contributor_count)))
+        .collect();
+    assert_eq!(org_totals, org_stats);
+
+    let first = &scrape.repo_scrapes[0];
+    let reloaded = RepoScrape::get(&mut conn, &first.id).await.unwrap();
+    assert_eq!((reloaded.repo.name.as_str(), reloaded.org.name.as_str()), (first.repo.name.as_str(), first.org.name.as_str()));
+    let usernames = |rs: &RepoScrape| rs.contributor_scrapes.iter().map(|cs| cs.contributor.username.clone()).collect::<Vec<_>>();
+    assert_eq!(usernames(&reloaded), usernames(first));
+}
+
+#[tokio::test]
+async fn demo_data_is_only_written_to_an_empty_database() {
+    let pool = memory_db().await;
+    generate_demo(&pool, &options(1)).await.unwrap();
+    let error = generate_demo(&pool, &options(2)).await.unwrap_err();
+    assert!(error.to_string().contains("already has scrapes"));
+
+    let mut conn = pool.acquire().await.unwrap();
+    assert_eq!(Scrape::list_all(&mut conn).await.unwrap().len(), 3);
+}
+
+#[test]
+fn demo_command_parses_its_options() {
+    let Command::Demo(parsed) = Command::parse(["demo", "--seed", "7", "--orgs=2", "--repos", "4", "--contributors", "30", "--weeks", "3", "--until", "2025-07-06"].map(String::from)).unwrap() else {
+        panic!("expected the demo command");
+    };
+    assert_eq!(parsed, options(7));
+
+    assert_eq!(Command::parse(["demo"].map(String::from)).unwrap(), Command::Demo(DemoOptions::default()));
+    assert!(Command::parse(["demo", "--orgs", "many"].map(String::from)).is_err());
+    assert!(Command::parse(["demo", "--size", "4"].map(String::from)).is_err());
+}
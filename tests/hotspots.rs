@@ -0,0 +1,11 @@
+use org_pulse::scraper::churn_directory;
+
+#[test]
+fn churn_rolls_files_up_to_their_leading_directories() {
+    assert_eq!(churn_directory("src/app/state/mod.rs", 2), "src/app");
+    assert_eq!(churn_directory("src/main.rs", 2), "src");
+    assert_eq!(churn_directory(".github/workflows/ci.yml", 1), ".github");
+    // Files at the root, or with no depth, have no directory
+    assert_eq!(churn_directory("README.md", 2), "");
+    assert_eq!(churn_directory("src/main.rs", 0), "");
+}
@@ -0,0 +1,118 @@
+mod common;
+
+use std::sync::{Arc, Mutex};
+
+use bytes::Bytes;
+use http::{Request, Response, StatusCode};
+use http_body_util::{BodyExt, Full};
+use org_pulse::config::{AppConfig, HttpCacheMode};
+use org_pulse::date_range::DateRange;
+use org_pulse::http_cache::{HttpCacheLayer, ResponseBody};
+use tower::{service_fn, BoxError, Layer, Service, ServiceExt};
+
+use common::memory_db;
+
+const COMMITS_URL: &str = "https://api.github.com/repos/acme/api/commits?per_page=100&page=2";
+const ETAG: &str = "W/\"6b2b1c\"";
+const LINK: &str = "<https://api.github.com/repos/acme/api/commits?per_page=100&page=3>; rel=\"next\"";
+
+fn body(bytes: &'static str) -> ResponseBody {
+    Full::new(Bytes::from(bytes)).map_err(|never| match never {}).boxed()
+}
+
+/// A stand-in for GitHub that answers `304` when the request's `If-None-Match` matches, and
+/// records the validators each request carried
+fn github(seen: Arc<Mutex<Vec<Option<String>>>>) -> impl Service<Request<()>, Response = Response<ResponseBody>, Error = BoxError, Future: Send> + Clone + Send + 'static {
+    service_fn(move |request: Request<()>| {
+        let seen = seen.clone();
+        async move {
+            let if_none_match = request.headers().get("if-none-match").map(|v| v.to_str().unwrap().to_string());
+            seen.lock().unwrap().push(if_none_match.clone());
+            let response = if if_none_match.as_deref() == Some(ETAG) {
+                Response::builder().status(StatusCode::NOT_MODIFIED).body(body(""))
+            } else {
+                Response::builder()
+                    .header("etag", ETAG)
+                    .header("link", LINK)
+                    .header("content-type", "application/json")
+                    .body(body("[{\"sha\":\"4f1c2a9e\"}]"))
+            };
+            Ok::<_, BoxError>(response.unwrap())
+        }
+    })
+}
+
+async fn get<S>(service: &mut S, url: &str) -> (StatusCode, Option<String>, String)
+where
+    S: Service<Request<()>, Response = Response<ResponseBody>, Error = BoxError>,
+{
+    let request = Request::get(url).header("accept", "application/vnd.github.v3+json").body(()).unwrap();
+    let response = service.ready().await.unwrap().call(request).await.unwrap();
+    let status = response.status();
+    let link = response.headers().get("link").map(|v| v.to_str().unwrap().to_string());
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    (status, link, String::from_utf8(body.to_vec()).unwrap())
+}
+
+#[tokio::test]
+async fn revalidates_cached_responses_and_replays_them_on_304() {
+    let pool = memory_db().await;
+    let seen = Arc::new(Mutex::new(vec![]));
+    let mut service = HttpCacheLayer::new(pool, HttpCacheMode::On).layer(github(seen.clone()));
+
+    let first = get(&mut service, COMMITS_URL).await;
+    assert_eq!(first, (StatusCode::OK, Some(LINK.to_string()), "[{\"sha\":\"4f1c2a9e\"}]".to_string()));
+
+    // The second request is conditional, and the 304 is answered from the cache with its pagination intact
+    let second = get(&mut service, COMMITS_URL).await;
+    assert_eq!(second, first);
+    assert_eq!(*seen.lock().unwrap(), vec![None, Some(ETAG.to_string())]);
+
+    // The rate limit changes on every call, so it's never cached or made conditional
+    get(&mut service, "https://api.github.com/rate_limit").await;
+    get(&mut service, "https://api.github.com/rate_limit").await;
+    assert_eq!(seen.lock().unwrap()[2..], [None, None]);
+}
+
+#[tokio::test]
+async fn consecutive_default_window_scrapes_revalidate_their_listings() {
+    let pool = memory_db().await;
+    let seen = Arc::new(Mutex::new(vec![]));
+    let mut service = HttpCacheLayer::new(pool, HttpCacheMode::On).layer(github(seen.clone()));
+
+    // Each scrape lists commits with its window as `since` and `until`
+    for _ in 0..2 {
+        let range = DateRange::for_profile(&AppConfig::default(), None);
+        let url = format!("https://api.github.com/repos/acme/api/commits?since={}&until={}&per_page=100", range.start.to_rfc3339(), range.end.to_rfc3339());
+        get(&mut service, &url).await;
+    }
+    assert_eq!(*seen.lock().unwrap(), vec![None, Some(ETAG.to_string())]);
+}
+
+#[tokio::test]
+async fn offline_mode_only_serves_from_cache() {
+    let pool = memory_db().await;
+    let seen = Arc::new(Mutex::new(vec![]));
+    let mut online = HttpCacheLayer::new(pool.clone(), HttpCacheMode::On).layer(github(seen.clone()));
+    get(&mut online, COMMITS_URL).await;
+
+    let mut offline = HttpCacheLayer::new(pool, HttpCacheMode::Offline).layer(github(seen.clone()));
+    let (status, link, body) = get(&mut offline, COMMITS_URL).await;
+    assert_eq!((status, link.as_deref()), (StatusCode::OK, Some(LINK)));
+    assert!(body.contains("4f1c2a9e"));
+
+    let (status, _, body) = get(&mut offline, "https://api.github.com/repos/acme/web/commits").await;
+    assert_eq!(status, StatusCode::GATEWAY_TIMEOUT);
+    assert!(body.contains("isn't in the HTTP cache"));
+    assert_eq!(seen.lock().unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn off_mode_passes_requests_through() {
+    let pool = memory_db().await;
+    let seen = Arc::new(Mutex::new(vec![]));
+    let mut service = HttpCacheLayer::new(pool, HttpCacheMode::Off).layer(github(seen.clone()));
+    get(&mut service, COMMITS_URL).await;
+    get(&mut service, COMMITS_URL).await;
+    assert_eq!(*seen.lock().unwrap(), vec![None, None]);
+}
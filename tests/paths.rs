@@ -0,0 +1,108 @@
+use std::path::{Path, PathBuf};
+
+use chrono::{TimeZone, Utc};
+use org_pulse::app::state::App;
+use org_pulse::cli::{Cli, Command};
+use org_pulse::config::{AppConfig, DatabaseConfig};
+use org_pulse::db::new_pool;
+use org_pulse::demo::{generate_demo, DemoOptions};
+use org_pulse::paths::{db_path, locate, set_db_path};
+use org_pulse::stats::ViewData;
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("org-pulse-{name}-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn database(name: &str, path: &Path) -> DatabaseConfig {
+    DatabaseConfig { name: name.to_string(), path: path.to_string_lossy().into_owned() }
+}
+
+#[test]
+fn paths_are_located_in_priority_order() {
+    let dir = temp_dir("locate");
+    let local = dir.join("org-pulse.db");
+    let app_dir = Some(PathBuf::from("/home/me/.local/share/org-pulse"));
+    let env = Some("/srv/org-pulse/team.db".into());
+
+    assert_eq!(locate(Some("flag.db".into()), env.clone(), &local, app_dir.clone()), PathBuf::from("flag.db"));
+    assert_eq!(locate(None, env, &local, app_dir.clone()), PathBuf::from("/srv/org-pulse/team.db"));
+    // Without a local database, the XDG data directory is used rather than creating one here
+    assert_eq!(locate(None, Some("".into()), &local, app_dir.clone()), PathBuf::from("/home/me/.local/share/org-pulse/org-pulse.db"));
+    std::fs::write(&local, "").unwrap();
+    assert_eq!(locate(None, None, &local, app_dir), local);
+    assert_eq!(locate(None, None, Path::new("missing.db"), None), PathBuf::from("missing.db"));
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn db_and_config_options_go_anywhere() {
+    let args = |args: &[&str]| args.iter().map(|a| a.to_string()).collect::<Vec<_>>();
+
+    let cli = Cli::parse(args(&["--db", "acme", "scrape", "--config=/etc/org-pulse.toml", "--profile", "weekly"])).unwrap();
+    assert_eq!(cli.db.as_deref(), Some("acme"));
+    assert_eq!(cli.config.as_deref(), Some("/etc/org-pulse.toml"));
+    assert_eq!(cli.command, Command::Scrape { range: None, profile: Some("weekly".to_string()) });
+
+    assert_eq!(Cli::parse(args(&[])).unwrap(), Cli { db: None, config: None, command: Command::Tui });
+    assert!(Cli::parse(args(&["daemon", "--db"])).is_err());
+
+    // `--db` takes a configured name or a path
+    let cfg = AppConfig { databases: vec![database("acme", Path::new("/data/acme.db"))], ..Default::default() };
+    assert_eq!(cfg.database_path("acme"), "/data/acme.db");
+    assert_eq!(cfg.database_path("other.db"), "other.db");
+}
+
+#[test]
+fn the_startup_database_is_listed_when_it_isnt_configured() {
+    let acme = database("acme", Path::new("/data/acme.db"));
+    let globex = database("globex", Path::new("/data/globex.db"));
+    let mut app = App::default();
+
+    app.set_databases(&[acme.clone(), globex.clone()], "/data/globex.db");
+    assert_eq!((app.databases.len(), app.database_index, app.database_name()), (2, 1, Some("globex")));
+
+    app.set_databases(&[acme.clone(), globex], "/home/me/.local/share/org-pulse/org-pulse.db");
+    assert_eq!((app.databases.len(), app.database_index, app.database_name()), (3, 0, Some("org-pulse")));
+
+    // With nothing to switch to, switching is a no-op and the header doesn't name the database
+    app.set_databases(&[], "org-pulse.db");
+    assert_eq!(app.database_name(), None);
+    app.request_database_switch();
+    assert!(!app.database_switch_requested);
+}
+
+#[tokio::test]
+async fn switching_databases_reloads_scrapes() {
+    let dir = temp_dir("switch");
+    let (demo, empty) = (dir.join("demo.db"), dir.join("nested/empty.db"));
+    set_db_path(&demo);
+    let options = DemoOptions { orgs: 1, repos_per_org: 2, contributors: 5, weeks: 2, end: Utc.with_ymd_and_hms(2025, 7, 7, 0, 0, 0).unwrap(), ..Default::default() };
+    generate_demo(&new_pool().await.unwrap(), &options).await.unwrap();
+
+    let mut app = App::default();
+    app.set_databases(&[database("demo", &demo), database("empty", &empty)], &demo.to_string_lossy());
+    app.request_database_switch();
+    app.handle_database_switch().await.unwrap();
+    assert_eq!(db_path(), empty);
+    assert!(empty.exists(), "missing directories are created");
+    assert!(app.scrapes.is_empty() && app.current_scrape.is_none());
+    assert!(matches!(app.data, ViewData::Error(_)));
+
+    app.request_database_switch();
+    app.handle_database_switch().await.unwrap();
+    assert_eq!(db_path(), demo);
+    assert_eq!(app.scrapes.len(), 2);
+    assert!(matches!(app.data, ViewData::Orgs(_)));
+
+    // The switched-to pool is the one the app keeps using, in WAL mode so a scrape can write while the TUI reads
+    let pool = app.db_pool.as_ref().expect("switching opens a pool");
+    let (journal_mode,): (String,) = sqlx::query_as("PRAGMA journal_mode").fetch_one(pool).await.unwrap();
+    assert_eq!(journal_mode, "wal");
+    app.db_pool.take().unwrap().close().await;
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
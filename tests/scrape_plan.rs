@@ -0,0 +1,80 @@
+mod common;
+
+use chrono::Duration;
+use octocrab::models::Rate;
+use org_pulse::cli::Command;
+use org_pulse::date_range::DateRange;
+use org_pulse::plan::{ActivityEstimator, EstimateBasis, OrgPlan, RepoActivity, ScrapePlan};
+
+use common::at;
+
+fn week() -> DateRange {
+    DateRange { start: at("2025-07-01T00:00:00Z"), end: at("2025-07-08T00:00:00Z") }
+}
+
+fn activity(repo: &str, commits_per_day: f64, prs_per_day: f64) -> RepoActivity {
+    RepoActivity { org_name: "acme".to_string(), repo_name: repo.to_string(), commits_per_day, prs_per_day }
+}
+
+#[test]
+fn estimates_repo_calls_from_history() {
+    let estimator = ActivityEstimator::new(vec![activity("api", 2.0, 1.0), activity("web", 4.0, 0.0), activity("docs", 0.0, 0.5)]);
+    let pushed = Some(at("2025-07-03T12:00:00Z"));
+
+    // 1 commit list + 14 commits + 7 PRs * 3 + 3 CODEOWNERS + PR list + releases + tags + 1 environment + runs
+    let api = estimator.estimate("acme", "api", pushed, &week(), 1, 100);
+    assert_eq!(api.basis, EstimateBasis::History);
+    assert_eq!(api.commits, 14.0);
+    assert_eq!(api.api_calls, 1 + 14 + 21 + 3 + 1 + 2 + 1 + 1);
+
+    // Nothing pushed since the window started, or no commits expected: only the commit list
+    let stale = estimator.estimate("acme", "api", Some(at("2025-06-01T00:00:00Z")), &week(), 1, 100);
+    assert_eq!((stale.basis, stale.api_calls), (EstimateBasis::NoPushes, 1));
+    assert_eq!(estimator.estimate("acme", "docs", pushed, &week(), 1, 100).api_calls, 1);
+
+    // Unknown repos get the median rates; long windows list more pages but cap commit details
+    let new = estimator.estimate("acme", "mobile", pushed, &DateRange { start: week().start, end: week().start + Duration::days(90) }, 0, 100);
+    assert_eq!(new.basis, EstimateBasis::Typical);
+    assert_eq!(new.commits, 180.0);
+    assert_eq!(new.prs, 45.0);
+    assert_eq!(new.api_calls, 2 + 100 + 135 + 3 + 1 + 2 + 1);
+}
+
+#[test]
+fn compares_estimate_to_quota() {
+    let estimator = ActivityEstimator::new(vec![]);
+    let mut plan = ScrapePlan {
+        range: week(),
+        profile: Some("payments-daily".to_string()),
+        orgs: vec![OrgPlan { name: "acme".to_string(), listed_repos: 120, excluded_repos: 118, teams: Some(2) }],
+        skipped_orgs: vec!["acme-sandbox".to_string()],
+        repos: vec![
+            estimator.estimate("acme", "api", None, &week(), 1, 100),
+            estimator.estimate("acme", "legacy", Some(at("2020-01-01T00:00:00Z")), &week(), 1, 100),
+        ],
+        rate_limit: Some(Rate { limit: 5000, used: 4990, remaining: 10, reset: 1751360400 }),
+    };
+
+    // Org list, 3 repo pages, the team list and 2 teams' members
+    assert_eq!(plan.listing_calls(), 1 + 3 + 1 + 2);
+    assert_eq!(plan.api_calls(), plan.listing_calls() + plan.repos[0].api_calls + 1);
+    assert_eq!(plan.fits_quota(), Some(false));
+    let printed = plan.to_string();
+    assert!(printed.contains("(profile payments-daily)"));
+    assert!(printed.contains("Skipped orgs: acme-sandbox"));
+    assert!(printed.contains("Exceeds the remaining quota"));
+    assert!(printed.contains("acme/api"));
+    assert!(!printed.contains("acme/legacy"));
+
+    plan.rate_limit = None;
+    assert_eq!(plan.fits_quota(), None);
+}
+
+#[test]
+fn parses_plan_command() {
+    assert_eq!(
+        Command::parse(["plan", "--profile=platform", "--since", "2025-07-01", "--until", "2025-07-07"].map(String::from)).unwrap(),
+        Command::Plan { range: Some(week()), profile: Some("platform".to_string()) }
+    );
+    assert!(Command::parse(["plan", "--dry"].map(String::from)).is_err());
+}
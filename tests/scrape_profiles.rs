@@ -0,0 +1,68 @@
+use chrono::{Duration, Utc};
+use org_pulse::app::state::App;
+use org_pulse::config::{AppConfig, ScrapeProfile};
+use org_pulse::date_range::DateRange;
+use org_pulse::stats::ScrapeInfo;
+
+fn scrape(id: i64, profile: Option<&str>) -> ScrapeInfo {
+    let end_dt = Utc::now() - Duration::days(id);
+    ScrapeInfo {
+        id,
+        start_dt: end_dt - Duration::days(7),
+        end_dt,
+        profile: profile.map(|p| p.to_string()),
+        repo_count: 0,
+    }
+}
+
+fn visible_ids(app: &App) -> Vec<i64> {
+    app.visible_scrapes().iter().map(|s| s.id).collect()
+}
+
+#[test]
+fn scrape_list_cycles_through_profiles() {
+    let mut app = App {
+        scrapes: vec![scrape(1, Some("weekly")), scrape(2, None), scrape(3, Some("platform")), scrape(4, Some("weekly"))],
+        ..Default::default()
+    };
+    assert_eq!(visible_ids(&app), vec![1, 2, 3, 4]);
+
+    // Scrapes without a profile come first, then profiles by name
+    app.cycle_scrape_profile_filter();
+    assert_eq!(visible_ids(&app), vec![2]);
+    app.cycle_scrape_profile_filter();
+    assert_eq!(visible_ids(&app), vec![3]);
+
+    app.scrape_selected_index = 0;
+    app.cycle_scrape_profile_filter();
+    assert_eq!(app.scrape_profile_filter, Some(Some("weekly".to_string())));
+    assert_eq!(visible_ids(&app), vec![1, 4]);
+    app.move_scrape_selection_down();
+    app.move_scrape_selection_down();
+    assert_eq!(app.scrape_selected_index, 0);
+
+    app.cycle_scrape_profile_filter();
+    assert_eq!(app.scrape_profile_filter, None);
+    assert_eq!(visible_ids(&app), vec![1, 2, 3, 4]);
+}
+
+#[test]
+fn profiles_fall_back_to_top_level_settings() {
+    let cfg = AppConfig {
+        days: 30,
+        profiles: vec![
+            ScrapeProfile { name: "monthly".to_string(), ..Default::default() },
+            ScrapeProfile { name: "daily".to_string(), days: Some(1), ..Default::default() },
+        ],
+        ..Default::default()
+    };
+
+    let monthly = DateRange::for_profile(&cfg, Some(cfg.profile("monthly").unwrap()));
+    assert_eq!(monthly.end - monthly.start, Duration::days(30));
+    let daily = DateRange::for_profile(&cfg, Some(cfg.profile("daily").unwrap()));
+    assert_eq!(daily.end - daily.start, Duration::days(1));
+    let default = DateRange::for_profile(&cfg, None);
+    assert_eq!(default.end - default.start, Duration::days(30));
+
+    assert!(cfg.profile("quarterly").is_err());
+}
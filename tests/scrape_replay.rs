@@ -0,0 +1,290 @@
+mod common;
+
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+use bytes::Bytes;
+use http::{Request, Response, StatusCode};
+use http_body_util::{BodyExt, Full};
+use org_pulse::config::{AppConfig, RecordingMode, RepoGroupConfig, TeamConfig};
+use org_pulse::date_range::DateRange;
+use org_pulse::db::{self, Scrape, ScrapeStatus};
+use org_pulse::github::Github;
+use org_pulse::http_cache::ResponseBody;
+use org_pulse::recording::{fixture_path, RecordedExchange, RecordingLayer};
+use org_pulse::scraper::scrape_into;
+use sqlx::SqlitePool;
+use tower::{service_fn, BoxError, Layer, Service, ServiceExt};
+
+use common::{memory_db, test_config};
+
+/// One org with two repos, recorded for the first week of July 2025: `api` has commits from alice,
+/// bob and renovate, a merged PR, a release, deployments and CI runs; `legacy` had no pushes.
+const FIXTURES: &str = "tests/fixtures/github";
+
+/// Copies the fixtures `keep` accepts into a fresh directory, for tests that edit or drop some of them
+fn copy_fixtures(name: &str, keep: impl Fn(&str) -> bool) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("org-pulse-{name}-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    for entry in std::fs::read_dir(FIXTURES).unwrap() {
+        let path = entry.unwrap().path();
+        if keep(path.file_name().unwrap().to_str().unwrap()) {
+            std::fs::copy(&path, dir.join(path.file_name().unwrap())).unwrap();
+        }
+    }
+    dir
+}
+
+async fn replayed_scrape() -> (SqlitePool, i64) {
+    let pool = memory_db().await;
+    let gh = Github::replay(FIXTURES, &pool).expect("replay client should build");
+    let range = DateRange::parse_input("2025-07-01..2025-07-07").unwrap();
+    let scrape = scrape_into(&pool, &test_config(), &gh, range, None).await.expect("replayed scrape should succeed");
+    (pool, scrape.id)
+}
+
+#[tokio::test]
+async fn replayed_scrape_fills_repo_and_contributor_stats() {
+    let (pool, scrape_id) = replayed_scrape().await;
+    let mut conn = pool.acquire().await.unwrap();
+
+    // `legacy` has no commits in the window, so only `api` is recorded
+    let repos = db::get_repo_stats(&mut conn, scrape_id).await.unwrap();
+    assert_eq!(repos.len(), 1);
+    let api = &repos[0];
+    assert_eq!((api.repo_name.as_str(), api.commits, api.prs, api.lines), ("api", 3, 1, 17));
+    assert_eq!((api.delivery.releases, api.delivery.deployments), (1, 2));
+    assert_eq!((api.ci.runs, api.ci.successful_runs, api.ci.flaky_reruns), (3, 2, 1));
+
+    // renovate[bot] matches `ignored_user_patterns`
+    let contributors = db::get_contributor_stats(&mut conn, scrape_id).await.unwrap();
+    let commits: Vec<(&str, i64)> = contributors.iter().map(|c| (c.username.as_str(), c.total_commits)).collect();
+    assert_eq!(commits, vec![("alice", 2), ("bob", 1)]);
+}
+
+#[tokio::test]
+async fn commit_details_can_be_skipped() {
+    let pool = memory_db().await;
+    let gh = Github::replay(FIXTURES, &pool).unwrap();
+    let range = DateRange::parse_input("2025-07-01..2025-07-07").unwrap();
+    let cfg = AppConfig { max_commit_details: 0, ..test_config() };
+    let scrape = scrape_into(&pool, &cfg, &gh, range, None).await.unwrap();
+
+    // Commits are still counted from the listing, just without line stats or files
+    let mut conn = pool.acquire().await.unwrap();
+    let repos = db::get_repo_stats(&mut conn, scrape.id).await.unwrap();
+    assert_eq!(repos[0].commits, 3);
+    let (lines, files): (i64, i64) = sqlx::query_as("SELECT SUM(additions + deletions), (SELECT COUNT(*) FROM commit_files) FROM commits")
+        .fetch_one(conn.as_mut()).await.unwrap();
+    assert_eq!((lines, files), (0, 0));
+}
+
+#[tokio::test]
+async fn replayed_scrape_fills_teams_and_ownership() {
+    let (pool, scrape_id) = replayed_scrape().await;
+    let mut conn = pool.acquire().await.unwrap();
+
+    let teams = db::get_team_stats(&mut conn, scrape_id).await.unwrap();
+    assert_eq!(teams.len(), 1);
+    assert_eq!((teams[0].team_slug.as_str(), teams[0].member_count, teams[0].commits), ("platform", 1, 2));
+    assert_eq!((teams[0].prs, teams[0].reviews), (0, 1));
+
+    // PRs and reviews in repos the scrape didn't record, like `legacy`, aren't counted
+    let merged = DateRange::parse_input("2025-07-02..2025-07-02").unwrap().start;
+    let (pr_id,): (i64,) = sqlx::query_as("
+        INSERT INTO pull_requests (repo_id, number, author_id, state, merged_at)
+        SELECT r.id, 1, c.id, 'closed', $1 FROM repos r, contributors c WHERE r.name = 'legacy' AND c.username = 'alice'
+        RETURNING id
+    ").bind(merged).fetch_one(conn.as_mut()).await.unwrap();
+    sqlx::query("INSERT INTO pull_request_reviews (pull_request_id, github_id, reviewer_id, state, submitted_at) SELECT $1, 1, id, 'APPROVED', $2 FROM contributors WHERE username = 'alice'")
+        .bind(pr_id).bind(merged).execute(conn.as_mut()).await.unwrap();
+    let teams = db::get_team_stats(&mut conn, scrape_id).await.unwrap();
+    assert_eq!((teams[0].prs, teams[0].reviews), (0, 1));
+
+    // alice owns everything and reviewed bob's PR
+    let ownership = db::get_repo_ownership(&mut conn, scrape_id, "acme", "api").await.unwrap();
+    assert!(ownership.has_codeowners);
+    assert_eq!((ownership.owned_prs, ownership.owner_reviewed_prs), (1, 1));
+}
+
+#[tokio::test]
+async fn scrapes_sync_config_groups_without_adding_contributors() {
+    let pool = memory_db().await;
+    let gh = Github::replay(FIXTURES, &pool).unwrap();
+    let range = DateRange::parse_input("2025-07-01..2025-07-07").unwrap();
+    let cfg = AppConfig {
+        teams: vec![TeamConfig { name: "core".to_string(), members: vec!["alice".to_string(), "carol".to_string()] }],
+        repo_groups: vec![RepoGroupConfig { name: "services".to_string(), repos: vec!["acme/*".to_string()] }],
+        ..test_config()
+    };
+    let scrape = scrape_into(&pool, &cfg, &gh, range, None).await.unwrap();
+
+    let mut conn = pool.acquire().await.unwrap();
+    let groups = db::get_group_stats(&mut conn, scrape.id).await.unwrap();
+    let sizes: Vec<(&str, i64)> = groups.iter().map(|g| (g.name.as_str(), g.size)).collect();
+    assert_eq!(sizes, vec![("core", 1), ("services", 2)]);
+
+    // carol is configured but never showed up, so no contributor is stored for them
+    let (carol,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM contributors WHERE username = 'carol'").fetch_one(conn.as_mut()).await.unwrap();
+    assert_eq!(carol, 0);
+}
+
+#[tokio::test]
+async fn a_failed_scrape_leaves_nothing_for_viewers() {
+    // Without the pull request listing, `api` fails after its commits were fetched
+    let dir = copy_fixtures("failed-scrape", |name| !name.starts_with("get-repos-acme-api-pulls-"));
+
+    let pool = memory_db().await;
+    let gh = Github::replay(&dir, &pool).unwrap();
+    let range = DateRange::parse_input("2025-07-01..2025-07-07").unwrap();
+    assert!(scrape_into(&pool, &test_config(), &gh, range, None).await.is_err());
+
+    let mut conn = pool.acquire().await.unwrap();
+    assert!(Scrape::get_latest(&mut conn).await.unwrap().is_none());
+    assert!(Scrape::list_all(&mut conn).await.unwrap().is_empty());
+    let scrape = Scrape::get(&mut conn, &1).await.unwrap();
+    assert_eq!(scrape.status, ScrapeStatus::Failed);
+    assert!(scrape.repo_scrapes.is_empty());
+    let (commits,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM commits").fetch_one(conn.as_mut()).await.unwrap();
+    assert_eq!(commits, 0);
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[tokio::test]
+async fn a_failed_commit_listing_fails_the_scrape() {
+    // `legacy`'s listing isn't recorded, so replaying it fails like an uncached offline request
+    let dir = copy_fixtures("failed-listing", |name| !name.starts_with("get-repos-acme-legacy-commits-"));
+    let pool = memory_db().await;
+    let gh = Github::replay(&dir, &pool).unwrap();
+    let range = DateRange::parse_input("2025-07-01..2025-07-07").unwrap();
+    let Err(error) = scrape_into(&pool, &test_config(), &gh, range, None).await else {
+        panic!("the scrape should fail");
+    };
+    assert!(error.to_string().contains("acme/legacy"), "{error}");
+    assert!(Scrape::get_latest(&mut pool.acquire().await.unwrap()).await.unwrap().is_none());
+
+    // An empty repo has nothing to list, which isn't a failure
+    let url = "https://api.github.com/repos/acme/legacy/commits?sha=main&since=2025-07-01T00%3A00%3A00Z&until=2025-07-08T00%3A00%3A00Z&per_page=100";
+    let empty = RecordedExchange {
+        method: "GET".to_string(),
+        url: url.to_string(),
+        accept: String::new(),
+        status: 409,
+        headers: vec![("content-type".to_string(), "application/json; charset=utf-8".to_string())],
+        body: serde_json::json!({ "message": "Git Repository is empty.", "documentation_url": "https://docs.github.com/rest" }),
+        text: false,
+    };
+    std::fs::write(fixture_path(&dir, "GET", url, ""), serde_json::to_vec(&empty).unwrap()).unwrap();
+    let scrape = scrape_into(&pool, &test_config(), &gh, range, None).await.unwrap();
+    assert_eq!(Scrape::get(&mut pool.acquire().await.unwrap(), &scrape.id).await.unwrap().status, ScrapeStatus::Complete);
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[tokio::test]
+async fn merged_prs_are_found_past_the_first_page() {
+    // An old PR that was commented on this week is listed first, and the PR merged in the
+    // window is on the second page
+    let dir = copy_fixtures("pr-pages", |_| true);
+    let list_url = "https://api.github.com/repos/acme/api/pulls?state=all&base=main&sort=updated&direction=desc&per_page=100";
+    let fixture = fixture_path(&dir, "GET", list_url, "");
+    let mut first: RecordedExchange = serde_json::from_slice(&std::fs::read(&fixture).unwrap()).unwrap();
+    let mut second = first.clone();
+    let next_url = format!("{list_url}&page=2");
+    let mut old_pr = first.body[1].clone();
+    old_pr["updated_at"] = serde_json::json!("2025-07-05T09:00:00Z");
+    first.body = serde_json::json!([old_pr]);
+    first.headers.push(("link".to_string(), format!("<{next_url}>; rel=\"next\"")));
+    std::fs::write(&fixture, serde_json::to_vec(&first).unwrap()).unwrap();
+    second.url = next_url.clone();
+    std::fs::write(fixture_path(&dir, "GET", &next_url, ""), serde_json::to_vec(&second).unwrap()).unwrap();
+
+    let pool = memory_db().await;
+    let gh = Github::replay(&dir, &pool).unwrap();
+    let range = DateRange::parse_input("2025-07-01..2025-07-07").unwrap();
+    let scrape = scrape_into(&pool, &test_config(), &gh, range, None).await.unwrap();
+
+    let mut conn = pool.acquire().await.unwrap();
+    let repos = db::get_repo_stats(&mut conn, scrape.id).await.unwrap();
+    assert_eq!(repos[0].prs, 1);
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[tokio::test]
+async fn a_finished_scrape_is_the_latest() {
+    let (pool, scrape_id) = replayed_scrape().await;
+    let mut conn = pool.acquire().await.unwrap();
+    assert_eq!(Scrape::get(&mut conn, &scrape_id).await.unwrap().status, ScrapeStatus::Complete);
+    assert_eq!(Scrape::get_latest(&mut conn).await.unwrap().map(|s| s.id), Some(scrape_id));
+
+    // A scrape of an earlier window made afterwards is the latest, and listed first
+    let range = DateRange::parse_input("2025-06-01..2025-06-07").unwrap();
+    let backfill = Scrape::create(&mut conn, range.start, range.end, None, ScrapeStatus::Complete).await.unwrap();
+    assert_eq!(Scrape::get_latest(&mut conn).await.unwrap().map(|s| s.id), Some(backfill.id));
+    let listed: Vec<i64> = Scrape::list_all(&mut conn).await.unwrap().iter().map(|s| s.id).collect();
+    assert_eq!(listed, vec![backfill.id, scrape_id]);
+}
+
+#[tokio::test]
+async fn interrupted_scrapes_are_marked_failed() {
+    let (pool, scrape_id) = replayed_scrape().await;
+    let mut conn = pool.acquire().await.unwrap();
+    let range = DateRange::parse_input("2025-07-08..2025-07-14").unwrap();
+    let interrupted = Scrape::create(&mut conn, range.start, range.end, None, ScrapeStatus::Running).await.unwrap();
+
+    assert_eq!(Scrape::fail_interrupted(&mut conn).await.unwrap(), 1);
+    assert_eq!(Scrape::get(&mut conn, &interrupted.id).await.unwrap().status, ScrapeStatus::Failed);
+    assert_eq!(Scrape::get(&mut conn, &scrape_id).await.unwrap().status, ScrapeStatus::Complete);
+    assert_eq!(Scrape::fail_interrupted(&mut conn).await.unwrap(), 0);
+}
+
+#[tokio::test]
+async fn recorded_traffic_replays_without_the_network() {
+    let dir = std::env::temp_dir().join(format!("org-pulse-recording-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let calls = Arc::new(Mutex::new(0));
+    let github = {
+        let calls = calls.clone();
+        service_fn(move |_request: Request<()>| {
+            *calls.lock().unwrap() += 1;
+            async move {
+                let body: ResponseBody = Full::new(Bytes::from("[{\"login\":\"acme\"}]")).map_err(|never| match never {}).boxed();
+                Ok::<_, BoxError>(Response::builder().header("content-type", "application/json").body(body).unwrap())
+            }
+        })
+    };
+    let url = "https://api.github.com/orgs/acme/teams?per_page=100";
+
+    let mut recorder = RecordingLayer::new(RecordingMode::Record, &dir).layer(github.clone());
+    get(&mut recorder, url).await;
+    let recorded: RecordedExchange = serde_json::from_slice(&std::fs::read(fixture_path(&dir, "GET", url, "")).unwrap()).unwrap();
+    assert_eq!(recorded.body, serde_json::json!([{ "login": "acme" }]));
+
+    let mut player = RecordingLayer::new(RecordingMode::Replay, &dir).layer(github);
+    assert_eq!(get(&mut player, url).await, (StatusCode::OK, "[{\"login\":\"acme\"}]".to_string()));
+    assert_eq!(get(&mut player, "https://api.github.com/orgs/acme/repos").await.0, StatusCode::GATEWAY_TIMEOUT);
+    assert_eq!(*calls.lock().unwrap(), 1);
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+async fn get<S>(service: &mut S, url: &str) -> (StatusCode, String)
+where
+    S: Service<Request<()>, Response = Response<ResponseBody>, Error = BoxError>,
+{
+    let response = service.ready().await.unwrap().call(Request::get(url).body(()).unwrap()).await.unwrap();
+    let status = response.status();
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    (status, String::from_utf8(body.to_vec()).unwrap())
+}
+
+#[test]
+fn fixture_names_follow_the_request_path() {
+    let path = fixture_path(&PathBuf::from(FIXTURES), "GET", "https://api.github.com/repos/acme/api/pulls/7/files", "");
+    let name = path.file_name().unwrap().to_str().unwrap();
+    assert!(name.starts_with("get-repos-acme-api-pulls-7-files-") && name.ends_with(".json"));
+    assert!(path.exists());
+}
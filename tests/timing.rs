@@ -0,0 +1,60 @@
+mod common;
+
+use chrono::FixedOffset;
+use org_pulse::timing::{activity_timing, parse_utc_offset};
+
+use common::at;
+
+fn offset_secs(value: &str) -> Option<i32> {
+    parse_utc_offset(value).map(|offset| offset.local_minus_utc())
+}
+
+#[test]
+fn parses_offsets_with_and_without_separators() {
+    assert_eq!(offset_secs("+05:30"), Some(5 * 3600 + 30 * 60));
+    assert_eq!(offset_secs("-0800"), Some(-8 * 3600));
+    assert_eq!(offset_secs("-08:00"), Some(-8 * 3600));
+    assert_eq!(offset_secs("+9"), Some(9 * 3600));
+    assert_eq!(offset_secs(" UTC "), Some(0));
+    assert_eq!(offset_secs("z"), Some(0));
+}
+
+#[test]
+fn rejects_invalid_offsets() {
+    assert_eq!(offset_secs(""), None);
+    assert_eq!(offset_secs("05:30"), None);
+    assert_eq!(offset_secs("EST"), None);
+    assert_eq!(offset_secs("+15:00"), None);
+    assert_eq!(offset_secs("+05:60"), None);
+    assert_eq!(offset_secs("+5:3x"), None);
+}
+
+#[test]
+fn buckets_commits_by_local_hour_and_weekday() {
+    let offset = parse_utc_offset("+05:30").unwrap();
+    let times = [
+        at("2025-07-07T03:00:00Z"), // Monday 08:30, before hours
+        at("2025-07-07T04:00:00Z"), // Monday 09:30
+        at("2025-07-07T12:29:00Z"), // Monday 17:59
+        at("2025-07-07T12:30:00Z"), // Monday 18:00, after hours
+        at("2025-07-11T20:00:00Z"), // Friday in UTC, but Saturday 01:30 locally
+    ];
+    let timing = activity_timing(&times, offset, 9, 18);
+
+    assert_eq!(timing.timezone, "+05:30");
+    assert_eq!(timing.total, 5);
+    assert_eq!(timing.outside_hours, 3);
+    assert_eq!((timing.by_hour[1], timing.by_hour[8], timing.by_hour[9], timing.by_hour[17], timing.by_hour[18]), (1, 1, 1, 1, 1));
+    assert_eq!(timing.by_weekday, [4, 0, 0, 0, 0, 1, 0]);
+}
+
+#[test]
+fn negative_offsets_can_move_commits_to_the_previous_day() {
+    let offset = FixedOffset::west_opt(8 * 3600).unwrap();
+    let timing = activity_timing(&[at("2025-07-07T06:00:00Z")], offset, 9, 18);
+
+    // Monday 06:00 UTC is Sunday 22:00 in UTC-8
+    assert_eq!(timing.by_weekday, [0, 0, 0, 0, 0, 0, 1]);
+    assert_eq!(timing.by_hour[22], 1);
+    assert_eq!(timing.outside_hours, 1);
+}
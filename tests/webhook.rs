@@ -0,0 +1,181 @@
+mod common;
+
+use std::sync::Arc;
+
+use chrono::{Duration, Utc};
+use org_pulse::config::AppConfig;
+use org_pulse::webhook::{sign, verify_signature, WebhookReceiver, LIVE_PROFILE};
+use sqlx::SqlitePool;
+use tokio::{io::{AsyncReadExt, AsyncWriteExt}, net::{TcpListener, TcpStream}};
+
+use common::{memory_db, test_config};
+
+const SECRET: &str = "It's a Secret to Everybody";
+
+async fn serve_receiver(pool: &SqlitePool, cfg: &AppConfig) -> String {
+    let receiver = WebhookReceiver::new(pool.clone(), cfg, SECRET, None).await.expect("receiver should start");
+    let listener = TcpListener::bind("127.0.0.1:0").await.expect("listener should bind");
+    let addr = listener.local_addr().expect("listener has an address").to_string();
+    tokio::spawn(Arc::new(receiver).serve(listener));
+    addr
+}
+
+async fn start_receiver() -> (SqlitePool, String) {
+    let pool = memory_db().await;
+    let addr = serve_receiver(&pool, &test_config()).await;
+    (pool, addr)
+}
+
+async fn deliver(addr: &str, event: &str, body: &[u8], signature: &str) -> (u16, String) {
+    let mut stream = TcpStream::connect(addr).await.expect("receiver should accept connections");
+    let head = format!(
+        "POST /webhook HTTP/1.1\r\nHost: {addr}\r\nContent-Type: application/json\r\nX-GitHub-Event: {event}\r\nX-Hub-Signature-256: {signature}\r\nContent-Length: {}\r\n\r\n",
+        body.len()
+    );
+    stream.write_all(head.as_bytes()).await.unwrap();
+    stream.write_all(body).await.unwrap();
+
+    let mut response = String::new();
+    stream.read_to_string(&mut response).await.unwrap();
+    let status = response.split(' ').nth(1).and_then(|code| code.parse().ok()).expect("response has a status code");
+    let body = response.split_once("\r\n\r\n").map(|(_, body)| body.to_string()).unwrap_or_default();
+    (status, body)
+}
+
+async fn deliver_signed(addr: &str, event: &str, body: &[u8]) -> (u16, String) {
+    deliver(addr, event, body, &sign(SECRET.as_bytes(), body)).await
+}
+
+/// The recorded push with its commits moved into the receiver's live window
+fn recent_push() -> Vec<u8> {
+    let mut push: serde_json::Value = serde_json::from_str(include_str!("fixtures/webhooks/push.json")).unwrap();
+    for (i, commit) in push["commits"].as_array_mut().unwrap().iter_mut().enumerate() {
+        commit["timestamp"] = (Utc::now() - Duration::hours(i as i64 + 1)).to_rfc3339().into();
+    }
+    serde_json::to_vec(&push).unwrap()
+}
+
+#[test]
+fn signatures_match_githubs_example() {
+    // From GitHub's "Validating webhook deliveries" guide
+    assert_eq!(
+        sign(SECRET.as_bytes(), b"Hello, World!"),
+        "sha256=757107ea0eb2509fc211221cce984b8a37570b6d7586c22c46f4379c8b043e17"
+    );
+    assert!(verify_signature(SECRET.as_bytes(), b"Hello, World!", &sign(SECRET.as_bytes(), b"Hello, World!")));
+    assert!(!verify_signature(b"another secret", b"Hello, World!", &sign(SECRET.as_bytes(), b"Hello, World!")));
+    assert!(!verify_signature(SECRET.as_bytes(), b"Hello, World!", "sha1=757107ea"));
+}
+
+#[tokio::test]
+async fn rejects_unsigned_and_tampered_deliveries() {
+    let (pool, addr) = start_receiver().await;
+    let body = recent_push();
+
+    let (status, _) = deliver(&addr, "push", &body, "sha256=00").await;
+    assert_eq!(status, 401);
+    let (status, _) = deliver(&addr, "push", &body, &sign(b"wrong secret", &body)).await;
+    assert_eq!(status, 401);
+
+    let commits: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM commits").fetch_one(&pool).await.unwrap();
+    assert_eq!(commits, 0);
+}
+
+#[tokio::test]
+async fn ingests_push_into_the_live_scrape() {
+    let (pool, addr) = start_receiver().await;
+
+    let (status, _) = deliver_signed(&addr, "push", &recent_push()).await;
+    assert_eq!(status, 200);
+
+    let commits: Vec<(String, String, Option<String>)> = sqlx::query_as(
+        "SELECT c.sha, a.username, c.change_type FROM commits c JOIN contributors a ON a.id = c.author_id ORDER BY c.sha",
+    ).fetch_all(&pool).await.unwrap();
+    assert_eq!(commits.len(), 2);
+    assert_eq!(commits[0], ("4f1c2a9e8d7b6c5a4f3e2d1c0b9a8f7e6d5c4b3a".to_string(), "gagbo".to_string(), Some("feat".to_string())));
+    assert_eq!(commits[1].2, None);
+
+    // The live scrape's counters are rebuilt from the stored commits
+    let repo_scrape: (String, i64) = sqlx::query_as(
+        "SELECT r.name, rs.commits FROM repo_scrapes rs JOIN repos r ON r.id = rs.repo_id",
+    ).fetch_one(&pool).await.unwrap();
+    assert_eq!(repo_scrape, ("app-test-repo".to_string(), 2));
+
+    // Redelivering the same push doesn't duplicate commits
+    let (status, _) = deliver_signed(&addr, "push", &recent_push()).await;
+    assert_eq!(status, 200);
+    let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM commits").fetch_one(&pool).await.unwrap();
+    assert_eq!(count, 2);
+}
+
+#[tokio::test]
+async fn ignores_pushes_outside_the_default_branch() {
+    let (pool, addr) = start_receiver().await;
+    let mut push: serde_json::Value = serde_json::from_slice(&recent_push()).unwrap();
+    push["ref"] = "refs/heads/feature".into();
+    let body = serde_json::to_vec(&push).unwrap();
+
+    let (status, message) = deliver_signed(&addr, "push", &body).await;
+    assert_eq!(status, 202);
+    assert!(message.contains("refs/heads/feature"));
+
+    let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM commits").fetch_one(&pool).await.unwrap();
+    assert_eq!(count, 0);
+}
+
+#[tokio::test]
+async fn ingests_pull_requests_and_reviews() {
+    let (pool, addr) = start_receiver().await;
+
+    let (status, _) = deliver_signed(&addr, "pull_request", include_bytes!("fixtures/webhooks/pull_request.json")).await;
+    assert_eq!(status, 200);
+    let (status, _) = deliver_signed(&addr, "pull_request_review", include_bytes!("fixtures/webhooks/pull_request_review.json")).await;
+    assert_eq!(status, 200);
+
+    let pr: (i64, String, Option<String>, Option<String>) = sqlx::query_as(
+        "SELECT number, title, merged_at, change_type FROM pull_requests",
+    ).fetch_one(&pool).await.unwrap();
+    assert_eq!(pr.0, 2);
+    assert!(pr.2.is_some());
+    assert_eq!(pr.3.as_deref(), Some("fix"));
+
+    let review: (String, String) = sqlx::query_as(
+        "SELECT c.username, r.state FROM pull_request_reviews r JOIN contributors c ON c.id = r.reviewer_id",
+    ).fetch_one(&pool).await.unwrap();
+    assert_eq!(review, ("octocat".to_string(), "approved".to_string()));
+
+    let (status, _) = deliver_signed(&addr, "ping", br#"{"zen": "Keep it logically awesome.", "hook_id": 1}"#).await;
+    assert_eq!(status, 200);
+}
+
+#[tokio::test]
+async fn restarts_pick_up_the_live_scrape() {
+    let pool = memory_db().await;
+    let addr = serve_receiver(&pool, &test_config()).await;
+    let (status, _) = deliver_signed(&addr, "push", &recent_push()).await;
+    assert_eq!(status, 200);
+
+    serve_receiver(&pool, &test_config()).await;
+    let scrapes: Vec<(Option<String>, String)> = sqlx::query_as("SELECT profile, status FROM scrapes")
+        .fetch_all(&pool).await.unwrap();
+    assert_eq!(scrapes, vec![(Some(LIVE_PROFILE.to_string()), "complete".to_string())]);
+    let repo_scrapes: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM repo_scrapes").fetch_one(&pool).await.unwrap();
+    assert_eq!(repo_scrapes, 1);
+}
+
+#[tokio::test]
+async fn ignores_deliveries_for_repos_scrapes_skip() {
+    let pool = memory_db().await;
+    let cfg = AppConfig {
+        ignored_org_pattern: "^gagbo$".to_string(),
+        ..test_config()
+    };
+    let addr = serve_receiver(&pool, &cfg).await;
+
+    let (status, message) = deliver_signed(&addr, "push", &recent_push()).await;
+    assert_eq!(status, 202);
+    assert!(message.contains("gagbo/app-test-repo"));
+
+    let commits: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM commits").fetch_one(&pool).await.unwrap();
+    assert_eq!(commits, 0);
+}
@@ -0,0 +1,67 @@
+use org_pulse::github::WorkflowRunPage;
+use org_pulse::stats::CiHealth;
+
+fn recorded_runs() -> WorkflowRunPage {
+    serde_json::from_str(include_str!("fixtures/workflow_runs.json")).expect("fixture should parse")
+}
+
+#[test]
+fn parses_recorded_workflow_runs() {
+    let page = recorded_runs();
+
+    assert_eq!(page.total_count, 5);
+    assert_eq!(page.workflow_runs.len(), 5);
+    assert_eq!(page.workflow_runs[2].run_attempt, 2);
+    // Runs without an attempt count are first attempts
+    assert_eq!(page.workflow_runs[4].run_attempt, 1);
+    assert_eq!(page.workflow_runs[4].head_branch, None);
+    assert_eq!(page.workflow_runs[4].conclusion, None);
+}
+
+#[test]
+fn computes_ci_health_from_recorded_runs() {
+    let page = recorded_runs();
+    let health = CiHealth::from_runs(page.workflow_runs.iter().map(|run| {
+        let duration_secs = run.run_started_at
+            .map(|started| (run.updated_at - started).num_seconds() as f64);
+        (run.conclusion.as_deref(), run.run_attempt as i64, duration_secs)
+    }));
+
+    assert_eq!(health.runs, 5);
+    // Cancelled and in-progress runs don't count towards the success rate
+    assert_eq!(health.decided_runs, 3);
+    assert_eq!(health.successful_runs, 2);
+    assert_eq!(health.flaky_reruns, 1);
+    assert_eq!(health.success_rate(), Some(2.0 / 3.0));
+    // Completed runs took 8.5, 4, 14 and 1 minutes
+    assert_eq!(health.median_duration_secs, Some(375.0));
+}
+
+#[test]
+fn ci_health_counts_reruns_and_only_finished_durations() {
+    let health = CiHealth::from_runs([
+        (Some("success"), 1, Some(60.0)),
+        (Some("success"), 3, Some(120.0)),
+        // Failures aren't flaky, however many attempts they took
+        (Some("failure"), 2, Some(30.0)),
+        (Some("timed_out"), 1, None),
+        (Some("cancelled"), 1, Some(5.0)),
+        // Still running, so its duration so far is left out
+        (None, 1, Some(999.0)),
+    ]);
+
+    assert_eq!(health.runs, 6);
+    assert_eq!(health.decided_runs, 4);
+    assert_eq!(health.successful_runs, 2);
+    assert_eq!(health.flaky_reruns, 1);
+    assert_eq!(health.success_rate(), Some(0.5));
+    assert_eq!(health.median_duration_secs, Some(45.0));
+}
+
+#[test]
+fn ci_health_without_runs_has_no_rates() {
+    let health = CiHealth::from_runs([]);
+    assert_eq!(health.runs, 0);
+    assert_eq!(health.success_rate(), None);
+    assert_eq!(health.median_duration_secs, None);
+}